@@ -29,17 +29,38 @@ fn benchmark(c: &mut Criterion) {
     }
     group.finish();
 
+    // consecutive Fibonacci numbers are the worst case for Euclid's algorithm
+    let mut fib = vec![(1i64, 2i64)];
+    while fib.len() < 40 {
+        let (a, b) = fib[fib.len() - 1];
+        fib.push((b, a + b));
+    }
+
+    let mut group = c.benchmark_group("GCD");
+    for (a, b) in fib.iter().step_by(4) {
+        group.bench_with_input(BenchmarkId::new("Recursive", b), &(*a, *b), |bn, (a, b)| {
+            bn.iter(|| recursion_lib::gcd::recursive(black_box(*a), black_box(*b)))
+        });
+        group.bench_with_input(BenchmarkId::new("Iterative", b), &(*a, *b), |bn, (a, b)| {
+            bn.iter(|| recursion_lib::gcd::iterative(black_box(*a), black_box(*b)))
+        });
+        group.bench_with_input(BenchmarkId::new("Binary", b), &(*a, *b), |bn, (a, b)| {
+            bn.iter(|| recursion_lib::gcd::binary(black_box(*a), black_box(*b)))
+        });
+    }
+    group.finish();
+
     let mut group = c.benchmark_group("LCD");
-    group.bench_with_input(BenchmarkId::new("Recursive", 1), &1, |b, i| {
-        b.iter(|| {
-            recursion_lib::lcd::recursive(black_box(recursion_lib::lcd::Expr::new(1, 20, 22)))
-        })
-    });
-    group.bench_with_input(BenchmarkId::new("Iterative", 1), &1, |b, i| {
-        b.iter(|| {
-            recursion_lib::lcd::iterative(black_box(recursion_lib::lcd::Expr::new(1, 20, 22)))
-        })
-    });
+    for (n, d) in fib.iter().step_by(4) {
+        // scale both sides so there is a common factor to divide out
+        let input = recursion_lib::lcd::Expr::new(0, n * 6, d * 6);
+        group.bench_with_input(BenchmarkId::new("Recursive", d), &input, |b, e| {
+            b.iter(|| recursion_lib::lcd::recursive(black_box(*e)))
+        });
+        group.bench_with_input(BenchmarkId::new("Iterative", d), &input, |b, e| {
+            b.iter(|| recursion_lib::lcd::iterative(black_box(*e)))
+        });
+    }
 
     group.finish();
 }
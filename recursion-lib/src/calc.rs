@@ -0,0 +1,740 @@
+////////////////////////////////////////////////////////////////////////////////
+
+use crate::expo;
+use crate::lcd::{Expr, LcdError, ParseExprError, ParseExprErrorKind};
+
+////////////////////////////////////////////////////////////////////////////////
+
+#[derive(Debug, PartialEq, Eq, Copy, Clone, Default)]
+/// A range of byte offsets into the input, `start..end`.
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
+}
+
+impl Span {
+    /// The part of `src` this span covers.
+    pub fn slice<'a>(&self, src: &'a str) -> &'a str {
+        &src[self.start..self.end]
+    }
+
+    fn to(self, other: Span) -> Span {
+        Span {
+            start: self.start,
+            end: other.end,
+        }
+    }
+}
+
+impl std::fmt::Display for Span {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_fmt(format_args!("{}..{}", self.start, self.end))
+    }
+}
+
+//---------------------------------------------------------------------------//
+
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+/// What went wrong while parsing or evaluating a calculation.
+pub enum CalcErrorKind {
+    /// Found a character that doesn't fit at this point.
+    UnexpectedChar(char),
+    /// The input stopped before the calculation was complete.
+    UnexpectedEnd,
+    /// Dividing by zero, either with `/`, a zero denominator or a negative
+    /// power of zero.
+    DivideByZero,
+    /// A number or intermediate value doesn't fit in an `i64` fraction.
+    Overflow,
+    /// Only whole numbers can be used as powers.
+    FractionalExponent,
+}
+
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+/// An error from parsing or evaluating a calculation, along with the part of
+/// the input responsible.
+pub struct CalcError {
+    /// What went wrong.
+    pub kind: CalcErrorKind,
+    /// Where it went wrong.
+    pub span: Span,
+}
+
+//---------------------------------------------------------------------------//
+
+impl std::fmt::Display for CalcError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.kind {
+            CalcErrorKind::UnexpectedChar(c) => {
+                f.write_fmt(format_args!("unexpected {:?} at {}", c, self.span))
+            }
+            CalcErrorKind::UnexpectedEnd => {
+                f.write_fmt(format_args!("unexpected end of input at {}", self.span))
+            }
+            CalcErrorKind::DivideByZero => {
+                f.write_fmt(format_args!("division by zero at {}", self.span))
+            }
+            CalcErrorKind::Overflow => {
+                f.write_fmt(format_args!("number too large at {}", self.span))
+            }
+            CalcErrorKind::FractionalExponent => {
+                f.write_fmt(format_args!("exponent is not an integer at {}", self.span))
+            }
+        }
+    }
+}
+
+impl std::error::Error for CalcError {}
+
+////////////////////////////////////////////////////////////////////////////////
+
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+/// A binary operator.
+pub enum Op {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    /// Raising to a whole number power.
+    Pow,
+}
+
+impl std::fmt::Display for Op {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Op::Add => "+",
+            Op::Sub => "-",
+            Op::Mul => "*",
+            Op::Div => "/",
+            Op::Pow => "^",
+        })
+    }
+}
+
+//---------------------------------------------------------------------------//
+
+#[derive(Debug, PartialEq, Clone)]
+/// The kinds of node in a parsed calculation.
+pub enum Node {
+    /// A literal, already simplified.
+    Number(Expr),
+    /// Unary minus.
+    Neg(Box<Ast>),
+    /// `lhs op rhs`
+    Binary(Op, Box<Ast>, Box<Ast>),
+}
+
+#[derive(Debug, PartialEq, Clone)]
+/// A parsed calculation, where every node remembers the part of the input it
+/// came from.
+pub struct Ast {
+    pub node: Node,
+    /// Includes any brackets around the node.
+    pub span: Span,
+}
+
+//---------------------------------------------------------------------------//
+
+impl std::fmt::Display for Ast {
+    /// Writes the calculation back out with every operation bracketed, so the
+    /// order it is evaluated in is clear.
+    /// (i.e. `1 + 2 * 3` -> `(1 + (2 * 3))` )
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.node {
+            Node::Number(e) => {
+                // the shortest form that parses back to the same value
+                if e.n == 0 {
+                    f.write_fmt(format_args!("{}", e.c))
+                } else if e.c == 0 {
+                    f.write_fmt(format_args!("{}/{}", e.n, e.d))
+                } else {
+                    f.write_fmt(format_args!("{}({}/{})", e.c, e.n, e.d))
+                }
+            }
+            Node::Neg(x) => f.write_fmt(format_args!("(-{})", x)),
+            Node::Binary(op, lhs, rhs) => f.write_fmt(format_args!("({} {} {})", lhs, op, rhs)),
+        }
+    }
+}
+
+//---------------------------------------------------------------------------//
+
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+/// One operation from evaluating a calculation: the part of the input it
+/// covers and what that part works out to.
+pub struct Step {
+    pub span: Span,
+    pub value: Expr,
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+#[derive(Debug, PartialEq, Clone)]
+enum Token {
+    Number(Expr),
+    /// One of `+ - * / ^ ( )`.
+    Symbol(char),
+}
+
+/// Splits the input into numbers and symbols.
+/// A `(` straight after a number's digits belongs to the number, so `2(1/4)`
+/// is the mixed number 2 1/4 rather than a multiplication.
+fn tokenize(src: &str) -> Result<Vec<(Token, Span)>, CalcError> {
+    let mut res = vec![];
+    let mut chars = src.char_indices().peekable();
+
+    while let Some(&(start, c)) = chars.peek() {
+        if c.is_whitespace() {
+            chars.next();
+        } else if "+-*/^()".contains(c) {
+            chars.next();
+            let span = Span {
+                start,
+                end: start + 1,
+            };
+            res.push((Token::Symbol(c), span));
+        } else if c.is_ascii_digit() {
+            // find where the literal ends, then let `Expr::from_str` read it
+            let mut end = start;
+            let mut bracket = false;
+            while let Some(&(i, c)) = chars.peek() {
+                let part_of = if bracket {
+                    true
+                } else if c == '(' {
+                    bracket = true;
+                    true
+                } else {
+                    c.is_ascii_digit() || c == '.'
+                };
+                if !part_of {
+                    break;
+                }
+                chars.next();
+                end = i + c.len_utf8();
+                if bracket && c == ')' {
+                    break;
+                }
+            }
+
+            let span = Span { start, end };
+            let value = span
+                .slice(src)
+                .parse::<Expr>()
+                .map_err(|e| literal(src, span, e))?;
+            res.push((Token::Number(value), span));
+        } else {
+            let span = Span {
+                start,
+                end: start + c.len_utf8(),
+            };
+            return Err(CalcError {
+                kind: CalcErrorKind::UnexpectedChar(c),
+                span,
+            });
+        }
+    }
+
+    Ok(res)
+}
+
+/// Moves an error from parsing a literal on its own into the whole input.
+fn literal(src: &str, span: Span, e: ParseExprError) -> CalcError {
+    let pos = span.start + e.pos;
+    let (kind, span) = match e.kind {
+        ParseExprErrorKind::UnexpectedChar(c) => (
+            CalcErrorKind::UnexpectedChar(c),
+            Span {
+                start: pos,
+                end: pos + c.len_utf8(),
+            },
+        ),
+        // the literal only runs out early if the input does
+        ParseExprErrorKind::UnexpectedEnd => (
+            CalcErrorKind::UnexpectedEnd,
+            Span {
+                start: src.len(),
+                end: src.len(),
+            },
+        ),
+        ParseExprErrorKind::ZeroDenominator => (CalcErrorKind::DivideByZero, span),
+        ParseExprErrorKind::Overflow => (CalcErrorKind::Overflow, span),
+    };
+    CalcError { kind, span }
+}
+
+//---------------------------------------------------------------------------//
+
+/// Walks through the tokens, one grammar rule per method:
+///
+/// ```text
+/// sum     = product (('+' | '-') product)*
+/// product = unary (('*' | '/') unary)*
+/// unary   = '-' unary | power
+/// power   = primary ('^' unary)?
+/// primary = number | '(' sum ')'
+/// ```
+struct Parser<'a> {
+    src: &'a str,
+    tokens: Vec<(Token, Span)>,
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos).map(|(t, _)| t)
+    }
+
+    /// Consumes the next token if it is one of `symbols`.
+    fn symbol(&mut self, symbols: &str) -> Option<(char, Span)> {
+        match self.tokens.get(self.pos) {
+            Some(&(Token::Symbol(c), span)) if symbols.contains(c) => {
+                self.pos += 1;
+                Some((c, span))
+            }
+            _ => None,
+        }
+    }
+
+    /// Complains about whatever is at the current position.
+    fn unexpected(&self) -> CalcError {
+        match self.tokens.get(self.pos) {
+            Some((_, span)) => {
+                let c = span.slice(self.src).chars().next().unwrap_or_default();
+                CalcError {
+                    kind: CalcErrorKind::UnexpectedChar(c),
+                    span: *span,
+                }
+            }
+            None => CalcError {
+                kind: CalcErrorKind::UnexpectedEnd,
+                span: Span {
+                    start: self.src.len(),
+                    end: self.src.len(),
+                },
+            },
+        }
+    }
+
+    fn sum(&mut self) -> Result<Ast, CalcError> {
+        let mut lhs = self.product()?;
+        while let Some((c, _)) = self.symbol("+-") {
+            let rhs = self.product()?;
+            let op = if c == '+' { Op::Add } else { Op::Sub };
+            lhs = binary(op, lhs, rhs);
+        }
+        Ok(lhs)
+    }
+
+    fn product(&mut self) -> Result<Ast, CalcError> {
+        let mut lhs = self.unary()?;
+        while let Some((c, _)) = self.symbol("*/") {
+            let rhs = self.unary()?;
+            let op = if c == '*' { Op::Mul } else { Op::Div };
+            lhs = binary(op, lhs, rhs);
+        }
+        Ok(lhs)
+    }
+
+    fn unary(&mut self) -> Result<Ast, CalcError> {
+        if let Some((_, span)) = self.symbol("-") {
+            let x = self.unary()?;
+            Ok(Ast {
+                span: span.to(x.span),
+                node: Node::Neg(Box::new(x)),
+            })
+        } else {
+            self.power()
+        }
+    }
+
+    fn power(&mut self) -> Result<Ast, CalcError> {
+        let base = self.primary()?;
+        if self.symbol("^").is_some() {
+            // right associative, and the exponent can be negative
+            let exponent = self.unary()?;
+            Ok(binary(Op::Pow, base, exponent))
+        } else {
+            Ok(base)
+        }
+    }
+
+    fn primary(&mut self) -> Result<Ast, CalcError> {
+        if let Some((Token::Number(e), span)) = self.tokens.get(self.pos) {
+            let ast = Ast {
+                node: Node::Number(*e),
+                span: *span,
+            };
+            self.pos += 1;
+            Ok(ast)
+        } else if let Some((_, open)) = self.symbol("(") {
+            let mut ast = self.sum()?;
+            let (_, close) = self.symbol(")").ok_or_else(|| self.unexpected())?;
+            ast.span = open.to(close);
+            Ok(ast)
+        } else {
+            Err(self.unexpected())
+        }
+    }
+}
+
+fn binary(op: Op, lhs: Ast, rhs: Ast) -> Ast {
+    Ast {
+        span: lhs.span.to(rhs.span),
+        node: Node::Binary(op, Box::new(lhs), Box::new(rhs)),
+    }
+}
+
+//---------------------------------------------------------------------------//
+
+/// Parses a calculation over fractions into its syntax tree.
+/// Numbers can be written any way [`Expr`] parses them, except with spaces.
+/// There is no implicit multiplication, `2(1/4)` is a mixed number.
+/// (i.e. `1 + 2 * 3` -> `(1 + (2 * 3))` )
+///
+/// Inputs:
+/// - `src: &str`
+///
+/// Outputs: `Result<Ast, CalcError>`
+/// The syntax tree, or what went wrong and where.
+///
+/// Time complexity: O(n)
+pub fn parse(src: &str) -> Result<Ast, CalcError> {
+    let mut p = Parser {
+        src,
+        tokens: tokenize(src)?,
+        pos: 0,
+    };
+
+    let ast = p.sum()?;
+    if p.peek().is_some() {
+        return Err(p.unexpected());
+    }
+    Ok(ast)
+}
+
+//---------------------------------------------------------------------------//
+
+/// Applies one operator to two values that have already been worked out.
+/// `span` is the whole operation, `rhs` just its right hand side.
+fn apply(op: Op, lhs: &Expr, rhs: &Expr, span: Span, rhs_span: Span) -> Result<Expr, CalcError> {
+    let error = |e: LcdError| CalcError {
+        kind: match e {
+            LcdError::ZeroDenominator => CalcErrorKind::DivideByZero,
+            // arithmetic on simplified values can't fail any other way
+            _ => CalcErrorKind::Overflow,
+        },
+        span,
+    };
+
+    match op {
+        Op::Add => lhs.checked_add(rhs).map_err(error),
+        Op::Sub => lhs.checked_sub(rhs).map_err(error),
+        Op::Mul => lhs.checked_mul(rhs).map_err(error),
+        Op::Div => lhs.checked_div(rhs).map_err(error),
+        Op::Pow => {
+            // values are always simplified, so whole numbers have n == 0
+            if rhs.n != 0 {
+                return Err(CalcError {
+                    kind: CalcErrorKind::FractionalExponent,
+                    span: rhs_span,
+                });
+            }
+            let e = u32::try_from(rhs.c.unsigned_abs()).map_err(|_| CalcError {
+                kind: CalcErrorKind::Overflow,
+                span,
+            })?;
+
+            let one = Expr::new(1, 0, 1);
+            let res = expo::iterative_with(lhs, e, one, &|a: &Expr, b: &Expr| a.checked_mul(b))
+                .map_err(error)?;
+
+            // x^-e = 1 / x^e
+            if rhs.c < 0 {
+                one.checked_div(&res).map_err(error)
+            } else {
+                Ok(res)
+            }
+        }
+    }
+}
+
+//---------------------------------------------------------------------------//
+
+/// Recursively evaluates a calculation exactly.
+/// (i.e. `(1/3 + 2(1/4)) * 3^-2` -> 0(31/108) )
+///
+/// Inputs:
+/// - `ast: &Ast`
+///
+/// Outputs: `Result<Expr, CalcError>`
+/// The simplified value, or which operation failed.
+///
+/// Time complexity: O(n)
+pub fn recursive(ast: &Ast) -> Result<Expr, CalcError> {
+    match &ast.node {
+        // terminating case, a literal is already a value
+        Node::Number(e) => Ok(*e),
+        // general cases, work out the operands then combine them
+        Node::Neg(x) => recursive(x)?.checked_neg().map_err(|_| CalcError {
+            kind: CalcErrorKind::Overflow,
+            span: ast.span,
+        }),
+        Node::Binary(op, lhs, rhs) => {
+            apply(*op, &recursive(lhs)?, &recursive(rhs)?, ast.span, rhs.span)
+        }
+    }
+}
+
+//---------------------------------------------------------------------------//
+
+/// Iteratively evaluates a calculation exactly.
+/// (i.e. `(1/3 + 2(1/4)) * 3^-2` -> 0(31/108) )
+///
+/// Inputs:
+/// - `ast: &Ast`
+///
+/// Outputs: `Result<Expr, CalcError>`
+/// The simplified value, or which operation failed.
+///
+/// Time complexity: O(n)
+pub fn iterative(ast: &Ast) -> Result<Expr, CalcError> {
+    iterative_traced(ast).map(|(e, _)| e)
+}
+
+//---------------------------------------------------------------------------//
+
+/// Iteratively evaluates a calculation exactly, recording each operation.
+/// The steps come in the order they are worked out, so every step only
+/// depends on the ones before it, and the last step is the whole calculation.
+///
+/// Inputs:
+/// - `ast: &Ast`
+///
+/// Outputs: `Result<(Expr, Vec<Step>), CalcError>`
+/// The simplified value and how it was reached, or which operation failed.
+///
+/// Time complexity: O(n)
+pub fn iterative_traced(ast: &Ast) -> Result<(Expr, Vec<Step>), CalcError> {
+    enum Visit<'a> {
+        /// Work out the operands first.
+        Enter(&'a Ast),
+        /// The operands are on the stack, combine them.
+        Exit(&'a Ast),
+    }
+
+    let mut todo = vec![Visit::Enter(ast)];
+    let mut values: Vec<Expr> = vec![];
+    let mut steps = vec![];
+
+    while let Some(visit) = todo.pop() {
+        match visit {
+            Visit::Enter(a) => match &a.node {
+                Node::Number(e) => values.push(*e),
+                Node::Neg(x) => {
+                    todo.push(Visit::Exit(a));
+                    todo.push(Visit::Enter(x));
+                }
+                Node::Binary(_, lhs, rhs) => {
+                    // pushed backwards so the left side is worked out first
+                    todo.push(Visit::Exit(a));
+                    todo.push(Visit::Enter(rhs));
+                    todo.push(Visit::Enter(lhs));
+                }
+            },
+            Visit::Exit(a) => {
+                let value = match &a.node {
+                    Node::Neg(_) => {
+                        let x = values.pop().expect("operand was evaluated");
+                        x.checked_neg().map_err(|_| CalcError {
+                            kind: CalcErrorKind::Overflow,
+                            span: a.span,
+                        })?
+                    }
+                    Node::Binary(op, _, rhs) => {
+                        let r = values.pop().expect("operand was evaluated");
+                        let l = values.pop().expect("operand was evaluated");
+                        apply(*op, &l, &r, a.span, rhs.span)?
+                    }
+                    Node::Number(_) => unreachable!("numbers are never exited"),
+                };
+                values.push(value);
+                steps.push(Step {
+                    span: a.span,
+                    value,
+                });
+            }
+        }
+    }
+
+    Ok((values.pop().expect("the root was evaluated"), steps))
+}
+
+//---------------------------------------------------------------------------//
+
+/// Parses and evaluates a calculation. See [`parse`] and [`iterative`].
+/// (i.e. `1/2 + 1/3` -> 0(5/6) )
+pub fn evaluate(src: &str) -> Result<Expr, CalcError> {
+    iterative(&parse(src)?)
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use super::{CalcError, CalcErrorKind, Span};
+    use crate::lcd::Expr;
+
+    fn span(start: usize, end: usize) -> Span {
+        Span { start, end }
+    }
+
+    fn error(kind: CalcErrorKind, start: usize, end: usize) -> CalcError {
+        CalcError {
+            kind,
+            span: span(start, end),
+        }
+    }
+
+    #[test]
+    fn parse() {
+        let show = |src: &str| super::parse(src).map(|ast| ast.to_string());
+
+        assert_eq!(show("1 + 2 * 3"), Ok("(1 + (2 * 3))".to_string()));
+        assert_eq!(show("(1 + 2) * 3"), Ok("((1 + 2) * 3)".to_string()));
+        assert_eq!(show("1 - 2 - 3"), Ok("((1 - 2) - 3)".to_string()));
+        assert_eq!(show("2^3^2"), Ok("(2 ^ (3 ^ 2))".to_string()));
+        assert_eq!(show("-3^2"), Ok("(-(3 ^ 2))".to_string()));
+        assert_eq!(show("3^-2"), Ok("(3 ^ (-2))".to_string()));
+        assert_eq!(show("2(1/4) + 0.5"), Ok("(2(1/4) + 1/2)".to_string()));
+        assert_eq!(show("0.(3)"), Ok("1/3".to_string()));
+        assert_eq!(show("--1"), Ok("(-(-1))".to_string()));
+
+        // spans cover brackets and whole operations
+        let ast = super::parse("(1/3 + 2(1/4)) * 3^-2").unwrap();
+        assert_eq!(ast.span, span(0, 21));
+        match ast.node {
+            super::Node::Binary(super::Op::Mul, lhs, rhs) => {
+                assert_eq!(lhs.span, span(0, 14));
+                assert_eq!(rhs.span, span(17, 21));
+            }
+            node => panic!("{:?}", node),
+        }
+
+        assert_eq!(
+            super::parse("1 + * 2"),
+            Err(error(CalcErrorKind::UnexpectedChar('*'), 4, 5))
+        );
+        assert_eq!(
+            super::parse("(1 + 2"),
+            Err(error(CalcErrorKind::UnexpectedEnd, 6, 6))
+        );
+        assert_eq!(
+            super::parse("1 + 2)"),
+            Err(error(CalcErrorKind::UnexpectedChar(')'), 5, 6))
+        );
+        assert_eq!(
+            super::parse("1 2"),
+            Err(error(CalcErrorKind::UnexpectedChar('2'), 2, 3))
+        );
+        assert_eq!(
+            super::parse("1 % 2"),
+            Err(error(CalcErrorKind::UnexpectedChar('%'), 2, 3))
+        );
+        assert_eq!(
+            super::parse(""),
+            Err(error(CalcErrorKind::UnexpectedEnd, 0, 0))
+        );
+        assert_eq!(
+            super::parse("1 + 2(3+4)"),
+            Err(error(CalcErrorKind::UnexpectedChar('+'), 7, 8))
+        );
+        assert_eq!(
+            super::parse("2(1/"),
+            Err(error(CalcErrorKind::UnexpectedEnd, 4, 4))
+        );
+        assert_eq!(
+            super::parse("1 + 2(1/0)"),
+            Err(error(CalcErrorKind::DivideByZero, 4, 10))
+        );
+        assert_eq!(
+            super::parse("99999999999999999999"),
+            Err(error(CalcErrorKind::Overflow, 0, 20))
+        );
+    }
+
+    #[test]
+    fn iterative() {
+        common(super::iterative);
+    }
+
+    #[test]
+    fn recursive() {
+        common(super::recursive);
+    }
+
+    fn common(func: fn(&super::Ast) -> Result<Expr, CalcError>) {
+        let eval = |src: &str| func(&super::parse(src).unwrap());
+
+        assert_eq!(eval("(1/3 + 2(1/4)) * 3^-2"), Ok(Expr::new(0, 31, 108)));
+        assert_eq!(eval("1/2 + 1/3"), Ok(Expr::new(0, 5, 6)));
+        assert_eq!(eval("1 + 2 * 3"), Ok(Expr::new(7, 0, 1)));
+        assert_eq!(eval("2^3^2"), Ok(Expr::new(512, 0, 1)));
+        assert_eq!(eval("-3^2"), Ok(Expr::new(-9, 0, 1)));
+        assert_eq!(eval("(-2/3)^3"), Ok(Expr::new(0, -8, 27)));
+        assert_eq!(eval("(2/3)^-2"), Ok(Expr::new(2, 1, 4)));
+        assert_eq!(eval("7^0"), Ok(Expr::new(1, 0, 1)));
+        assert_eq!(eval("0^0"), Ok(Expr::new(1, 0, 1)));
+        assert_eq!(eval("0.(3) * 3"), Ok(Expr::new(1, 0, 1)));
+        assert_eq!(eval("1 - 2(1/2)"), Ok(Expr::new(-1, 1, 2)));
+        assert_eq!(eval("1^4294967295"), Ok(Expr::new(1, 0, 1)));
+
+        assert_eq!(
+            eval("1 + 1/(2 - 2)"),
+            Err(error(CalcErrorKind::DivideByZero, 4, 13))
+        );
+        assert_eq!(eval("0^-1"), Err(error(CalcErrorKind::DivideByZero, 0, 4)));
+        assert_eq!(
+            eval("2^(1/2)"),
+            Err(error(CalcErrorKind::FractionalExponent, 2, 7))
+        );
+        assert_eq!(eval("2^63"), Err(error(CalcErrorKind::Overflow, 0, 4)));
+        assert_eq!(
+            eval("1^4294967296"),
+            Err(error(CalcErrorKind::Overflow, 0, 12))
+        );
+        assert_eq!(
+            eval("3 * (1/3)^40"),
+            Err(error(CalcErrorKind::Overflow, 4, 12))
+        );
+    }
+
+    #[test]
+    fn iterative_traced() {
+        let src = "(1/3 + 2(1/4)) * 3^-2";
+        let (value, steps) = super::iterative_traced(&super::parse(src).unwrap()).unwrap();
+        assert_eq!(value, Expr::new(0, 31, 108));
+
+        let shown = steps
+            .iter()
+            .map(|s| format!("{} = {}", s.span.slice(src), s.value))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            shown,
+            [
+                "1/3 = 0(1/3)",
+                "(1/3 + 2(1/4)) = 2(7/12)",
+                "-2 = -2(0/1)",
+                "3^-2 = 0(1/9)",
+                "(1/3 + 2(1/4)) * 3^-2 = 0(31/108)",
+            ]
+        );
+
+        assert_eq!(super::evaluate(src), Ok(Expr::new(0, 31, 108)));
+        assert_eq!(
+            super::evaluate("1 +"),
+            Err(error(CalcErrorKind::UnexpectedEnd, 3, 3))
+        );
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
@@ -0,0 +1,453 @@
+////////////////////////////////////////////////////////////////////////////////
+
+use tailcall::tailcall;
+
+use crate::lcd::{self, Expr, LcdError};
+
+////////////////////////////////////////////////////////////////////////////////
+
+/// Recursively expands an expression into a continued fraction.
+/// (i.e. 45/16 -> 2 + 1/(1 + 1/(4 + 1/3)) -> [2; 1, 4, 3] )
+///
+/// Inputs:
+/// - `e: Expr` The expression to expand.
+///
+/// Outputs: `Vec<i64>`
+/// The terms of the continued fraction.
+/// The first term may be negative, every other term is positive.
+/// Undefined expressions (zero denominator) have no terms.
+///
+/// Panics if the first term doesn't fit in an `i64`.
+///
+/// Time complexity: O(log(denominator))
+pub fn recursive(e: Expr) -> Vec<i64> {
+    #[tailcall]
+    fn inner(n: i128, d: i128, terms: &mut Vec<i64>) {
+        // n/d = a + r/d = a + 1/(d/r)
+        let a = n.div_euclid(d);
+        let r = n.rem_euclid(d);
+        terms.push(a.try_into().expect("term does not fit in an i64"));
+
+        if r != 0 {
+            // keep expanding the leftover fraction
+            inner(d, r, terms)
+        }
+    }
+
+    let (n, d) = e.to_improper_wide();
+    let mut terms = vec![];
+
+    if d != 0 {
+        inner(n, d, &mut terms);
+    }
+
+    terms
+}
+
+//---------------------------------------------------------------------------//
+
+/// Iteratively expands an expression into a continued fraction.
+/// (i.e. 45/16 -> 2 + 1/(1 + 1/(4 + 1/3)) -> [2; 1, 4, 3] )
+///
+/// Inputs:
+/// - `e: Expr` The expression to expand.
+///
+/// Outputs: `Vec<i64>`
+/// The terms of the continued fraction.
+/// The first term may be negative, every other term is positive.
+/// Undefined expressions (zero denominator) have no terms.
+///
+/// Panics if the first term doesn't fit in an `i64`.
+///
+/// Time complexity: O(log(denominator))
+pub fn iterative(e: Expr) -> Vec<i64> {
+    let (mut n, mut d) = e.to_improper_wide();
+    let mut terms = vec![];
+
+    // this is just Euclid's algorithm, keeping the quotients
+    while d != 0 {
+        // n/d = a + r/d = a + 1/(d/r)
+        let a = n.div_euclid(d);
+        let r = n.rem_euclid(d);
+        terms.push(a.try_into().expect("term does not fit in an i64"));
+
+        n = d;
+        d = r;
+    }
+
+    terms
+}
+
+//---------------------------------------------------------------------------//
+
+/// Recursively expands a float into a continued fraction.
+/// (i.e. 3.14159... -> [3; 7, 15, 1, 292, ...] )
+///
+/// Inputs:
+/// - `x: f64` The value to expand.
+/// - `max_terms: usize` The most terms to produce, since irrational values
+///   never stop.
+///
+/// Outputs: `Vec<i64>`
+/// The terms of the continued fraction.
+/// Later terms pick up rounding errors, so only the first dozen or so should
+/// be trusted.
+/// Stops once what's left over is no bigger than the rounding error picked up
+/// so far, or early if a term doesn't fit in an `i64`, and non-finite values
+/// have no terms.
+///
+/// Time complexity: O(max_terms)
+pub fn recursive_f64(x: f64, max_terms: usize) -> Vec<i64> {
+    #[tailcall]
+    fn inner(x: f64, noise: f64, remaining: usize, terms: &mut Vec<i64>) {
+        let a = x.floor();
+
+        if remaining > 0 && x.is_finite() && a >= i64::MIN as f64 && a < i64::MAX as f64 {
+            // x = a + f = a + 1/(1/f)
+            terms.push(a as i64);
+
+            let f = x - a;
+            if f > noise {
+                // keep expanding the leftover fraction
+                inner(1.0 / f, next_noise(noise, f), remaining - 1, terms)
+            }
+        }
+    }
+
+    let mut terms = vec![];
+    inner(x, x.abs() * f64::EPSILON, max_terms, &mut terms);
+    terms
+}
+
+//---------------------------------------------------------------------------//
+
+/// Iteratively expands a float into a continued fraction.
+/// (i.e. 3.14159... -> [3; 7, 15, 1, 292, ...] )
+///
+/// Inputs:
+/// - `x: f64` The value to expand.
+/// - `max_terms: usize` The most terms to produce, since irrational values
+///   never stop.
+///
+/// Outputs: `Vec<i64>`
+/// The terms of the continued fraction.
+/// Later terms pick up rounding errors, so only the first dozen or so should
+/// be trusted.
+/// Stops once what's left over is no bigger than the rounding error picked up
+/// so far, or early if a term doesn't fit in an `i64`, and non-finite values
+/// have no terms.
+///
+/// Time complexity: O(max_terms)
+pub fn iterative_f64(mut x: f64, max_terms: usize) -> Vec<i64> {
+    let mut terms = vec![];
+    // how far x might be from the value it stands for
+    let mut noise = x.abs() * f64::EPSILON;
+
+    while terms.len() < max_terms && x.is_finite() {
+        let a = x.floor();
+        if a < i64::MIN as f64 || a >= i64::MAX as f64 {
+            break;
+        }
+
+        // x = a + f = a + 1/(1/f)
+        terms.push(a as i64);
+
+        let f = x - a;
+        if f <= noise {
+            break;
+        }
+        noise = next_noise(noise, f);
+        x = 1.0 / f;
+    }
+
+    terms
+}
+
+/// Works out how far `1/f` might be from the value it stands for, given how far
+/// `x` might have been, where `f` is the fractional part of `x`.
+/// Taking away the whole part and dividing can both round, and dividing also
+/// magnifies the error that's already there by `1/f^2`.
+fn next_noise(noise: f64, f: f64) -> f64 {
+    noise / (f * f) + 2.0 * f64::EPSILON / f
+}
+
+//---------------------------------------------------------------------------//
+
+/// Computes the convergents of a continued fraction.
+/// That is, the value of each prefix of the terms.
+/// (i.e. [2; 1, 4, 3] -> 2, 3, 14/5, 45/16 )
+///
+/// Inputs:
+/// - `terms: &[i64]` The terms of the continued fraction.
+///
+/// Outputs: `Vec<Expr>`
+/// The simplified convergents, one per term.
+/// Stops early if a convergent doesn't fit in an `i64`.
+///
+/// Time complexity: O(len(terms))
+pub fn convergents(terms: &[i64]) -> Vec<Expr> {
+    // h(i) = a(i) * h(i-1) + h(i-2), starting from h(-2) = 0, h(-1) = 1
+    // k(i) = a(i) * k(i-1) + k(i-2), starting from k(-2) = 1, k(-1) = 0
+    let (mut h0, mut h1) = (0i64, 1i64);
+    let (mut k0, mut k1) = (1i64, 0i64);
+    let mut res = vec![];
+
+    for a in terms {
+        let h = a.checked_mul(h1).and_then(|h| h.checked_add(h0));
+        let k = a.checked_mul(k1).and_then(|k| k.checked_add(k0));
+
+        let (Some(h), Some(k)) = (h, k) else {
+            break;
+        };
+
+        res.push(lcd::recursive(Expr::new(0, h, k)));
+
+        (h0, h1) = (h1, h);
+        (k0, k1) = (k1, k);
+    }
+
+    res
+}
+
+//---------------------------------------------------------------------------//
+
+/// Finds the fraction closest to `x` whose denominator is at most `max_den`.
+/// (i.e. 3.14159..., 10 -> 3(1/7), 3.14159..., 1000 -> 3(16/113) )
+///
+/// Walks down the convergents of `x` (the same path as the Stern-Brocot tree
+/// takes, just in bigger steps) until the denominator gets too large, then
+/// checks the best semiconvergent in between.
+///
+/// Inputs:
+/// - `x: f64` The value to approximate.
+/// - `max_den: i64` The largest denominator allowed.
+///
+/// Outputs: `Result<Expr, LcdError>`
+/// The simplified best approximation, or why it couldn't be found.
+/// `LcdError::ZeroDenominator` if `max_den` is less than 1, and
+/// `LcdError::Overflow` if the whole part doesn't fit in an `i64`.
+///
+/// Time complexity: O(log(max_den))
+pub fn try_best_approximation(x: f64, max_den: i64) -> Result<Expr, LcdError> {
+    if !x.is_finite() {
+        return Err(LcdError::NotFinite);
+    }
+    if max_den < 1 {
+        return Err(LcdError::ZeroDenominator);
+    }
+
+    let max_den = max_den as i128;
+    let (mut h0, mut h1) = (0i128, 1i128);
+    let (mut k0, mut k1) = (1i128, 0i128);
+
+    // an f64 has at most a few dozen meaningful terms
+    for a in iterative_f64(x, 64) {
+        let a = a as i128;
+        let k = a * k1 + k0;
+
+        if k > max_den {
+            // the next convergent is out of reach, but a semiconvergent
+            // (h0 + t*h1)/(k0 + t*k1) with a smaller t might still be closer
+            let t = (max_den - k0) / k1;
+            let (hs, ks) = (h0 + t * h1, k0 + t * k1);
+
+            let err = |h: i128, k: i128| (x - h as f64 / k as f64).abs();
+            if err(hs, ks) < err(h1, k1) {
+                (h1, k1) = (hs, ks);
+            }
+            break;
+        }
+
+        let Some(h) = a.checked_mul(h1).and_then(|h| h.checked_add(h0)) else {
+            break;
+        };
+
+        (h0, h1) = (h1, h);
+        (k0, k1) = (k1, k);
+    }
+
+    // not even the first term fit in an i64
+    if k1 == 0 {
+        return Err(LcdError::Overflow);
+    }
+
+    // split off the whole part before narrowing, the improper nominator can
+    // be far bigger than the mixed number it describes
+    // h1/k1 is already in lowest terms, so this is simplified
+    let c = i64::try_from(h1 / k1).map_err(|_| LcdError::Overflow)?;
+    let n = (h1 % k1) as i64;
+    let d = k1 as i64;
+    Ok(if c == 0 {
+        Expr::new(0, n, d)
+    } else {
+        Expr::new(c, n.abs(), d)
+    })
+}
+
+/// Finds the fraction closest to `x` whose denominator is at most `max_den`.
+/// See [`try_best_approximation`].
+/// (i.e. 3.14159..., 10 -> 3(1/7), 3.14159..., 1000 -> 3(16/113) )
+///
+/// Panics if `x` isn't finite, if `max_den` is less than 1, or if the whole
+/// part of the approximation doesn't fit in an `i64`.
+pub fn best_approximation(x: f64, max_den: i64) -> Expr {
+    match try_best_approximation(x, max_den) {
+        Ok(e) => e,
+        Err(err) => panic!("cannot approximate {} within {}: {}", x, max_den, err),
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use crate::lcd::{Expr, LcdError};
+
+    #[test]
+    fn iterative() {
+        common(super::iterative);
+    }
+
+    #[test]
+    fn recursive() {
+        common(super::recursive);
+    }
+
+    #[test]
+    fn iterative_f64() {
+        common_f64(super::iterative_f64);
+    }
+
+    #[test]
+    fn recursive_f64() {
+        common_f64(super::recursive_f64);
+    }
+
+    fn common(func: fn(Expr) -> Vec<i64>) {
+        assert_eq!(func(Expr::new(0, 45, 16)), [2, 1, 4, 3]);
+        assert_eq!(func(Expr::new(2, 13, 16)), [2, 1, 4, 3]);
+        assert_eq!(func(Expr::new(0, 415, 93)), [4, 2, 6, 7]);
+        assert_eq!(func(Expr::new(0, 1, 3)), [0, 3]);
+        assert_eq!(func(Expr::new(0, -1, 3)), [-1, 1, 2]);
+        assert_eq!(func(Expr::new(-2, 1, 5)), [-3, 1, 4]);
+        assert_eq!(func(Expr::new(0, 7, -1)), [-7]);
+        assert_eq!(func(Expr::new(0, 0, 1)), [0]);
+        assert_eq!(func(Expr::new(0, 1, 0)), []);
+        assert_eq!(func(Expr::new(i64::MAX, 1, 2)), [i64::MAX, 2]);
+
+        // consecutive Fibonacci numbers give (almost) all ones
+        assert_eq!(func(Expr::new(0, 89, 55)), [1, 1, 1, 1, 1, 1, 1, 1, 2]);
+
+        // the last convergent is always the original value
+        for n in -40..40i64 {
+            for d in 1..40i64 {
+                let e = Expr::new(0, n, d);
+                let terms = func(e);
+                println!("{} -> {:?}", e, terms);
+
+                assert!(terms[1..].iter().all(|a| *a > 0));
+                assert_eq!(*super::convergents(&terms).last().unwrap(), e);
+            }
+        }
+    }
+
+    fn common_f64(func: fn(f64, usize) -> Vec<i64>) {
+        assert_eq!(func(0.5, 10), [0, 2]);
+        assert_eq!(func(-0.5, 10), [-1, 2]);
+        assert_eq!(func(2.8125, 10), [2, 1, 4, 3]);
+        assert_eq!(func(0.1, 10), [0, 10]);
+        assert_eq!(func(std::f64::consts::PI, 5), [3, 7, 15, 1, 292]);
+        assert_eq!(
+            func(std::f64::consts::E, 11),
+            [2, 1, 2, 1, 1, 4, 1, 1, 6, 1, 1]
+        );
+        assert_eq!(func(std::f64::consts::SQRT_2, 8), [1, 2, 2, 2, 2, 2, 2, 2]);
+        assert_eq!(func(std::f64::consts::PI, 0), []);
+        assert_eq!(func(f64::NAN, 10), []);
+        assert_eq!(func(f64::INFINITY, 10), []);
+        assert_eq!(func(1e300, 10), []);
+
+        // tiny values aren't mistaken for rounding error
+        assert_eq!(func(1e-10, 2), [0, 10_000_000_000]);
+        assert_eq!(func(-1e-10, 2), [-1, 1]);
+    }
+
+    #[test]
+    fn convergents() {
+        let real = super::convergents(&[2, 1, 4, 3]);
+        let expected = [
+            Expr::new(2, 0, 1),
+            Expr::new(3, 0, 1),
+            Expr::new(2, 4, 5),
+            Expr::new(2, 13, 16),
+        ];
+        assert_eq!(real, expected);
+
+        let real = super::convergents(&[3, 7, 15, 1, 292]);
+        let expected = [
+            Expr::new(0, 3, 1),
+            Expr::new(0, 22, 7),
+            Expr::new(0, 333, 106),
+            Expr::new(0, 355, 113),
+            Expr::new(0, 103993, 33102),
+        ];
+        assert_eq!(real, expected);
+
+        assert_eq!(super::convergents(&[]), []);
+        assert_eq!(super::convergents(&[i64::MAX, i64::MAX, 2]).len(), 1);
+    }
+
+    #[test]
+    fn best_approximation() {
+        use std::f64::consts::{E, PI, SQRT_2};
+
+        assert_eq!(super::best_approximation(PI, 1), Expr::new(0, 3, 1));
+        assert_eq!(super::best_approximation(PI, 7), Expr::new(0, 22, 7));
+        assert_eq!(super::best_approximation(PI, 100), Expr::new(0, 311, 99));
+        assert_eq!(super::best_approximation(PI, 113), Expr::new(0, 355, 113));
+        assert_eq!(
+            super::best_approximation(SQRT_2, 1000),
+            Expr::new(0, 1393, 985)
+        );
+        assert_eq!(super::best_approximation(0.333333, 100), Expr::new(0, 1, 3));
+        assert_eq!(super::best_approximation(-0.75, 100), Expr::new(0, -3, 4));
+        assert_eq!(super::best_approximation(0.125, 8), Expr::new(0, 1, 8));
+        assert_eq!(super::best_approximation(0.125, 7), Expr::new(0, 1, 7));
+        assert_eq!(super::best_approximation(5.0, 10), Expr::new(5, 0, 1));
+        assert_eq!(
+            super::best_approximation(2.5f64.powi(-3), 1000),
+            Expr::new(0, 8, 125)
+        );
+        assert_eq!(
+            super::best_approximation(1e-10, 1_000_000_000_000),
+            Expr::new(0, 1, 10_000_000_000)
+        );
+        assert_eq!(super::best_approximation(1e-10, 1000), Expr::new(0, 0, 1));
+
+        // nothing to approximate with
+        let approx = super::try_best_approximation;
+        assert_eq!(approx(1e300, 10), Err(LcdError::Overflow));
+        assert_eq!(approx(-1e300, 10), Err(LcdError::Overflow));
+        assert_eq!(approx(f64::NAN, 10), Err(LcdError::NotFinite));
+        assert_eq!(approx(0.5, 0), Err(LcdError::ZeroDenominator));
+        assert!(std::panic::catch_unwind(|| super::best_approximation(1e300, 10)).is_err());
+
+        // compare against checking every denominator
+        for x in [PI, E, SQRT_2, -PI, 0.1, 0.7, 1.0 / 3.0, 12.345678, -0.001] {
+            for max_den in 1..200i64 {
+                let expected = (1..=max_den)
+                    .map(|q| (x - (x * q as f64).round() / q as f64).abs())
+                    .fold(f64::INFINITY, f64::min);
+
+                let real = super::best_approximation(x, max_den);
+                let (n, d) = real.to_improper();
+                println!("{} ~ {} (max {})", x, real, max_den);
+
+                assert!(d <= max_den);
+                assert!((x - n as f64 / d as f64).abs() <= expected + 1e-15);
+            }
+        }
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
@@ -0,0 +1,348 @@
+////////////////////////////////////////////////////////////////////////////////
+
+use crate::gcd;
+use crate::lcd::Expr;
+
+////////////////////////////////////////////////////////////////////////////////
+
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+/// Reasons an expression can't be decomposed into unit fractions.
+pub enum EgyptianError {
+    /// The denominator is zero, so the fraction is undefined.
+    ZeroDenominator,
+    /// The expression is negative, and unit fractions are all positive.
+    Negative,
+    /// A denominator doesn't fit in an `i64`.
+    Overflow,
+}
+
+//---------------------------------------------------------------------------//
+
+impl std::fmt::Display for EgyptianError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            EgyptianError::ZeroDenominator => f.write_str("denominator is zero"),
+            EgyptianError::Negative => f.write_str("value is negative"),
+            EgyptianError::Overflow => f.write_str("denominator does not fit in an i64"),
+        }
+    }
+}
+
+impl std::error::Error for EgyptianError {}
+
+////////////////////////////////////////////////////////////////////////////////
+
+/// Checks the expression can be decomposed and turns it into a reduced
+/// fraction with a positive denominator.
+fn fraction(e: Expr) -> Result<(i128, i128), EgyptianError> {
+    if e.d == 0 {
+        return Err(EgyptianError::ZeroDenominator);
+    }
+
+    let (n, d) = e.to_improper_wide();
+    if n < 0 {
+        return Err(EgyptianError::Negative);
+    }
+
+    let g = gcd::iterative(n, d);
+    Ok((n / g, d / g))
+}
+
+/// n/d - 1/k -> (n*k - d)/(d*k), reduced.
+fn subtract_unit(n: i128, d: i128, k: i128) -> Result<(i128, i128), EgyptianError> {
+    let n = n.checked_mul(k).ok_or(EgyptianError::Overflow)? - d;
+    let d = d.checked_mul(k).ok_or(EgyptianError::Overflow)?;
+
+    let g = gcd::iterative(n, d);
+    Ok((n / g, d / g))
+}
+
+/// ceil(d/n) for positive n and d, without the overflow of (d + n - 1)/n.
+fn ceil_div(d: i128, n: i128) -> i128 {
+    (d - 1) / n + 1
+}
+
+/// The greedy choice of the next denominator.
+/// The largest unit fraction that fits in n/d is 1/ceil(d/n), but it also has
+/// to be smaller than every unit fraction used so far.
+fn greedy_denominator(n: i128, d: i128, min: i128) -> Result<i128, EgyptianError> {
+    let k = ceil_div(d, n).max(min);
+
+    if k > i64::MAX as i128 {
+        Err(EgyptianError::Overflow)
+    } else {
+        Ok(k)
+    }
+}
+
+//---------------------------------------------------------------------------//
+
+/// Recursively decomposes an expression into a sum of distinct unit fractions
+/// using the greedy (Fibonacci-Sylvester) algorithm.
+/// (i.e. 4/13 -> 1/4 + 1/18 + 1/468 -> [4, 18, 468] )
+///
+/// Inputs:
+/// - `e: Expr` The expression to decompose.
+///
+/// Outputs: `Result<Vec<i64>, EgyptianError>`
+/// The denominators of the unit fractions, from largest fraction to smallest,
+/// or why it couldn't be decomposed.
+/// Zero is the sum of no unit fractions.
+///
+/// Time complexity: O(n), though the denominators can grow doubly
+/// exponentially.
+pub fn recursive(e: Expr) -> Result<Vec<i64>, EgyptianError> {
+    fn inner(n: i128, d: i128, min: i128, res: &mut Vec<i64>) -> Result<(), EgyptianError> {
+        if n == 0 {
+            // terminating case, nothing left over
+            Ok(())
+        } else {
+            // take the largest unit fraction that fits and decompose the rest
+            let k = greedy_denominator(n, d, min)?;
+            res.push(k as i64);
+
+            let (n, d) = subtract_unit(n, d, k)?;
+            inner(n, d, k + 1, res)
+        }
+    }
+
+    let (n, d) = fraction(e)?;
+    let mut res = vec![];
+    inner(n, d, 1, &mut res)?;
+    Ok(res)
+}
+
+//---------------------------------------------------------------------------//
+
+/// Iteratively decomposes an expression into a sum of distinct unit fractions
+/// using the greedy (Fibonacci-Sylvester) algorithm.
+/// (i.e. 4/13 -> 1/4 + 1/18 + 1/468 -> [4, 18, 468] )
+///
+/// Inputs:
+/// - `e: Expr` The expression to decompose.
+///
+/// Outputs: `Result<Vec<i64>, EgyptianError>`
+/// The denominators of the unit fractions, from largest fraction to smallest,
+/// or why it couldn't be decomposed.
+/// Zero is the sum of no unit fractions.
+///
+/// Time complexity: O(n), though the denominators can grow doubly
+/// exponentially.
+pub fn iterative(e: Expr) -> Result<Vec<i64>, EgyptianError> {
+    let (mut n, mut d) = fraction(e)?;
+    let mut min = 1;
+    let mut res = vec![];
+
+    // keep taking the largest unit fraction that fits until nothing is left
+    while n != 0 {
+        let k = greedy_denominator(n, d, min)?;
+        res.push(k as i64);
+
+        (n, d) = subtract_unit(n, d, k)?;
+        min = k + 1;
+    }
+
+    Ok(res)
+}
+
+//---------------------------------------------------------------------------//
+
+/// Searches for the shortest decomposition of an expression into at most
+/// `max_terms` distinct unit fractions, using only denominators up to
+/// `max_den`.
+/// The greedy algorithm often gives long expansions with huge denominators,
+/// this trades time for something more readable.
+/// (i.e. 5/121 -> 1/33 + 1/121 + 1/363 -> [33, 121, 363] )
+///
+/// Inputs:
+/// - `e: Expr` The expression to decompose.
+/// - `max_den: i64` The largest denominator allowed.
+/// - `max_terms: usize` The most unit fractions allowed.
+///
+/// Outputs: `Result<Option<Vec<i64>>, EgyptianError>`
+/// The denominators of the shortest decomposition, from largest fraction to
+/// smallest, `None` if there isn't one within the bounds, or why it couldn't be
+/// decomposed.
+///
+/// Time complexity: O(max_den^max_terms), so keep `max_terms` small.
+pub fn shortest(
+    e: Expr,
+    max_den: i64,
+    max_terms: usize,
+) -> Result<Option<Vec<i64>>, EgyptianError> {
+    let (n, d) = fraction(e)?;
+    let max = max_den as i128;
+    let mut res = vec![];
+
+    // try each length in turn, so the first one found is the shortest
+    for len in 0..=max_terms {
+        if search(n, d, 1, max, len, &mut res)? {
+            return Ok(Some(res));
+        }
+    }
+
+    Ok(None)
+}
+
+/// Depth first search for exactly `left` more unit fractions adding up to n/d,
+/// each with a denominator in min..=max. See [`shortest`].
+fn search(
+    n: i128,
+    d: i128,
+    min: i128,
+    max: i128,
+    left: usize,
+    res: &mut Vec<i64>,
+) -> Result<bool, EgyptianError> {
+    if n == 0 {
+        return Ok(true);
+    }
+    if left == 0 {
+        return Ok(false);
+    }
+
+    // the next unit fraction has to fit: 1/k <= n/d -> k >= d/n
+    // and the rest are smaller, so it has to be big enough to cover its
+    // share: left/k >= n/d -> k <= left*d/n
+    let lo = ceil_div(d, n).max(min);
+    let hi = (left as i128)
+        .checked_mul(d)
+        .ok_or(EgyptianError::Overflow)?
+        / n;
+
+    for k in lo..=hi.min(max) {
+        res.push(k as i64);
+
+        let (n, d) = subtract_unit(n, d, k)?;
+        if search(n, d, k + 1, max, left - 1, res)? {
+            return Ok(true);
+        }
+
+        res.pop();
+    }
+
+    Ok(false)
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use super::EgyptianError;
+    use crate::lcd::{self, Expr};
+
+    #[test]
+    fn iterative() {
+        common(super::iterative);
+    }
+
+    #[test]
+    fn recursive() {
+        common(super::recursive);
+    }
+
+    /// Checks the denominators are distinct and add back up to `e`.
+    fn check(e: Expr, ds: &[i64]) {
+        println!("{} -> {:?}", e, ds);
+
+        assert!(ds.windows(2).all(|w| w[0] < w[1]));
+
+        let units = ds.iter().map(|d| Expr::new(0, 1, *d)).collect::<Vec<_>>();
+        assert_eq!(lcd::sum(&units), Ok(e));
+    }
+
+    fn common(func: fn(Expr) -> Result<Vec<i64>, EgyptianError>) {
+        assert_eq!(func(Expr::new(0, 4, 13)), Ok(vec![4, 18, 468]));
+        assert_eq!(func(Expr::new(0, 2, 3)), Ok(vec![2, 6]));
+        assert_eq!(func(Expr::new(0, 3, 7)), Ok(vec![3, 11, 231]));
+        assert_eq!(func(Expr::new(0, 1, 5)), Ok(vec![5]));
+        assert_eq!(func(Expr::new(0, 6, 12)), Ok(vec![2]));
+        assert_eq!(func(Expr::new(1, 1, 2)), Ok(vec![1, 2]));
+        assert_eq!(func(Expr::new(2, 0, 1)), Ok(vec![1, 2, 3, 6]));
+        assert_eq!(func(Expr::new(0, 0, 1)), Ok(vec![]));
+        assert_eq!(func(Expr::new(0, -1, -2)), Ok(vec![2]));
+
+        assert_eq!(
+            func(Expr::new(0, 1, 0)),
+            Err(EgyptianError::ZeroDenominator)
+        );
+        assert_eq!(func(Expr::new(0, -1, 2)), Err(EgyptianError::Negative));
+        assert_eq!(func(Expr::new(-1, 1, 2)), Err(EgyptianError::Negative));
+
+        // the greedy expansion of 5/121 needs a 25 digit denominator
+        assert_eq!(func(Expr::new(0, 5, 121)), Err(EgyptianError::Overflow));
+
+        // every proper fraction with a small denominator
+        for d in 2..40i64 {
+            for n in 1..d {
+                let e = Expr::new(0, n, d);
+                match func(e) {
+                    Ok(ds) => check(e, &ds),
+                    Err(err) => assert_eq!(err, EgyptianError::Overflow),
+                }
+            }
+        }
+
+        // and a few larger ones
+        for c in 1..=2 {
+            let e = Expr::new(c, 1, 3);
+            check(e, &func(e).unwrap());
+        }
+    }
+
+    #[test]
+    fn shortest() {
+        let e = Expr::new(0, 5, 121);
+        let ds = super::shortest(e, 400, 3).unwrap().unwrap();
+        assert_eq!(ds, [33, 121, 363]);
+        check(e, &ds);
+
+        // 4/13 can't be done in two, but can avoid 1/468
+        let e = Expr::new(0, 4, 13);
+        let ds = super::shortest(e, 200, 3).unwrap().unwrap();
+        assert_eq!(ds, [4, 20, 130]);
+        check(e, &ds);
+
+        assert_eq!(
+            super::shortest(Expr::new(0, 4, 13), 60, 3),
+            Ok(Some(vec![4, 26, 52]))
+        );
+        assert_eq!(super::shortest(Expr::new(0, 2, 3), 5, 3), Ok(None));
+        assert_eq!(
+            super::shortest(Expr::new(0, 2, 3), 10, 3),
+            Ok(Some(vec![2, 6]))
+        );
+        assert_eq!(super::shortest(Expr::new(0, 2, 3), 10, 1), Ok(None));
+
+        // however large the denominators can be, the length is capped
+        assert_eq!(super::shortest(Expr::new(0, 5, 121), i64::MAX, 2), Ok(None));
+
+        // too large to work out the range of denominators to try
+        let mut res = vec![];
+        assert_eq!(
+            super::search(1, i128::MAX / 2, 1, i64::MAX as i128, 3, &mut res),
+            Err(EgyptianError::Overflow)
+        );
+        assert_eq!(super::shortest(Expr::new(0, 0, 1), 10, 0), Ok(Some(vec![])));
+        assert_eq!(super::shortest(Expr::new(0, 1, 2), 0, 3), Ok(None));
+        assert_eq!(
+            super::shortest(Expr::new(0, -1, 2), 10, 3),
+            Err(EgyptianError::Negative)
+        );
+
+        // never longer than the greedy expansion
+        for d in 2..16i64 {
+            for n in 1..d {
+                let e = Expr::new(0, n, d);
+                let greedy = super::iterative(e).unwrap();
+                let max = *greedy.last().unwrap();
+
+                let ds = super::shortest(e, max, greedy.len()).unwrap().unwrap();
+                check(e, &ds);
+                assert!(ds.len() <= greedy.len());
+            }
+        }
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
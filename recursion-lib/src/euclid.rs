@@ -0,0 +1,321 @@
+////////////////////////////////////////////////////////////////////////////////
+
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+/// Reasons a system of congruences can't be solved.
+pub enum CrtError {
+    /// A modulus is zero or negative.
+    BadModulus,
+    /// The congruences contradict each other.
+    NoSolution,
+    /// The combined modulus doesn't fit in an `i64`.
+    Overflow,
+}
+
+//---------------------------------------------------------------------------//
+
+impl std::fmt::Display for CrtError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CrtError::BadModulus => f.write_str("modulus is not positive"),
+            CrtError::NoSolution => f.write_str("congruences have no common solution"),
+            CrtError::Overflow => f.write_str("modulus does not fit in an i64"),
+        }
+    }
+}
+
+impl std::error::Error for CrtError {}
+
+////////////////////////////////////////////////////////////////////////////////
+
+/// Moves the sign of the result onto the coefficients, so the gcd is
+/// non-negative.
+fn normalise((g, x, y): (i128, i128, i128)) -> (i128, i128, i128) {
+    if g < 0 {
+        (-g, -x, -y)
+    } else {
+        (g, x, y)
+    }
+}
+
+/// Narrows a result back down to `i64`.
+/// The coefficients are at most |a|/g and |b|/g, so only the gcd of
+/// `i64::MIN` with 0 or itself can fail.
+fn narrow((g, x, y): (i128, i128, i128)) -> (i64, i64, i64) {
+    let narrow = |v: i128| i64::try_from(v).expect("gcd does not fit in an i64");
+    (narrow(g), narrow(x), narrow(y))
+}
+
+/// The iterative algorithm, widened so the callers below can work on values
+/// that don't fit in an `i64`.
+fn iterative_wide(a: i128, b: i128) -> (i128, i128, i128) {
+    // invariant: a*x0 + b*y0 = r0 and a*x1 + b*y1 = r1
+    let (mut r0, mut r1) = (a, b);
+    let (mut x0, mut x1) = (1, 0);
+    let (mut y0, mut y1) = (0, 1);
+
+    while r1 != 0 {
+        let q = r0 / r1;
+        (r0, r1) = (r1, r0 - q * r1);
+        (x0, x1) = (x1, x0 - q * x1);
+        (y0, y1) = (y1, y0 - q * y1);
+    }
+
+    normalise((r0, x0, y0))
+}
+
+//---------------------------------------------------------------------------//
+
+/// Recursively computes the Greatest Common Divisor of `a` and `b` along with
+/// a pair of Bézout coefficients, using the extended Euclidean algorithm.
+/// (i.e. 240, 46 -> 2 = 240*(-9) + 46*47 -> (2, -9, 47) )
+///
+/// Inputs:
+/// - `a: i64`
+/// - `b: i64`
+///
+/// Outputs: `(i64, i64, i64)`
+/// The gcd `g` and coefficients `x` and `y` with a*x + b*y = g.
+/// The gcd is always non-negative, and gcd(0, 0) is defined as 0.
+///
+/// Panics if the gcd is 2^63, which only happens for `i64::MIN` with 0 or
+/// itself.
+///
+/// Time complexity: O(log(min(a, b)))
+pub fn recursive(a: i64, b: i64) -> (i64, i64, i64) {
+    fn inner(a: i128, b: i128) -> (i128, i128, i128) {
+        if b == 0 {
+            // terminating case, gcd(a, 0) = a = a*1 + 0*0
+            (a, 1, 0)
+        } else {
+            // general case, gcd(a, b) = gcd(b, a mod b)
+            // and since a mod b = a - (a/b)*b,
+            // b*x + (a - (a/b)*b)*y = a*y + b*(x - (a/b)*y)
+            let (g, x, y) = inner(b, a % b);
+            (g, y, x - (a / b) * y)
+        }
+    }
+
+    narrow(normalise(inner(a as i128, b as i128)))
+}
+
+//---------------------------------------------------------------------------//
+
+/// Iteratively computes the Greatest Common Divisor of `a` and `b` along with
+/// a pair of Bézout coefficients, using the extended Euclidean algorithm.
+/// (i.e. 240, 46 -> 2 = 240*(-9) + 46*47 -> (2, -9, 47) )
+///
+/// Inputs:
+/// - `a: i64`
+/// - `b: i64`
+///
+/// Outputs: `(i64, i64, i64)`
+/// The gcd `g` and coefficients `x` and `y` with a*x + b*y = g.
+/// The gcd is always non-negative, and gcd(0, 0) is defined as 0.
+///
+/// Panics if the gcd is 2^63, which only happens for `i64::MIN` with 0 or
+/// itself.
+///
+/// Time complexity: O(log(min(a, b)))
+pub fn iterative(a: i64, b: i64) -> (i64, i64, i64) {
+    narrow(iterative_wide(a as i128, b as i128))
+}
+
+//---------------------------------------------------------------------------//
+
+/// Finds the modular multiplicative inverse of `a` modulo `m`.
+/// That is, the x with a*x = 1 (mod m), read off the Bézout coefficients of
+/// a*x + m*y = 1.
+/// (i.e. 3 mod 11 -> 4, since 3*4 = 12 = 1 (mod 11) )
+///
+/// Inputs:
+/// - `a: i64`
+/// - `m: i64` The modulus.
+///
+/// Outputs: `Option<i64>`
+/// The inverse in 0..m, or `None` if `m` isn't positive or `a` and `m` share
+/// a factor.
+///
+/// Time complexity: O(log(min(a, m)))
+pub fn inverse(a: i64, m: i64) -> Option<i64> {
+    if m <= 0 {
+        return None;
+    }
+
+    let m = m as i128;
+    let (g, x, _) = iterative_wide(a as i128, m);
+
+    if g == 1 {
+        Some(x.rem_euclid(m) as i64)
+    } else {
+        None
+    }
+}
+
+//---------------------------------------------------------------------------//
+
+/// Solves a system of congruences x = r (mod m) with the Chinese Remainder
+/// Theorem.
+/// The moduli don't need to be coprime, each pair is merged into one
+/// congruence modulo their lcm as long as they agree where they overlap.
+/// (i.e. x = 2 (mod 3), x = 3 (mod 5), x = 2 (mod 7) -> x = 23 (mod 105) )
+///
+/// Inputs:
+/// - `congruences: &[(i64, i64)]` Each residue and its modulus.
+///
+/// Outputs: `Result<(i64, i64), CrtError>`
+/// The smallest non-negative solution and the modulus every other solution
+/// differs from it by, or why there isn't one.
+/// The empty system is solved by everything, x = 0 (mod 1).
+///
+/// Time complexity: O(len(congruences) * log(max(m)))
+pub fn crt(congruences: &[(i64, i64)]) -> Result<(i64, i64), CrtError> {
+    let (mut x, mut m) = (0i128, 1i128);
+
+    for &(r2, m2) in congruences {
+        if m2 <= 0 {
+            return Err(CrtError::BadModulus);
+        }
+        let (r2, m2) = (r2 as i128, m2 as i128);
+
+        // x + m*t = r2 (mod m2) -> m*t = r2 - x (mod m2)
+        // which needs gcd(m, m2) to divide r2 - x
+        let (g, p, _) = iterative_wide(m, m2);
+        let diff = r2 - x;
+        if diff % g != 0 {
+            return Err(CrtError::NoSolution);
+        }
+
+        // divide through by g, then p is the inverse of m/g modulo m2/g
+        let step = m2 / g;
+        let t = (diff / g % step * p).rem_euclid(step);
+
+        let l = m * step;
+        if l > i64::MAX as i128 {
+            return Err(CrtError::Overflow);
+        }
+
+        x = (x + m * t).rem_euclid(l);
+        m = l;
+    }
+
+    Ok((x as i64, m as i64))
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use super::CrtError;
+    use crate::gcd;
+
+    #[test]
+    fn iterative() {
+        common(super::iterative);
+    }
+
+    #[test]
+    fn recursive() {
+        common(super::recursive);
+    }
+
+    /// Checks the coefficients actually give the gcd.
+    fn check(func: fn(i64, i64) -> (i64, i64, i64), a: i64, b: i64) {
+        let (g, x, y) = func(a, b);
+        println!("{}*{} + {}*{} = {}", a, x, b, y, g);
+
+        assert_eq!(g, gcd::iterative(a, b));
+        assert_eq!(a as i128 * x as i128 + b as i128 * y as i128, g as i128);
+    }
+
+    fn common(func: fn(i64, i64) -> (i64, i64, i64)) {
+        assert_eq!(func(240, 46), (2, -9, 47));
+        assert_eq!(func(99, 78), (3, -11, 14));
+        assert_eq!(func(35, 15), (5, 1, -2));
+        assert_eq!(func(7, 0), (7, 1, 0));
+        assert_eq!(func(-7, 0), (7, -1, 0));
+        assert_eq!(func(0, 7), (7, 0, 1));
+        assert_eq!(func(0, 0), (0, 1, 0));
+
+        for a in -60..60 {
+            for b in -60..60 {
+                check(func, a, b);
+            }
+        }
+
+        check(func, i64::MAX, i64::MAX - 1);
+        check(func, i64::MIN, i64::MAX);
+        check(func, i64::MIN, 6);
+        check(func, 1 << 62, 3 << 40);
+    }
+
+    #[test]
+    fn inverse() {
+        assert_eq!(super::inverse(3, 11), Some(4));
+        assert_eq!(super::inverse(10, 17), Some(12));
+        assert_eq!(super::inverse(-3, 11), Some(7));
+        assert_eq!(super::inverse(14, 11), Some(4));
+        assert_eq!(super::inverse(5, 1), Some(0));
+        assert_eq!(super::inverse(2, 4), None);
+        assert_eq!(super::inverse(0, 7), None);
+        assert_eq!(super::inverse(3, 0), None);
+        assert_eq!(super::inverse(3, -11), None);
+        assert_eq!(super::inverse(2, i64::MAX), Some(i64::MAX / 2 + 1));
+
+        for m in 1..100i64 {
+            for a in -m..m {
+                let inv = super::inverse(a, m);
+                match inv {
+                    Some(x) => {
+                        assert!((0..m).contains(&x));
+                        assert_eq!((a * x).rem_euclid(m), 1 % m);
+                    }
+                    None => assert_ne!(gcd::iterative(a, m), 1),
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn crt() {
+        // Sunzi Suanjing: three by three leaves two, five by five leaves
+        // three, seven by seven leaves two
+        assert_eq!(super::crt(&[(2, 3), (3, 5), (2, 7)]), Ok((23, 105)));
+        assert_eq!(super::crt(&[(0, 3), (3, 4), (4, 5)]), Ok((39, 60)));
+        assert_eq!(super::crt(&[(1, 5), (1, 7), (3, 11)]), Ok((36, 385)));
+        assert_eq!(super::crt(&[(-1, 3), (-1, 4)]), Ok((11, 12)));
+        assert_eq!(super::crt(&[]), Ok((0, 1)));
+        assert_eq!(super::crt(&[(17, 5)]), Ok((2, 5)));
+
+        // moduli that share a factor
+        assert_eq!(super::crt(&[(2, 4), (4, 6)]), Ok((10, 12)));
+        assert_eq!(super::crt(&[(3, 6), (3, 6)]), Ok((3, 6)));
+        assert_eq!(super::crt(&[(1, 4), (2, 6)]), Err(CrtError::NoSolution));
+
+        assert_eq!(super::crt(&[(1, 4), (2, 0)]), Err(CrtError::BadModulus));
+        assert_eq!(super::crt(&[(1, -4)]), Err(CrtError::BadModulus));
+        assert_eq!(
+            super::crt(&[(1, i64::MAX), (0, 2)]),
+            Err(CrtError::Overflow)
+        );
+        assert_eq!(super::crt(&[(1, i64::MAX), (1, 1)]), Ok((1, i64::MAX)));
+
+        // compare against a slow but obviously correct search
+        for m1 in 1..12i64 {
+            for m2 in 1..12i64 {
+                for r1 in 0..m1 {
+                    for r2 in 0..m2 {
+                        let l = m1 * m2 / gcd::iterative(m1, m2);
+                        let expected = (0..l)
+                            .find(|x| x % m1 == r1 && x % m2 == r2)
+                            .map(|x| (x, l))
+                            .ok_or(CrtError::NoSolution);
+
+                        assert_eq!(super::crt(&[(r1, m1), (r2, m2)]), expected);
+                    }
+                }
+            }
+        }
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
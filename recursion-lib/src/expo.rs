@@ -1,5 +1,9 @@
 ////////////////////////////////////////////////////////////////////////////////
 
+use crate::trace::{Call, Traced};
+
+////////////////////////////////////////////////////////////////////////////////
+
 /// Recursively calculates x raised to the power of e
 ///
 /// Inputs:
@@ -11,16 +15,74 @@
 ///
 /// Time complexity: O(e)
 pub fn recursive(x: f64, e: i32) -> f64 {
+    match next_step(e) {
+        // general case, build on the rest of the calls
+        Some(next) => combine(x, e, recursive(x, next)),
+        // terminating case, return 1
+        None => 1.0,
+    }
+}
+
+/// The exponent [`recursive`] calls itself with next, or `None` once there's
+/// nothing left to multiply.
+fn next_step(e: i32) -> Option<i32> {
     if e > 0 {
-        // general case, just multiply x by the rest of the xs
-        x * recursive(x, e - 1)
+        // general case, one fewer x to multiply
+        Some(e - 1)
     } else if e < 0 {
         // edge case, if we have a negative exponent, just rewrite as 1/(x^|e|)
-        1.0 / recursive(x, e * -1)
+        Some(-e)
     } else {
-        // terminating case, return 1
-        1.0
+        None
+    }
+}
+
+/// Builds the result for `e` out of the result for `next_step(e)`.
+fn combine(x: f64, e: i32, rest: f64) -> f64 {
+    if e > 0 {
+        // just multiply x by the rest of the xs
+        x * rest
+    } else {
+        1.0 / rest
+    }
+}
+
+//---------------------------------------------------------------------------//
+
+/// Recursively calculates x raised to the power of e, recording each call
+/// along with what it returned. See [`recursive`].
+///
+/// Inputs:
+/// - `x: f64`
+/// - `e: i32`
+/// - `max_depth: usize` How many levels of calls to record, at least 1. Calls
+///   below that are only counted.
+///
+/// Outputs: `(f64, Call)`
+/// x raised to the power of e, and the calls it took to get there.
+///
+/// Time complexity: O(e)
+pub fn recursive_traced(x: f64, e: i32, max_depth: usize) -> (f64, Call) {
+    fn inner(x: f64, e: i32, max_depth: usize) -> (f64, Traced) {
+        let (res, children) = match next_step(e) {
+            Some(next) => {
+                let (rest, call) = inner(x, next, max_depth.saturating_sub(1));
+                (combine(x, e, rest), vec![call])
+            }
+            None => (1.0, vec![]),
+        };
+
+        let call = Traced::record(
+            max_depth,
+            || format!("recursive({}, {})", x, e),
+            || res.to_string(),
+            children,
+        );
+        (res, call)
     }
+
+    let (res, call) = inner(x, e, max_depth.max(1));
+    (res, call.into_call())
 }
 
 //---------------------------------------------------------------------------//
@@ -36,25 +98,187 @@ pub fn recursive(x: f64, e: i32) -> f64 {
 ///
 /// Time complexity: O(e)
 pub fn iterative(x: f64, e: i32) -> f64 {
+    power_with(x, e, &mut |_| {})
+}
+
+/// Iteratively calculates x raised to the power of e, recording each
+/// multiplication along the way. See [`iterative`].
+///
+/// Inputs:
+/// - `x: f64`
+/// - `e: i32`
+///
+/// Outputs: `(f64, Vec<Step>)`
+/// x raised to the power of e, and the steps taken to get there.
+///
+/// Time complexity: O(e)
+pub fn iterative_traced(x: f64, e: i32) -> (f64, Vec<Step>) {
+    let mut steps = vec![];
+    let res = power_with(x, e, &mut |step| steps.push(step));
+    (res, steps)
+}
+
+/// The iterative power, reporting each step to `record` as it is made rather
+/// than collecting them, so a caller can keep as few as it needs.
+/// See [`iterative_traced`].
+///
+/// Time complexity: O(e)
+pub fn power_with(x: f64, e: i32, record: &mut impl FnMut(Step)) -> f64 {
     let mut res = 1.0;
 
     // just repeatedly multiple x by itself |e| times
     for _ in 0..e.abs() {
-        res = res * x;
+        record(Step::Multiply { res, by: x });
+        res *= x;
     }
 
     // rewrite x^(-e) as 1/(x^e)
     if e < 0 {
+        record(Step::Invert(res));
         1.0 / res
     } else {
         res
     }
 }
 
+//---------------------------------------------------------------------------//
+
+#[derive(Debug, PartialEq, Copy, Clone)]
+/// A single operation made while raising x to a power.
+/// See [`iterative_traced`].
+pub enum Step {
+    /// The running result was multiplied by x.
+    Multiply { res: f64, by: f64 },
+    /// The exponent was negative, so the running result was inverted.
+    Invert(f64),
+}
+
+impl std::fmt::Display for Step {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Step::Multiply { res, by } => {
+                f.write_fmt(format_args!("{} * {} = {}", res, by, res * by))
+            }
+            Step::Invert(res) => f.write_fmt(format_args!("1 / {} = {}", res, 1.0 / res)),
+        }
+    }
+}
+
+//---------------------------------------------------------------------------//
+
+/// Recursively raises x to the power of e by repeated squaring, with any
+/// multiplication that might fail.
+/// Useful for exact types like fractions, where each product can overflow.
+/// (i.e. 2/3, 3 -> 8/27 )
+///
+/// Inputs:
+/// - `x: &T`
+/// - `e: u32`
+/// - `one: T` The multiplicative identity, x^0.
+/// - `mul: impl Fn(&T, &T) -> Result<T, E>` How to multiply two values.
+///
+/// Outputs: `Result<T, E>`
+/// x raised to the power of e, or the first error from `mul`.
+///
+/// Time complexity: O(log(e))
+pub fn recursive_with<T: Clone, E>(
+    x: &T,
+    e: u32,
+    one: T,
+    mul: &impl Fn(&T, &T) -> Result<T, E>,
+) -> Result<T, E> {
+    if e == 0 {
+        // terminating case, return 1
+        Ok(one)
+    } else {
+        // general case, x^e = (x^(e/2))^2, times another x if e is odd
+        let half = recursive_with(x, e / 2, one, mul)?;
+        let square = mul(&half, &half)?;
+
+        if e % 2 == 1 {
+            mul(&square, x)
+        } else {
+            Ok(square)
+        }
+    }
+}
+
+//---------------------------------------------------------------------------//
+
+/// Iteratively raises x to the power of e by repeated squaring, with any
+/// multiplication that might fail.
+/// Useful for exact types like fractions, where each product can overflow.
+/// (i.e. 2/3, 3 -> 8/27 )
+///
+/// Inputs:
+/// - `x: &T`
+/// - `e: u32`
+/// - `one: T` The multiplicative identity, x^0.
+/// - `mul: impl Fn(&T, &T) -> Result<T, E>` How to multiply two values.
+///
+/// Outputs: `Result<T, E>`
+/// x raised to the power of e, or the first error from `mul`.
+///
+/// Time complexity: O(log(e))
+pub fn iterative_with<T: Clone, E>(
+    x: &T,
+    e: u32,
+    one: T,
+    mul: &impl Fn(&T, &T) -> Result<T, E>,
+) -> Result<T, E> {
+    let mut res = one;
+
+    // walk down the bits of e from the top, squaring for each and multiplying
+    // in another x for each that is set
+    for bit in (0..u32::BITS - e.leading_zeros()).rev() {
+        res = mul(&res, &res)?;
+        if e >> bit & 1 == 1 {
+            res = mul(&res, x)?;
+        }
+    }
+
+    Ok(res)
+}
+
+//---------------------------------------------------------------------------//
+
+/// Calculates x raised to the power of e, modulo m, by repeated squaring.
+/// Works in `u128` so the products can't overflow.
+///
+/// Inputs:
+/// - `x: u64`
+/// - `e: u64`
+/// - `m: u64` The modulus.
+///
+/// Outputs: `u64`
+/// x raised to the power of e, modulo m.
+///
+/// Panics if `m` is 0.
+///
+/// Time complexity: O(log(e))
+pub fn modular(x: u64, e: u64, m: u64) -> u64 {
+    let m = m as u128;
+    let mut base = x as u128 % m;
+    let mut e = e;
+    let mut res = 1 % m;
+
+    // x^e = (x^2)^(e/2), times another x if e is odd
+    while e > 0 {
+        if e & 1 == 1 {
+            res = res * base % m;
+        }
+        base = base * base % m;
+        e >>= 1;
+    }
+
+    res as u64
+}
+
 ////////////////////////////////////////////////////////////////////////////////
 
 #[cfg(test)]
 mod tests {
+    use super::Step;
 
     #[test]
     fn iterative() {
@@ -66,6 +290,51 @@ mod tests {
         common(super::recursive);
     }
 
+    #[test]
+    fn iterative_traced() {
+        common(|x, e| super::iterative_traced(x, e).0);
+
+        let (res, steps) = super::iterative_traced(2.0, -2);
+        assert_eq!(res, 0.25);
+        assert_eq!(
+            steps,
+            [
+                Step::Multiply { res: 1.0, by: 2.0 },
+                Step::Multiply { res: 2.0, by: 2.0 },
+                Step::Invert(4.0),
+            ]
+        );
+        let steps = steps.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+        assert_eq!(steps, ["1 * 2 = 2", "2 * 2 = 4", "1 / 4 = 0.25"]);
+
+        assert!(super::iterative_traced(2.5, 0).1.is_empty());
+    }
+
+    #[test]
+    fn recursive_traced() {
+        common(|x, e| super::recursive_traced(x, e, usize::MAX).0);
+
+        let (res, call) = super::recursive_traced(2.0, -2, usize::MAX);
+        assert_eq!(res, 0.25);
+        assert_eq!(
+            call.to_text(usize::MAX),
+            "\
+recursive(2, -2) = 0.25
+  recursive(2, 2) = 4
+    recursive(2, 1) = 2
+      recursive(2, 0) = 1
+"
+        );
+        assert_eq!(super::recursive_traced(1.5, 100, usize::MAX).1.depth(), 101);
+
+        // only the first few levels are kept, the rest are counted
+        let (res, call) = super::recursive_traced(1.5, 100, 3);
+        assert_eq!(res, super::recursive(1.5, 100));
+        assert_eq!(call.depth(), 3);
+        assert_eq!(call.size(), 101);
+        assert_eq!(call.children[0].children[0].hidden, 98);
+    }
+
     fn common(func: fn(f64, i32) -> f64) {
         for i in -50..100 {
             for n in -50..50 {
@@ -96,6 +365,63 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn iterative_with() {
+        common_with(super::iterative_with);
+    }
+
+    #[test]
+    fn recursive_with() {
+        common_with(super::recursive_with);
+    }
+
+    type MulI64 = fn(&i64, &i64) -> Result<i64, ()>;
+
+    fn common_with(func: fn(&i64, u32, i64, &MulI64) -> Result<i64, ()>) {
+        let checked: MulI64 = |a, b| a.checked_mul(*b).ok_or(());
+
+        assert_eq!(func(&2, 10, 1, &checked), Ok(1024));
+        assert_eq!(func(&-3, 3, 1, &checked), Ok(-27));
+        assert_eq!(func(&7, 0, 1, &checked), Ok(1));
+        assert_eq!(func(&0, 0, 1, &checked), Ok(1));
+        assert_eq!(func(&2, 62, 1, &checked), Ok(1 << 62));
+        assert_eq!(func(&2, 63, 1, &checked), Err(()));
+        assert_eq!(func(&1, u32::MAX, 1, &checked), Ok(1));
+        assert_eq!(func(&-1, u32::MAX, 1, &checked), Ok(-1));
+
+        for x in -6..=6i64 {
+            for e in 0..20u32 {
+                assert_eq!(func(&x, e, 1, &checked), Ok(x.pow(e)));
+            }
+        }
+    }
+
+    #[test]
+    fn modular() {
+        assert_eq!(super::modular(4, 13, 497), 445);
+        assert_eq!(super::modular(2, 10, 1000), 24);
+        assert_eq!(super::modular(3, 0, 7), 1);
+        assert_eq!(super::modular(3, 0, 1), 0);
+        assert_eq!(super::modular(0, 5, 7), 0);
+        assert_eq!(super::modular(u64::MAX, 2, u64::MAX - 1), 1);
+
+        // Fermat's little theorem, x^(p-1) = 1 (mod p)
+        let p = (1 << 61) - 1;
+        for x in 1..100 {
+            assert_eq!(super::modular(x, p - 1, p), 1);
+        }
+
+        // compare against repeated multiplication
+        for x in 0..20u64 {
+            for e in 0..20u64 {
+                for m in 1..20u64 {
+                    let expected = (0..e).fold(1 % m, |acc, _| acc * x % m);
+                    assert_eq!(super::modular(x, e, m), expected);
+                }
+            }
+        }
+    }
 }
 
 ////////////////////////////////////////////////////////////////////////////////
@@ -0,0 +1,461 @@
+////////////////////////////////////////////////////////////////////////////////
+
+use tailcall::tailcall;
+
+use crate::gcd;
+use crate::lcd::Expr;
+
+////////////////////////////////////////////////////////////////////////////////
+
+/// Turns a non-negative fraction already in lowest terms into a simplified
+/// expression.
+fn fraction(a: i64, b: i64) -> Expr {
+    // gcd(a % b, b) = gcd(a, b) = 1, so this is already simplified
+    Expr::new(a / b, a % b, b)
+}
+
+/// Checks the expression is a positive rational and turns it into a reduced
+/// fraction with a positive denominator.
+fn positive(e: Expr) -> Option<(i128, i128)> {
+    if e.d == 0 {
+        return None;
+    }
+
+    let (n, d) = e.to_improper_wide();
+    if n <= 0 {
+        return None;
+    }
+
+    let g = gcd::iterative(n, d);
+    Some((n / g, d / g))
+}
+
+//---------------------------------------------------------------------------//
+
+/// Recursively generates the Farey sequence of order `n`.
+/// That is, every fraction between 0 and 1 with a denominator of at most `n`,
+/// in lowest terms and in order.
+/// Starting from 0/1 and 1/1, the next fraction between two neighbours a/b
+/// and c/d is always their mediant (a+c)/(b+d).
+/// (i.e. 3 -> 0/1, 1/3, 1/2, 2/3, 1/1 )
+///
+/// Inputs:
+/// - `n: i64` The order, the largest denominator allowed.
+///
+/// Outputs: `Vec<Expr>`
+/// The simplified fractions, from smallest to largest.
+/// Empty if `n` is less than 1.
+///
+/// Time complexity: O(n^2)
+pub fn recursive(n: i64) -> Vec<Expr> {
+    fn inner(left: (i64, i64), right: (i64, i64), n: i64, res: &mut Vec<Expr>) {
+        let (a, b) = left;
+        let (c, d) = right;
+
+        if b + d > n {
+            // terminating case, the mediant's denominator is too big
+            return;
+        }
+
+        // general case, fill in on either side of the mediant
+        let mediant = (a + c, b + d);
+        inner(left, mediant, n, res);
+        res.push(fraction(mediant.0, mediant.1));
+        inner(mediant, right, n, res);
+    }
+
+    if n < 1 {
+        return vec![];
+    }
+
+    let mut res = vec![fraction(0, 1)];
+    inner((0, 1), (1, 1), n, &mut res);
+    res.push(fraction(1, 1));
+    res
+}
+
+//---------------------------------------------------------------------------//
+
+/// Iteratively generates the Farey sequence of order `n`.
+/// That is, every fraction between 0 and 1 with a denominator of at most `n`,
+/// in lowest terms and in order.
+/// Each fraction follows from the two before it: after a/b and c/d comes
+/// (k*c - a)/(k*d - b) with k = (n + b)/d.
+/// (i.e. 3 -> 0/1, 1/3, 1/2, 2/3, 1/1 )
+///
+/// Inputs:
+/// - `n: i64` The order, the largest denominator allowed.
+///
+/// Outputs: `Vec<Expr>`
+/// The simplified fractions, from smallest to largest.
+/// Empty if `n` is less than 1.
+///
+/// Time complexity: O(n^2)
+pub fn iterative(n: i64) -> Vec<Expr> {
+    if n < 1 {
+        return vec![];
+    }
+
+    // the first two terms are always 0/1 and 1/n
+    let (mut a, mut b, mut c, mut d) = (0, 1, 1, n);
+    let mut res = vec![fraction(a, b)];
+
+    while c <= n {
+        res.push(fraction(c, d));
+
+        let k = (n + b) / d;
+        (a, b, c, d) = (c, d, k * c - a, k * d - b);
+    }
+
+    res
+}
+
+//---------------------------------------------------------------------------//
+
+/// Generates the first `depth` levels of the Stern-Brocot tree.
+/// Every positive rational shows up exactly once in the tree, in lowest terms,
+/// and an in-order walk visits them from smallest to largest.
+/// (i.e. 2 -> 1/2, 1/1, 2/1 )
+///
+/// Inputs:
+/// - `depth: usize` How many levels to generate.
+///
+/// Outputs: `Vec<Expr>`
+/// The 2^depth - 1 simplified fractions, from smallest to largest.
+///
+/// Time complexity: O(2^depth)
+pub fn tree(depth: usize) -> Vec<Expr> {
+    fn inner(left: (i64, i64), right: (i64, i64), depth: usize, res: &mut Vec<Expr>) {
+        if depth == 0 {
+            // terminating case, deep enough
+            return;
+        }
+
+        // general case, each node is the mediant of its nearest ancestors
+        let mediant = (left.0 + right.0, left.1 + right.1);
+        inner(left, mediant, depth - 1, res);
+        res.push(fraction(mediant.0, mediant.1));
+        inner(mediant, right, depth - 1, res);
+    }
+
+    // the tree sits between 0/1 and 1/0
+    let mut res = vec![];
+    inner((0, 1), (1, 0), depth, &mut res);
+    res
+}
+
+//---------------------------------------------------------------------------//
+
+/// Adds a run of `count` steps the same way to a path, written as `L^count`,
+/// or just `L` for a single step.
+fn push_run(path: &mut String, step: char, count: i128) {
+    if !path.is_empty() {
+        path.push(' ');
+    }
+    path.push(step);
+    if count > 1 {
+        path.push_str(&format!("^{}", count));
+    }
+}
+
+//---------------------------------------------------------------------------//
+
+/// Recursively finds the path from the root of the Stern-Brocot tree to a
+/// fraction.
+/// Going left from p/q leads to the subtree of p/(q - p), and going right to
+/// (p - q)/q, so a whole run of steps the same way takes off as many copies of
+/// the smaller part as fit, which is Euclid's algorithm.
+/// The runs are the terms of the continued fraction, the last one less one.
+/// (i.e. 3/5 -> L R L, 22/7 -> R^3 L^6 )
+///
+/// Inputs:
+/// - `e: Expr` The fraction to find.
+///
+/// Outputs: `Option<String>`
+/// The path, as runs of `L` and `R` steps. The root 1/1 is the empty path.
+/// `None` if `e` isn't positive, since the tree only has positive rationals.
+///
+/// Time complexity: O(log(max(nominator, denominator)))
+pub fn path_recursive(e: Expr) -> Option<String> {
+    #[tailcall]
+    fn inner(p: i128, q: i128, path: &mut String) {
+        if p < q {
+            // general case, it's smaller so go left until it isn't
+            let count = (q - 1) / p;
+            push_run(path, 'L', count);
+            inner(p, q - count * p, path)
+        } else if p > q {
+            // general case, it's bigger so go right until it isn't
+            let count = (p - 1) / q;
+            push_run(path, 'R', count);
+            inner(p - count * q, q, path)
+        }
+        // terminating case, p == q, found it
+    }
+
+    let (p, q) = positive(e)?;
+    let mut path = String::new();
+    inner(p, q, &mut path);
+    Some(path)
+}
+
+//---------------------------------------------------------------------------//
+
+/// Iteratively finds the path from the root of the Stern-Brocot tree to a
+/// fraction.
+/// Going left from p/q leads to the subtree of p/(q - p), and going right to
+/// (p - q)/q, so a whole run of steps the same way takes off as many copies of
+/// the smaller part as fit, which is Euclid's algorithm.
+/// The runs are the terms of the continued fraction, the last one less one.
+/// (i.e. 3/5 -> L R L, 22/7 -> R^3 L^6 )
+///
+/// Inputs:
+/// - `e: Expr` The fraction to find.
+///
+/// Outputs: `Option<String>`
+/// The path, as runs of `L` and `R` steps. The root 1/1 is the empty path.
+/// `None` if `e` isn't positive, since the tree only has positive rationals.
+///
+/// Time complexity: O(log(max(nominator, denominator)))
+pub fn path_iterative(e: Expr) -> Option<String> {
+    let (mut p, mut q) = positive(e)?;
+    let mut path = String::new();
+
+    while p != q {
+        // stop before the smaller part runs out, p == q is the end
+        if p < q {
+            let count = (q - 1) / p;
+            push_run(&mut path, 'L', count);
+            q -= count * p;
+        } else {
+            let count = (p - 1) / q;
+            push_run(&mut path, 'R', count);
+            p -= count * q;
+        }
+    }
+
+    Some(path)
+}
+
+//---------------------------------------------------------------------------//
+
+/// Follows a path down the Stern-Brocot tree from the root.
+/// (i.e. L R L -> 3/5, R^3 L^6 -> 3(1/7) )
+///
+/// Inputs:
+/// - `path: &str` The path, as `L` and `R` steps, each optionally followed by
+///   `^count` to repeat it. Whitespace between them is ignored, so `LRL` and
+///   `L R L` are the same path.
+///
+/// Outputs: `Option<Expr>`
+/// The simplified fraction at the end of the path, or `None` if the path has
+/// anything else in it, or leads somewhere that doesn't fit in an `i64`.
+///
+/// Time complexity: O(len(path))
+pub fn from_path(path: &str) -> Option<Expr> {
+    let (mut left, mut right) = ((0i64, 1i64), (1i64, 0i64));
+    let mut chars = path.chars().filter(|c| !c.is_whitespace()).peekable();
+
+    while let Some(step) = chars.next() {
+        let count = if chars.peek() == Some(&'^') {
+            chars.next();
+            let mut digits = String::new();
+            while let Some(c) = chars.next_if(char::is_ascii_digit) {
+                digits.push(c);
+            }
+            digits.parse::<i64>().ok().filter(|k| *k > 0)?
+        } else {
+            1
+        };
+
+        // k steps the same way move the other bound k times over
+        let moved = |from: (i64, i64), by: (i64, i64)| {
+            Some((
+                by.0.checked_mul(count)?.checked_add(from.0)?,
+                by.1.checked_mul(count)?.checked_add(from.1)?,
+            ))
+        };
+        match step {
+            'L' => right = moved(right, left)?,
+            'R' => left = moved(left, right)?,
+            _ => return None,
+        }
+    }
+
+    let (a, b) = (left.0.checked_add(right.0)?, left.1.checked_add(right.1)?);
+    Some(fraction(a, b))
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use crate::{gcd, lcd::Expr};
+
+    /// Checks a sequence of fractions is simplified, in order, and that
+    /// neighbours a/b < c/d are as close as the Farey property says,
+    /// b*c - a*d = 1.
+    fn check(fractions: &[Expr]) {
+        for e in fractions {
+            assert!(e.is_canonical(), "{} is not simplified", e);
+        }
+        for w in fractions.windows(2) {
+            let (a, b) = w[0].to_improper();
+            let (c, d) = w[1].to_improper();
+            assert_eq!(b * c - a * d, 1, "{} and {}", w[0], w[1]);
+        }
+    }
+
+    #[test]
+    fn iterative() {
+        common(super::iterative);
+    }
+
+    #[test]
+    fn recursive() {
+        common(super::recursive);
+    }
+
+    fn common(func: fn(i64) -> Vec<Expr>) {
+        let fields = |n| func(n).iter().map(|e| e.to_improper()).collect::<Vec<_>>();
+
+        assert_eq!(fields(1), [(0, 1), (1, 1)]);
+        assert_eq!(fields(3), [(0, 1), (1, 3), (1, 2), (2, 3), (1, 1)]);
+        assert_eq!(
+            fields(5),
+            [
+                (0, 1),
+                (1, 5),
+                (1, 4),
+                (1, 3),
+                (2, 5),
+                (1, 2),
+                (3, 5),
+                (2, 3),
+                (3, 4),
+                (4, 5),
+                (1, 1)
+            ]
+        );
+        assert_eq!(func(0), []);
+        assert_eq!(func(-3), []);
+
+        for n in 1..60 {
+            let fractions = func(n);
+            check(&fractions);
+
+            // exactly the reduced fractions with a small enough denominator
+            let expected = 1
+                + (1..=n)
+                    .map(|d| (0..d).filter(|m| gcd::iterative(*m, d) == 1).count())
+                    .sum::<usize>();
+            assert_eq!(fractions.len(), expected);
+            assert!(fractions.iter().all(|e| e.d <= n));
+        }
+    }
+
+    #[test]
+    fn tree() {
+        let fields = |depth| {
+            super::tree(depth)
+                .iter()
+                .map(|e| e.to_improper())
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(fields(0), []);
+        assert_eq!(fields(1), [(1, 1)]);
+        assert_eq!(
+            fields(3),
+            [(1, 3), (1, 2), (2, 3), (1, 1), (3, 2), (2, 1), (3, 1)]
+        );
+
+        for depth in 0..12 {
+            let fractions = super::tree(depth);
+            assert_eq!(fractions.len(), (1 << depth) - 1);
+            for e in &fractions {
+                assert!(e.is_canonical(), "{} is not simplified", e);
+            }
+            assert!(fractions.windows(2).all(|w| w[0] < w[1]));
+        }
+
+        // the fractions below 1 in a level, along with 0/1 and 1/1, are a
+        // superset of the matching Farey sequence
+        let tree = super::tree(8);
+        let farey = super::iterative(8);
+        for e in &farey[1..farey.len() - 1] {
+            assert!(tree.contains(e), "{} is missing", e);
+        }
+    }
+
+    #[test]
+    fn path_iterative() {
+        paths(super::path_iterative);
+    }
+
+    #[test]
+    fn path_recursive() {
+        paths(super::path_recursive);
+    }
+
+    fn paths(func: fn(Expr) -> Option<String>) {
+        let path = |c, n, d| func(Expr::new(c, n, d));
+
+        assert_eq!(path(1, 0, 1), Some("".to_string()));
+        assert_eq!(path(0, 3, 5), Some("L R L".to_string()));
+        assert_eq!(path(3, 1, 7), Some("R^3 L^6".to_string()));
+        assert_eq!(path(0, 1, 2), Some("L".to_string()));
+        assert_eq!(path(0, 6, 10), Some("L R L".to_string()));
+        assert_eq!(path(0, -3, -5), Some("L R L".to_string()));
+        assert_eq!(path(5, 0, 1), Some("R^4".to_string()));
+        assert_eq!(path(0, 1, 100_000), Some("L^99999".to_string()));
+
+        // long paths stay short when written as runs
+        assert_eq!(
+            path(i64::MAX, 0, 1),
+            Some("R^9223372036854775806".to_string())
+        );
+        assert_eq!(
+            path(0, 1, i64::MAX),
+            Some("L^9223372036854775806".to_string())
+        );
+        assert_eq!(
+            path(0, i64::MAX - 1, i64::MAX),
+            Some("L R^9223372036854775805".to_string())
+        );
+
+        assert_eq!(path(0, 0, 1), None);
+        assert_eq!(path(0, -1, 2), None);
+        assert_eq!(path(-1, 1, 2), None);
+        assert_eq!(path(0, 1, 0), None);
+
+        // every node in the tree is found at the end of its own path
+        let tree = super::tree(10);
+        for e in &tree {
+            let p = func(*e).unwrap();
+            assert_eq!(super::from_path(&p), Some(*e), "{}", p);
+        }
+    }
+
+    #[test]
+    fn from_path() {
+        assert_eq!(super::from_path(""), Some(Expr::new(1, 0, 1)));
+        assert_eq!(super::from_path("LRL"), Some(Expr::new(0, 3, 5)));
+        assert_eq!(super::from_path("L R L"), Some(Expr::new(0, 3, 5)));
+        assert_eq!(super::from_path("RRRLLLLLL"), Some(Expr::new(3, 1, 7)));
+        assert_eq!(super::from_path("R^3 L^6"), Some(Expr::new(3, 1, 7)));
+        assert_eq!(super::from_path("R^2R L^5L"), Some(Expr::new(3, 1, 7)));
+        assert_eq!(
+            super::from_path("R^9223372036854775806"),
+            Some(Expr::new(i64::MAX, 0, 1))
+        );
+        assert_eq!(super::from_path("LXR"), None);
+        assert_eq!(super::from_path("L^"), None);
+        assert_eq!(super::from_path("L^0"), None);
+        assert_eq!(super::from_path("^2"), None);
+        assert_eq!(super::from_path("R^9223372036854775807"), None);
+        assert_eq!(super::from_path(&"LR".repeat(100)), None);
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
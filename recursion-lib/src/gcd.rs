@@ -0,0 +1,204 @@
+////////////////////////////////////////////////////////////////////////////////
+
+use tailcall::tailcall;
+
+use crate::lcd::Integer;
+
+////////////////////////////////////////////////////////////////////////////////
+
+/// Recursively computes the Greatest Common Divisor of `a` and `b` using the
+/// Euclidean algorithm.
+///
+/// Inputs:
+/// - `a: T`
+/// - `b: T`
+///
+/// Outputs: `T`
+/// The largest integer dividing both `a` and `b`. Non-negative whenever it
+/// fits, the one exception being a gcd of exactly `T::MIN`
+/// (i.e. gcd(i64::MIN, 0)), which stays negative rather than overflowing.
+/// gcd(0, 0) is defined as 0.
+///
+/// Time complexity: O(log(min(a, b)))
+pub fn recursive<T: Integer>(a: T, b: T) -> T {
+    #[tailcall]
+    fn inner<T: Integer>(a: T, b: T) -> T {
+        if b.is_zero() {
+            // terminating case, gcd(a, 0) = |a|
+            abs(a)
+        } else {
+            // general case, gcd(a, b) = gcd(b, a mod b)
+            // (the only remainder that can fail with b != 0 is MIN mod -1,
+            // which is 0 anyway)
+            let r = a.checked_rem(&b).unwrap_or_else(T::zero);
+            inner(b, r)
+        }
+    }
+
+    inner(a, b)
+}
+
+//---------------------------------------------------------------------------//
+
+/// Iteratively computes the Greatest Common Divisor of `a` and `b` using the
+/// Euclidean algorithm.
+///
+/// Inputs:
+/// - `a: T`
+/// - `b: T`
+///
+/// Outputs: `T`
+/// The largest integer dividing both `a` and `b`. Non-negative whenever it
+/// fits, the one exception being a gcd of exactly `T::MIN`
+/// (i.e. gcd(i64::MIN, 0)), which stays negative rather than overflowing.
+/// gcd(0, 0) is defined as 0.
+///
+/// Time complexity: O(log(min(a, b)))
+pub fn iterative<T: Integer>(mut a: T, mut b: T) -> T {
+    // keep replacing (a, b) with (b, a mod b) until the remainder runs out
+    while !b.is_zero() {
+        // (the only remainder that can fail with b != 0 is MIN mod -1, which
+        // is 0 anyway)
+        let r = a.checked_rem(&b).unwrap_or_else(T::zero);
+        a = b;
+        b = r;
+    }
+
+    abs(a)
+}
+
+/// |a|, leaving `T::MIN` as it is since its absolute value doesn't fit.
+/// Dividing by it still gives the right fraction.
+fn abs<T: Integer>(a: T) -> T {
+    if a.is_negative() {
+        a.checked_neg().unwrap_or(a)
+    } else {
+        a
+    }
+}
+
+//---------------------------------------------------------------------------//
+
+/// Computes the Greatest Common Divisor of `a` and `b` using the binary (Stein)
+/// algorithm.
+/// Only uses shifts, subtraction and comparisons, no division.
+///
+/// Inputs:
+/// - `a: i64`
+/// - `b: i64`
+///
+/// Outputs: `i64`
+/// The largest integer dividing both `a` and `b`. Always non-negative.
+/// gcd(0, 0) is defined as 0.
+///
+/// Time complexity: O(log(a) + log(b))
+pub fn binary(a: i64, b: i64) -> i64 {
+    let mut a = a.unsigned_abs();
+    let mut b = b.unsigned_abs();
+
+    // basic edge cases, gcd(a, 0) = a
+    if a == 0 {
+        return b as i64;
+    }
+    if b == 0 {
+        return a as i64;
+    }
+
+    // gcd(2^k * a, 2^k * b) = 2^k * gcd(a, b)
+    // remember the shared power of two and strip it from both
+    let k = (a | b).trailing_zeros();
+    a >>= a.trailing_zeros();
+
+    loop {
+        // a is always odd here, so any factor of two in b isn't shared
+        b >>= b.trailing_zeros();
+
+        // gcd(a, b) = gcd(a, b - a), keeping a <= b
+        if a > b {
+            std::mem::swap(&mut a, &mut b);
+        }
+        b -= a;
+
+        if b == 0 {
+            break;
+        }
+    }
+
+    (a << k) as i64
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use crate::lcd::BigInt;
+
+    #[test]
+    fn iterative() {
+        common(super::iterative);
+    }
+
+    #[test]
+    fn recursive() {
+        common(super::recursive);
+    }
+
+    #[test]
+    fn binary() {
+        common(super::binary);
+    }
+
+    #[test]
+    fn generic() {
+        use super::{iterative, recursive};
+
+        assert_eq!((iterative(21i32, 45), recursive(21i32, 45)), (3, 3));
+        assert_eq!((iterative(21i128, -45), recursive(21i128, -45)), (3, 3));
+        assert_eq!((iterative(i128::MIN, 6), recursive(i128::MIN, 6)), (2, 2));
+
+        // the one gcd that can't be made positive
+        assert_eq!(iterative(i64::MIN, 0), i64::MIN);
+        assert_eq!(recursive(i64::MIN, 0), i64::MIN);
+        assert_eq!(iterative(i64::MIN, i64::MIN), i64::MIN);
+        assert_eq!(recursive(i64::MIN, i64::MIN), i64::MIN);
+
+        let big = BigInt::from(1) << 200;
+        assert_eq!(iterative(&big * 3, &big * -6), &big * 3);
+        assert_eq!(recursive(&big * 3, &big * -6), &big * 3);
+        assert_eq!(recursive(BigInt::from(21), BigInt::from(45)), 3.into());
+    }
+
+    fn common(func: fn(i64, i64) -> i64) {
+        assert_eq!(func(0, 0), 0);
+        assert_eq!(func(0, 7), 7);
+        assert_eq!(func(7, 0), 7);
+        assert_eq!(func(20, 22), 2);
+        assert_eq!(func(21, 45), 3);
+        assert_eq!(func(48, 18), 6);
+        assert_eq!(func(17, 5), 1);
+        assert_eq!(func(-48, 18), 6);
+        assert_eq!(func(48, -18), 6);
+        assert_eq!(func(-48, -18), 6);
+        assert_eq!(func(1 << 40, 1 << 20), 1 << 20);
+        assert_eq!(func(1_000_000_007 * 6, 1_000_000_007 * 4), 2_000_000_014);
+        assert_eq!(func(i64::MIN, -1), 1);
+        assert_eq!(func(i64::MIN, 6), 2);
+        assert_eq!(func(i64::MAX, i64::MIN), 1);
+
+        // compare against a slow but obviously correct search
+        for a in -60..60i64 {
+            for b in -60..60i64 {
+                let expected = (1..=a.abs().max(b.abs()))
+                    .rev()
+                    .find(|m| a % m == 0 && b % m == 0)
+                    .unwrap_or(0);
+
+                let real = func(a, b);
+                println!("gcd({}, {}) = {} >< {}", a, b, real, expected);
+                assert_eq!(real, expected);
+            }
+        }
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
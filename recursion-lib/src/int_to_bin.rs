@@ -2,6 +2,8 @@
 
 use tailcall::tailcall;
 
+use crate::trace::{Call, Traced};
+
 ////////////////////////////////////////////////////////////////////////////////
 /* p-code
 
@@ -64,30 +66,20 @@ function recursive(x) {
 /// Time complexity: O(1)*
 ///
 /// * It should be noted that while this function takes constant time, it is
-/// expensive internally due to repeated math operations and may be out
-/// performed by more bit-efficient algorithms.
+///   expensive internally due to repeated math operations and may be out
+///   performed by more bit-efficient algorithms.
 ///
 pub fn recursive(x: i64) -> [bool; 64] {
     #[tailcall]
     /// Generate the binary representation
     fn inner(x: i64, r: &mut [bool; 64], i: usize) {
-        // Starting from the outermost bit, work inwards by checking if the value
-        // associated with that bit is in the remaining total.
-        // If it is, that means that bit should be on and we'll need to update our
-        // running total.
+        // Starting from the outermost bit, work inwards one bit at a time.
         // Here, we'll do this recursively so we'll need the `i` tracking variable.
-        let y = x - 2i64.pow(i.try_into().unwrap());
-        if y >= 0 {
-            // Current total contains the current bit value
-            // Switch bit on
-            r[i] = true;
-            if i > 0 {
-                // Continue generating binary
-                inner(y, r, i - 1)
-            }
-        } else if i > 0 {
+        let (on, rest) = bit(x, i);
+        r[i] = on;
+        if i > 0 {
             // Continue generating binary
-            inner(x, r, i - 1)
+            inner(rest, r, i - 1)
         }
     }
 
@@ -107,6 +99,89 @@ pub fn recursive(x: i64) -> [bool; 64] {
     }
 }
 
+/// Checks whether the value of bit `i` is in the remaining total `x`.
+/// If it is, that means that bit should be on and we'll need to update our
+/// running total.
+/// Returns whether the bit is on, and the total left for the bits below it.
+fn bit(x: i64, i: usize) -> (bool, i64) {
+    let y = x - 2i64.pow(i.try_into().unwrap());
+    if y >= 0 {
+        // Current total contains the current bit value
+        (true, y)
+    } else {
+        (false, x)
+    }
+}
+
+//---------------------------------------------------------------------------//
+
+/// Recursively writes `x` in binary, recording each call of `inner` along with
+/// the bit it decided on. See [`recursive`].
+///
+/// Inputs:
+/// - `x: i64`
+/// - `max_depth: usize` How many levels of calls to record, at least 1. Calls
+///   below that are only counted.
+///
+/// Outputs: `([bool; 64], Call)`
+/// The integer `x` expressed in binary, and the calls it took to get there.
+///
+/// Time complexity: O(1)
+///
+pub fn recursive_traced(x: i64, max_depth: usize) -> ([bool; 64], Call) {
+    /// Generate the binary representation
+    /// Not a tail call here, since the call has to be recorded once the rest
+    /// of the bits are done.
+    fn inner(x: i64, r: &mut [bool; 64], i: usize, max_depth: usize) -> Traced {
+        let (on, rest) = bit(x, i);
+        r[i] = on;
+
+        let children = if i == 0 {
+            vec![]
+        } else {
+            vec![inner(rest, r, i - 1, max_depth.saturating_sub(1))]
+        };
+        Traced::record(
+            max_depth,
+            || format!("inner({}, {})", x, i),
+            || format!("bit {} {}", i, if on { "on" } else { "off" }),
+            children,
+        )
+    }
+
+    let max_depth = max_depth.max(1);
+    let mut res = [false; 64];
+    let children = if x == 0 {
+        vec![]
+    } else {
+        res[63] = x.signum() < 0;
+        vec![inner(x.abs(), &mut res, 62, max_depth - 1)]
+    };
+
+    let call = Traced::record(
+        max_depth,
+        || format!("recursive({})", x),
+        || digits(&res),
+        children,
+    );
+    (res, call.into_call())
+}
+
+/// Writes bits most significant first, with a `-` for the sign bit and
+/// without leading zeros.
+/// (i.e. -11 -> -1011 )
+fn digits(bits: &[bool; 64]) -> String {
+    let digits = bits[..63]
+        .iter()
+        .rev()
+        .skip_while(|b| !**b)
+        .map(|b| if *b { '1' } else { '0' })
+        .collect::<String>();
+    let sign = if bits[63] { "-" } else { "" };
+    let digits = if digits.is_empty() { "0" } else { &digits };
+    format!("{}{}", sign, digits)
+}
+
 //---------------------------------------------------------------------------//
 
 /// Iteratively writes `x` in binary
@@ -121,10 +196,32 @@ pub fn recursive(x: i64) -> [bool; 64] {
 /// Time complexity: O(1)*
 ///
 /// * It should be noted that while this function takes constant time, it is
-/// expensive internally due to repeated math operations and may be out
-/// performed by more bit-efficient algorithms.
+///   expensive internally due to repeated math operations and may be out
+///   performed by more bit-efficient algorithms.
 ///
-pub fn iterative(mut x: i64) -> [bool; 64] {
+pub fn iterative(x: i64) -> [bool; 64] {
+    bits_with(x, &mut |_| {})
+}
+
+/// Iteratively writes `x` in binary, recording each bit as it's decided.
+/// See [`iterative`].
+///
+/// Inputs:
+/// - `x: i64`
+///
+/// Outputs: `([bool; 64], Vec<Step>)`
+/// The integer `x` expressed in binary, and the steps taken to get there.
+///
+/// Time complexity: O(1)
+///
+pub fn iterative_traced(x: i64) -> ([bool; 64], Vec<Step>) {
+    let mut steps = vec![];
+    let res = bits_with(x, &mut |step| steps.push(step));
+    (res, steps)
+}
+
+/// The iterative conversion, reporting each step to `record`.
+fn bits_with(mut x: i64, record: &mut impl FnMut(Step)) -> [bool; 64] {
     let mut res = [false; 64];
 
     //  0 -> 000...0000 --- 0 --- 0 --- 0 ---|
@@ -143,6 +240,7 @@ pub fn iterative(mut x: i64) -> [bool; 64] {
 
     // handle negative numbers
     if x.signum() < 0 {
+        record(Step::Negative(x));
         res[63] = true;
         x = x.abs();
     }
@@ -152,22 +250,69 @@ pub fn iterative(mut x: i64) -> [bool; 64] {
     // if it is, that means that bit should be on and we'll need to update our
     // running total.
     for i in (0..63usize).rev() {
-        let y = x - 2i64.pow(i.try_into().unwrap());
+        let value = 2i64.pow(i.try_into().unwrap());
+        let y = x - value;
         if y >= 0 {
             // Current total contains the current bit value
             // Switch bit on
+            record(Step::On {
+                bit: i,
+                value,
+                left: x,
+            });
             res[i] = true;
             x = y;
+        } else {
+            record(Step::Off {
+                bit: i,
+                value,
+                left: x,
+            });
         }
     }
 
     res
 }
 
+//---------------------------------------------------------------------------//
+
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+/// A single decision made while writing an integer in binary.
+/// See [`iterative_traced`].
+pub enum Step {
+    /// The integer was negative, so the sign bit was switched on and the rest
+    /// worked out for its absolute value.
+    Negative(i64),
+    /// The bit's value fit in what was `left`, so it was switched on and
+    /// subtracted.
+    On { bit: usize, value: i64, left: i64 },
+    /// The bit's value was more than what was `left`, so it stayed off.
+    Off { bit: usize, value: i64, left: i64 },
+}
+
+impl std::fmt::Display for Step {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Step::Negative(x) => f.write_fmt(format_args!("{} is negative, bit 63 is on", x)),
+            Step::On { bit, value, left } => f.write_fmt(format_args!(
+                "{} - {} = {}, bit {} is on",
+                left,
+                value,
+                left - value,
+                bit
+            )),
+            Step::Off { bit, value, left } => {
+                f.write_fmt(format_args!("{} > {}, bit {} is off", value, left, bit))
+            }
+        }
+    }
+}
+
 ////////////////////////////////////////////////////////////////////////////////
 
 #[cfg(test)]
 mod tests {
+    use super::Step;
 
     #[test]
     fn iterative() {
@@ -179,6 +324,70 @@ mod tests {
         common(super::recursive);
     }
 
+    #[test]
+    fn iterative_traced() {
+        common(|x| super::iterative_traced(x).0);
+
+        let (_, steps) = super::iterative_traced(-11);
+        assert_eq!(steps.len(), 64);
+        assert_eq!(steps[0], Step::Negative(-11));
+        assert!(steps[1..60].iter().all(|s| matches!(s, Step::Off { .. })));
+        assert_eq!(
+            steps[60..],
+            [
+                Step::On {
+                    bit: 3,
+                    value: 8,
+                    left: 11
+                },
+                Step::Off {
+                    bit: 2,
+                    value: 4,
+                    left: 3
+                },
+                Step::On {
+                    bit: 1,
+                    value: 2,
+                    left: 3
+                },
+                Step::On {
+                    bit: 0,
+                    value: 1,
+                    left: 1
+                },
+            ]
+        );
+        assert_eq!(steps[60].to_string(), "11 - 8 = 3, bit 3 is on");
+        assert_eq!(steps[61].to_string(), "4 > 3, bit 2 is off");
+        assert_eq!(steps[0].to_string(), "-11 is negative, bit 63 is on");
+    }
+
+    #[test]
+    fn recursive_traced() {
+        common(|x| super::recursive_traced(x, usize::MAX).0);
+
+        let (_, call) = super::recursive_traced(-11, usize::MAX);
+        assert_eq!(call.result, "-1011");
+        assert_eq!(call.depth(), 64);
+        let text = call.to_text(usize::MAX);
+        let lines = text.lines().collect::<Vec<_>>();
+        assert_eq!(lines[0], "recursive(-11) = -1011");
+        assert_eq!(lines[1], "  inner(11, 62) = bit 62 off");
+        assert_eq!(lines[60].trim(), "inner(11, 3) = bit 3 on");
+        assert_eq!(lines[61].trim(), "inner(3, 2) = bit 2 off");
+        assert_eq!(lines[63].trim(), "inner(1, 0) = bit 0 on");
+
+        let (_, call) = super::recursive_traced(0, usize::MAX);
+        assert_eq!(call.to_text(usize::MAX), "recursive(0) = 0\n");
+
+        let (res, call) = super::recursive_traced(-11, 2);
+        assert_eq!(res, super::recursive(-11));
+        assert_eq!(
+            call.to_text(usize::MAX),
+            "recursive(-11) = -1011\n  inner(11, 62) = bit 62 off\n    ... 62 more calls\n"
+        );
+    }
+
     fn common(func: fn(i64) -> [bool; 64]) {
         let mut ans = [false; 64];
         assert_eq!(func(0), ans);
@@ -1,210 +1,866 @@
 ////////////////////////////////////////////////////////////////////////////////
 
-use tailcall::tailcall;
+use std::cmp::Ordering;
+
+mod common;
+pub mod decimal;
+mod float;
+mod integer;
+mod parse;
+
+pub use common::{compare, least_common_denominator, over_common_denominator, sum};
+pub use decimal::RepeatingDecimal;
+pub use integer::Integer;
+pub use num_bigint::BigInt;
+pub use parse::{ParseExprError, ParseExprErrorKind};
+
+use crate::gcd;
+use crate::trace::{Call, Traced};
 
 ////////////////////////////////////////////////////////////////////////////////
 
-#[derive(Debug, Default, PartialEq, Eq, Copy, Clone)]
+#[derive(Debug, Default, Copy, Clone)]
 /// A basic expression.
 /// It consists of:
 /// - A fraction
 /// - A co-efficient
-pub struct Expr {
+///
+/// Together they form a mixed number, written `c(n/d)`.
+/// Like on paper, the sign applies to the whole number, so `-2(1/5)` is
+/// `-(2 + 1/5)`, and when there is no whole part the sign sits on the
+/// nominator instead, as in `0(-1/5)`.
+///
+/// Simplified expressions always have:
+/// - A positive denominator
+/// - A nominator smaller than the denominator, with no common factors
+/// - A non-negative nominator whenever the co-efficient isn't zero
+///
+/// Expressions are compared by value, so `1(1/2)` is equal to `0(3/2)`.
+/// Arithmetic is exact and always returns a simplified expression.
+///
+/// The fields can be any [`Integer`]: `i32`, `i64` (the default) and `i128`
+/// for small fast fractions, or [`BigInt`] for exact ones that never
+/// overflow.
+pub struct Expr<T: Integer = i64> {
     /// The co-efficient
-    pub c: i64,
+    pub c: T,
     /// The nominator of the fraction
-    pub n: i64,
+    pub n: T,
     /// The denominator of the fraction
-    pub d: i64,
+    pub d: T,
 }
 
 //---------------------------------------------------------------------------//
 
-impl Expr {
-    pub fn new(c: i64, n: i64, d: i64) -> Self {
+impl<T: Integer> Expr<T> {
+    pub fn new(c: T, n: T, d: T) -> Self {
         Self { c, n, d }
     }
+
+    /// Creates an expression, checking that it is already simplified.
+    ///
+    /// Inputs:
+    /// - `c: T` The co-efficient.
+    /// - `n: T` The nominator.
+    /// - `d: T` The denominator.
+    ///
+    /// Outputs: `Result<Expr<T>, LcdError<T>>`
+    /// The expression, or why it isn't a valid simplified expression.
+    pub fn try_new(c: T, n: T, d: T) -> Result<Self, LcdError<T>> {
+        let e = Self::new(c, n, d);
+
+        if e.d.is_zero() {
+            Err(LcdError::ZeroDenominator)
+        } else if !e.is_canonical() {
+            Err(LcdError::NonCanonical(e))
+        } else {
+            Ok(e)
+        }
+    }
+
+    /// Folds the co-efficient into the fraction.
+    /// (i.e. 2(1/5) -> 11/5, -2(1/5) -> -11/5 )
+    ///
+    /// Outputs: `(T, T)`
+    /// The nominator and denominator of the equivalent improper fraction.
+    ///
+    /// Panics if the nominator doesn't fit in `T`.
+    pub fn to_improper(&self) -> (T, T) {
+        self.checked_improper()
+            .expect("attempt to convert to an improper fraction with overflow")
+    }
+
+    /// Same as [`Expr::to_improper`], but returns `None` on overflow.
+    fn checked_improper(&self) -> Option<(T, T)> {
+        let whole = self.c.checked_mul(&self.d)?;
+
+        if self.c.is_negative() {
+            // the sign belongs to the whole number, so the fraction pulls
+            // it further away from zero
+            Some((whole.checked_sub(&self.n)?, self.d.clone()))
+        } else {
+            Some((whole.checked_add(&self.n)?, self.d.clone()))
+        }
+    }
+
+    /// Checks whether the expression is already in its simplified form.
+    /// See [`Expr`] for the exact rules.
+    pub fn is_canonical(&self) -> bool {
+        // d > 0, so -d can't overflow
+        self.d > T::zero()
+            && self.n < self.d
+            && self.d.checked_neg().is_some_and(|neg| neg < self.n)
+            && (self.c.is_zero() || !self.n.is_negative())
+            && gcd::iterative(self.n.clone(), self.d.clone()) == T::one()
+    }
 }
 
 //---------------------------------------------------------------------------//
 
-impl std::fmt::Display for Expr {
+impl Expr {
+    /// Same as [`Expr::to_improper`], but in `i128` so it can't overflow, and
+    /// with the sign moved onto the nominator.
+    pub(crate) fn to_improper_wide(self) -> (i128, i128) {
+        let (c, n, d) = (self.c as i128, self.n as i128, self.d as i128);
+        let (n, d) = if c < 0 {
+            (c * d - n, d)
+        } else {
+            (c * d + n, d)
+        };
+
+        if d < 0 {
+            (-n, -d)
+        } else {
+            (n, d)
+        }
+    }
+}
+
+//---------------------------------------------------------------------------//
+
+impl<T: Integer> std::fmt::Display for Expr<T> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.write_fmt(format_args!("{}({}/{})", self.c, self.n, self.d))
     }
 }
 
+//---------------------------------------------------------------------------//
+
+impl<T: Integer> Expr<T> {
+    /// The simplified form, used to compare expressions by value.
+    /// It's worked out as a [`BigInt`], since the simplified form of a valid
+    /// expression might not fit in `T` (i.e. i64::MAX(3/2) -> 2^63(1/2) ).
+    /// Expressions with a zero denominator are undefined and are left as-is.
+    fn key(&self) -> Expr<BigInt> {
+        recursive(Expr::new(
+            self.c.to_bigint(),
+            self.n.to_bigint(),
+            self.d.to_bigint(),
+        ))
+    }
+}
+
+impl<T: Integer> PartialEq for Expr<T> {
+    fn eq(&self, other: &Self) -> bool {
+        let (lhs, rhs) = (self.key(), other.key());
+        (lhs.c, lhs.n, lhs.d) == (rhs.c, rhs.n, rhs.d)
+    }
+}
+
+impl<T: Integer> Eq for Expr<T> {}
+
+impl<T: Integer> std::hash::Hash for Expr<T> {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        let key = self.key();
+        (key.c, key.n, key.d).hash(state);
+    }
+}
+
+impl<T: Integer> PartialOrd for Expr<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Compares a/b with x/y, where both are between 0 and 1 (0 <= a < b and
+/// 0 <= x < y), without multiplying anything so it can't overflow.
+/// Flipping both fractions over reverses the order, and b/a vs y/x can be
+/// decided by their whole parts, or failing that, by what's left over.
+fn cmp_proper<T: Integer>(mut a: T, mut b: T, mut x: T, mut y: T) -> Ordering {
+    let mut flipped = false;
+
+    let res = loop {
+        if a.is_zero() || x.is_zero() {
+            // zero is smaller than anything else in range
+            break x.is_zero().cmp(&a.is_zero());
+        }
+
+        // all positive, so none of these can fail
+        let p = b.checked_div(&a).unwrap();
+        let q = y.checked_div(&x).unwrap();
+        if p != q {
+            // a bigger whole part means a smaller fraction
+            break q.cmp(&p);
+        }
+
+        (a, b) = (b.checked_rem(&a).unwrap(), a);
+        (x, y) = (y.checked_rem(&x).unwrap(), x);
+        flipped = !flipped;
+    };
+
+    if flipped {
+        res.reverse()
+    } else {
+        res
+    }
+}
+
+impl<T: Integer> Ord for Expr<T> {
+    /// Orders expressions by value.
+    /// Undefined expressions (zero denominator) sort before everything else.
+    fn cmp(&self, other: &Self) -> Ordering {
+        let (lhs, rhs) = (self.key(), other.key());
+        match (lhs.d.is_zero(), rhs.d.is_zero()) {
+            (true, true) => (lhs.c, lhs.n, lhs.d).cmp(&(rhs.c, rhs.n, rhs.d)),
+            (true, false) => Ordering::Less,
+            (false, true) => Ordering::Greater,
+            (false, false) => {
+                // once simplified, each co-efficient covers its own range of
+                // values, c(n/d) for c > 0 is in [c, c+1), for c < 0 it's in
+                // (c-1, c], and 0(n/d) is in (-1, 1)
+                // so only the fractions are left when the co-efficients match
+                let abs = |n: &BigInt| {
+                    if n.is_negative() {
+                        n.checked_neg().unwrap()
+                    } else {
+                        n.clone()
+                    }
+                };
+                let fractions =
+                    || cmp_proper(abs(&lhs.n), lhs.d.clone(), abs(&rhs.n), rhs.d.clone());
+
+                lhs.c.cmp(&rhs.c).then_with(|| {
+                    if lhs.c.is_negative() {
+                        // further below zero
+                        fractions().reverse()
+                    } else if !lhs.c.is_zero() {
+                        fractions()
+                    } else {
+                        match (lhs.n.is_negative(), rhs.n.is_negative()) {
+                            (true, true) => fractions().reverse(),
+                            (false, false) => fractions(),
+                            (true, false) => Ordering::Less,
+                            (false, true) => Ordering::Greater,
+                        }
+                    }
+                })
+            }
+        }
+    }
+}
+
+//---------------------------------------------------------------------------//
+
+impl<T: Integer> Expr<T> {
+    /// -(c(n/d)) -> (-c)(n/d) OR -(0(n/d)) -> 0(-n/d)
+    ///
+    /// Outputs: `Result<Expr<T>, LcdError<T>>`
+    /// The simplified negation, or why it couldn't be found.
+    pub fn checked_neg(&self) -> Result<Self, LcdError<T>> {
+        try_recursive(self.negate()?)
+    }
+
+    /// a/b + x/y -> (a*y + x*b)/(b*y)
+    ///
+    /// Outputs: `Result<Expr<T>, LcdError<T>>`
+    /// The simplified sum, or why it couldn't be found.
+    pub fn checked_add(&self, rhs: &Self) -> Result<Self, LcdError<T>> {
+        try_recursive(self.add_raw(rhs)?)
+    }
+
+    /// a/b - x/y -> (a*y - x*b)/(b*y)
+    ///
+    /// Outputs: `Result<Expr<T>, LcdError<T>>`
+    /// The simplified difference, or why it couldn't be found.
+    pub fn checked_sub(&self, rhs: &Self) -> Result<Self, LcdError<T>> {
+        try_recursive(self.sub_raw(rhs)?)
+    }
+
+    /// a/b * x/y -> (a*x)/(b*y)
+    ///
+    /// Outputs: `Result<Expr<T>, LcdError<T>>`
+    /// The simplified product, or why it couldn't be found.
+    pub fn checked_mul(&self, rhs: &Self) -> Result<Self, LcdError<T>> {
+        try_recursive(self.mul_raw(rhs)?)
+    }
+
+    /// a/b / x/y -> (a*y)/(b*x)
+    ///
+    /// Outputs: `Result<Expr<T>, LcdError<T>>`
+    /// The simplified quotient, or why it couldn't be found.
+    /// `LcdError::ZeroDenominator` if `rhs` is zero.
+    pub fn checked_div(&self, rhs: &Self) -> Result<Self, LcdError<T>> {
+        try_recursive(self.div_raw(rhs)?)
+    }
+
+    // The operations before simplifying, so the operators can pass undefined
+    // expressions through like `recursive` does.
+
+    fn negate(&self) -> Result<Self, LcdError<T>> {
+        if self.c.is_zero() {
+            let n = self.n.checked_neg().ok_or(LcdError::Overflow)?;
+            Ok(Expr::new(T::zero(), n, self.d.clone()))
+        } else {
+            let c = self.c.checked_neg().ok_or(LcdError::Overflow)?;
+            Ok(Expr::new(c, self.n.clone(), self.d.clone()))
+        }
+    }
+
+    fn add_raw(&self, rhs: &Self) -> Result<Self, LcdError<T>> {
+        let (a, b, x, y) = improper_pair(self, rhs)?;
+        let n = cross(&a, &y)?.checked_add(&cross(&x, &b)?);
+        Ok(Expr::new(
+            T::zero(),
+            n.ok_or(LcdError::Overflow)?,
+            cross(&b, &y)?,
+        ))
+    }
+
+    fn sub_raw(&self, rhs: &Self) -> Result<Self, LcdError<T>> {
+        let (a, b, x, y) = improper_pair(self, rhs)?;
+        let n = cross(&a, &y)?.checked_sub(&cross(&x, &b)?);
+        Ok(Expr::new(
+            T::zero(),
+            n.ok_or(LcdError::Overflow)?,
+            cross(&b, &y)?,
+        ))
+    }
+
+    fn mul_raw(&self, rhs: &Self) -> Result<Self, LcdError<T>> {
+        let (a, b, x, y) = improper_pair(self, rhs)?;
+        Ok(Expr::new(T::zero(), cross(&a, &x)?, cross(&b, &y)?))
+    }
+
+    fn div_raw(&self, rhs: &Self) -> Result<Self, LcdError<T>> {
+        let (a, b, x, y) = improper_pair(self, rhs)?;
+        Ok(Expr::new(T::zero(), cross(&a, &y)?, cross(&b, &x)?))
+    }
+}
+
+/// Both expressions as improper fractions, a/b and x/y.
+fn improper_pair<T: Integer>(lhs: &Expr<T>, rhs: &Expr<T>) -> Result<(T, T, T, T), LcdError<T>> {
+    let (a, b) = lhs.checked_improper().ok_or(LcdError::Overflow)?;
+    let (x, y) = rhs.checked_improper().ok_or(LcdError::Overflow)?;
+    Ok((a, b, x, y))
+}
+
+/// A checked product, as an [`LcdError`].
+fn cross<T: Integer>(a: &T, b: &T) -> Result<T, LcdError<T>> {
+    a.checked_mul(b).ok_or(LcdError::Overflow)
+}
+
+/// Simplifies the result of an operator, panicking on overflow like integer
+/// arithmetic does.
+fn or_panic<T: Integer>(res: Result<Expr<T>, LcdError<T>>, op: &str) -> Expr<T> {
+    match res {
+        Ok(e) => recursive(e),
+        Err(_) => panic!("attempt to {} with overflow", op),
+    }
+}
+
+impl<T: Integer> std::ops::Neg for Expr<T> {
+    type Output = Expr<T>;
+
+    /// -(c(n/d)) -> (-c)(n/d) OR -(0(n/d)) -> 0(-n/d)
+    fn neg(self) -> Expr<T> {
+        or_panic(self.negate(), "negate")
+    }
+}
+
+impl<T: Integer> std::ops::Add for Expr<T> {
+    type Output = Expr<T>;
+
+    /// a/b + x/y -> (a*y + x*b)/(b*y)
+    fn add(self, rhs: Expr<T>) -> Expr<T> {
+        or_panic(self.add_raw(&rhs), "add")
+    }
+}
+
+impl<T: Integer> std::ops::Sub for Expr<T> {
+    type Output = Expr<T>;
+
+    /// a/b - x/y -> (a*y - x*b)/(b*y)
+    fn sub(self, rhs: Expr<T>) -> Expr<T> {
+        or_panic(self.sub_raw(&rhs), "subtract")
+    }
+}
+
+impl<T: Integer> std::ops::Mul for Expr<T> {
+    type Output = Expr<T>;
+
+    /// a/b * x/y -> (a*x)/(b*y)
+    fn mul(self, rhs: Expr<T>) -> Expr<T> {
+        or_panic(self.mul_raw(&rhs), "multiply")
+    }
+}
+
+impl<T: Integer> std::ops::Div for Expr<T> {
+    type Output = Expr<T>;
+
+    /// a/b / x/y -> (a*y)/(b*x)
+    ///
+    /// Panics if `rhs` is zero, just like integer division.
+    fn div(self, rhs: Expr<T>) -> Expr<T> {
+        if rhs.n.is_zero() && rhs.c.is_zero() {
+            panic!("attempt to divide by zero");
+        }
+        or_panic(self.div_raw(&rhs), "divide")
+    }
+}
+
+impl<T: Integer> std::ops::AddAssign for Expr<T> {
+    fn add_assign(&mut self, rhs: Expr<T>) {
+        *self = std::mem::take(self) + rhs;
+    }
+}
+
+impl<T: Integer> std::ops::SubAssign for Expr<T> {
+    fn sub_assign(&mut self, rhs: Expr<T>) {
+        *self = std::mem::take(self) - rhs;
+    }
+}
+
+impl<T: Integer> std::ops::MulAssign for Expr<T> {
+    fn mul_assign(&mut self, rhs: Expr<T>) {
+        *self = std::mem::take(self) * rhs;
+    }
+}
+
+impl<T: Integer> std::ops::DivAssign for Expr<T> {
+    fn div_assign(&mut self, rhs: Expr<T>) {
+        *self = std::mem::take(self) / rhs;
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+/// Reasons an expression can't be simplified or constructed.
+pub enum LcdError<T: Integer = i64> {
+    /// The denominator is zero, so the fraction is undefined.
+    ZeroDenominator,
+    /// An intermediate or final value doesn't fit in the integer type.
+    Overflow,
+    /// The expression is valid, but not in its simplified form.
+    NonCanonical(Expr<T>),
+    /// A floating point value is infinite or NaN, so it isn't a fraction.
+    NotFinite,
+}
+
+//---------------------------------------------------------------------------//
+
+impl<T: Integer> std::fmt::Display for LcdError<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LcdError::ZeroDenominator => f.write_str("denominator is zero"),
+            LcdError::Overflow => f.write_str("value does not fit in the integer type"),
+            LcdError::NonCanonical(e) => {
+                f.write_fmt(format_args!("{} is not in its simplified form", e))
+            }
+            LcdError::NotFinite => f.write_str("value is not finite"),
+        }
+    }
+}
+
+impl<T: Integer> std::error::Error for LcdError<T> {}
+
 ////////////////////////////////////////////////////////////////////////////////
 
+/// Splits a reduced improper fraction with a positive denominator back into a
+/// mixed number.
+/// (i.e. 11/5 -> 2(1/5), -11/5 -> -2(1/5), -1/5 -> 0(-1/5) )
+fn split<T: Integer>(n: T, d: T) -> Expr<T> {
+    // d > 0, so neither of these can fail
+    let c = n.checked_div(&d).unwrap();
+    let r = n.checked_rem(&d).unwrap();
+
+    if c.is_zero() {
+        // no whole part, so the nominator keeps the sign
+        Expr::new(c, n, d)
+    } else if r.is_negative() {
+        // the whole part carries the sign
+        // |r| < d, so this can't overflow either
+        Expr::new(c, r.checked_neg().unwrap(), d)
+    } else {
+        Expr::new(c, r, d)
+    }
+}
+
+/// Divides both n and d by their greatest common factor, which is never zero
+/// here since d isn't.
+fn divide<T: Integer>(n: &T, d: &T, by: &T) -> Result<(T, T), LcdError<T>> {
+    let n = n.checked_div(by).ok_or(LcdError::Overflow)?;
+    let d = d.checked_div(by).ok_or(LcdError::Overflow)?;
+    Ok((n, d))
+}
+
+/// n/-d -> -n/d
+fn move_sign<T: Integer>(n: &T, d: &T) -> Result<(T, T), LcdError<T>> {
+    let n = n.checked_neg().ok_or(LcdError::Overflow)?;
+    let d = d.checked_neg().ok_or(LcdError::Overflow)?;
+    Ok((n, d))
+}
+
+//---------------------------------------------------------------------------//
+
 /// Recursively computes the Lowest Common Denominator of a fraction. Kind of.
 /// Really it does several things.
+/// - It moves the sign off of the denominator.
+///     - (i.e. 1/-5 -> 0(-1/5) )
 /// - It converts improper fractions to proper fractions.
 ///     - (i.e. 11/5 -> 2(1/5) )
 /// - It simplifies integer fractions.
 ///     - (i.e. 11/22 -> 1/2)
-/// - It find the smallest integer factor between the denominator and nominator.
+/// - It divides out the greatest common factor of the denominator and nominator.
 ///     - (i.e. 21/45 -> 7/15)
 ///
 /// Inputs:
-/// - `e: Expr` The expression to simplify.
+/// - `e: Expr<T>` The expression to simplify.
 ///
-/// Outputs: `Expr`
+/// Outputs: `Expr<T>`
 /// The simplified expression.
+/// Undefined expressions (zero denominator) are returned as-is.
 ///
-/// Time complexity: O(denominator)
-///
-pub fn recursive(mut e: Expr) -> Expr {
-    #[tailcall]
-    /// Simplifies expression in the (n/m)/(d/m) form.
-    /// Will maximize m with the following constraints:
-    /// - n/m is an integer
-    /// - d/m is an integer
-    /// - (n/m)/(d/m) = n/d
-    /// - m < d
-    fn inner(mut e: Expr, m: i64, gm: i64) -> Expr {
-        if m < e.d {
-            // currently looking for larger common factor
-            if e.n % m == 0 && e.d % m == 0 {
-                // found better factor, re-curse with new best factor
-                inner(e, m + 1, m)
-            } else {
-                // not any better, continue looking
-                inner(e, m + 1, gm)
-            }
-        } else if e.n % gm == 0 && e.d % gm == 0 && gm < e.d {
-            // finishing looking for a better factor and found one!
-            // update our expression with the lowest common denominator
-            e.n = e.n / gm;
-            e.d = e.d / gm;
-            e
-        } else {
-            // couldn't find any common factors
-            e
-        }
+/// Panics if the simplified expression doesn't fit in `T`.
+/// Use [`try_recursive`] to handle that case instead.
+///
+/// Time complexity: O(log(denominator))
+///
+pub fn recursive<T: Integer>(e: Expr<T>) -> Expr<T> {
+    if e.d.is_zero() {
+        return e;
     }
+    match try_recursive(e) {
+        Ok(res) => res,
+        Err(err) => panic!("{}", err),
+    }
+}
 
-    if e.d == 0 {
+/// Recursively computes the Lowest Common Denominator of a fraction, reporting
+/// undefined fractions and overflow. See [`recursive`].
+///
+/// Inputs:
+/// - `e: Expr<T>` The expression to simplify.
+///
+/// Outputs: `Result<Expr<T>, LcdError<T>>`
+/// The simplified expression, or why it couldn't be simplified.
+///
+/// Time complexity: O(log(denominator))
+///
+pub fn try_recursive<T: Integer>(e: Expr<T>) -> Result<Expr<T>, LcdError<T>> {
+    if e.d.is_zero() {
         // basic edge case handling
-        e
-    } else if e.n == e.d {
-        // n/n or d/d, either way its all equal to 1
-        Expr::new(1, 1, 1)
+        return Err(LcdError::ZeroDenominator);
+    }
+
+    match next_step(&e)? {
+        // general case, one step closer
+        Some(next) => try_recursive(next),
+        // terminating case, nothing left but to tidy up
+        None => Ok(finish(e)),
+    }
+}
+
+/// Works out what [`try_recursive`] calls itself with next, or `None` once all
+/// that's left is [`finish`]ing the expression.
+/// `e` must have a non-zero denominator.
+fn next_step<T: Integer>(e: &Expr<T>) -> Result<Option<Expr<T>>, LcdError<T>> {
+    if e.is_canonical() {
+        // nothing left to do
+        Ok(None)
+    } else if !e.c.is_zero() {
+        // c(n/d) -> (c*d + n)/d
+        // work with a plain fraction so the sign only lives in one place
+        let (n, d) = e.checked_improper().ok_or(LcdError::Overflow)?;
+        Ok(Some(Expr::new(T::zero(), n, d)))
     } else {
-        // general case
-
-        // handle case where n is a multiple of d
-        // (d*m)/d -> m OR (n*m)/d -> m(n/d)
-        e.c = 1.max(e.n / e.d);
-        e.n = e.n / e.c;
-
-        if e.d % e.n == 0 {
-            // handle case where d is a constant multiple of n
-            // n/(n*m) -> 1/m
-            e.d = e.d / e.n;
-            e.n = 1;
-            e
+        // find (n/m)/(d/m) cases
+        // the largest such m is the greatest common divisor of n and d
+        let gm = gcd::recursive(e.n.clone(), e.d.clone());
+
+        if gm != T::one() {
+            // n and d are both divisible by a common factor
+            // this gives us our lowest common denominator
+            let (n, d) = divide(&e.n, &e.d, &gm)?;
+            Ok(Some(Expr::new(T::zero(), n, d)))
+        } else if e.d.is_negative() {
+            // n/-d -> -n/d
+            let (n, d) = move_sign(&e.n, &e.d)?;
+            Ok(Some(Expr::new(T::zero(), n, d)))
         } else {
-            // general general case
-
-            // Form: n/d
-            // Known constraints:
-            // - n != d
-            // - n % d != 0
-            // - d % n != 0
-            // - 1 < n < d
-
-            // find (n/m)/(d/m) cases
-            // iterate up to the current denominator
-            inner(e, 2, 2)
+            // n and d are coprime, but n might be too large
+            Ok(None)
         }
     }
 }
 
+/// The last step of [`try_recursive`], once [`next_step`] has run out.
+fn finish<T: Integer>(e: Expr<T>) -> Expr<T> {
+    if e.is_canonical() {
+        e
+    } else {
+        // (d*m + n)/d -> m(n/d)
+        split(e.n, e.d)
+    }
+}
+
+/// Recursively computes the Lowest Common Denominator of a fraction, recording
+/// each call of [`try_recursive`] it goes through along with what it returned.
+/// See [`recursive`].
+///
+/// Inputs:
+/// - `e: Expr<T>` The expression to simplify.
+/// - `max_depth: usize` How many levels of calls to record, at least 1. Calls
+///   below that are only counted.
+///
+/// Outputs: `(Expr<T>, Call)`
+/// The simplified expression and the calls it took to get there.
+/// Undefined expressions (zero denominator) are returned as-is, in one call.
+///
+/// Panics if the simplified expression doesn't fit in `T`.
+///
+/// Time complexity: O(log(denominator))
+///
+pub fn recursive_traced<T: Integer>(e: Expr<T>, max_depth: usize) -> (Expr<T>, Call) {
+    if e.d.is_zero() {
+        let call = Call::new(format!("try_recursive({})", e), e.to_string(), vec![]);
+        return (e, call);
+    }
+    match try_recursive_traced(e, max_depth.max(1)) {
+        Ok((res, call)) => (res, call.into_call()),
+        Err(err) => panic!("{}", err),
+    }
+}
+
+/// [`try_recursive`], building up the calls it makes as it goes.
+fn try_recursive_traced<T: Integer>(
+    e: Expr<T>,
+    max_depth: usize,
+) -> Result<(Expr<T>, Traced), LcdError<T>> {
+    let (res, children) = match next_step(&e)? {
+        Some(next) => {
+            let (res, call) = try_recursive_traced(next, max_depth.saturating_sub(1))?;
+            (res, vec![call])
+        }
+        None => (finish(e.clone()), vec![]),
+    };
+
+    let call = Traced::record(
+        max_depth,
+        || format!("try_recursive({})", e),
+        || res.to_string(),
+        children,
+    );
+    Ok((res, call))
+}
+
 //---------------------------------------------------------------------------//
 
 /// Iteratively computes the Lowest Common Denominator of a fraction. Kind of.
 /// Really it does several things.
+/// - It moves the sign off of the denominator.
+///     - (i.e. 1/-5 -> 0(-1/5) )
 /// - It converts improper fractions to proper fractions.
 ///     - (i.e. 11/5 -> 2(1/5) )
 /// - It simplifies integer fractions.
 ///     - (i.e. 11/22 -> 1/2)
-/// - It find the smallest integer factor between the denominator and nominator.
+/// - It divides out the greatest common factor of the denominator and nominator.
 ///     - (i.e. 21/45 -> 7/15)
 ///
 /// Inputs:
-/// - `e: Expr` The expression to simplify.
+/// - `e: Expr<T>` The expression to simplify.
 ///
-/// Outputs: `Expr`
+/// Outputs: `Expr<T>`
 /// The simplified expression.
+/// Undefined expressions (zero denominator) are returned as-is.
 ///
-/// Time complexity: O(denominator)
+/// Panics if the simplified expression doesn't fit in `T`.
+/// Use [`try_iterative`] to handle that case instead.
 ///
-pub fn iterative(mut e: Expr) -> Expr {
-    // basic undefined edge case
-    if e.d == 0 {
+/// Time complexity: O(log(denominator))
+///
+pub fn iterative<T: Integer>(e: Expr<T>) -> Expr<T> {
+    if e.d.is_zero() {
         return e;
     }
+    match try_iterative(e) {
+        Ok(res) => res,
+        Err(err) => panic!("{}", err),
+    }
+}
+
+/// Iteratively computes the Lowest Common Denominator of a fraction, reporting
+/// undefined fractions and overflow. See [`iterative`].
+///
+/// Inputs:
+/// - `e: Expr<T>` The expression to simplify.
+///
+/// Outputs: `Result<Expr<T>, LcdError<T>>`
+/// The simplified expression, or why it couldn't be simplified.
+///
+/// Time complexity: O(log(denominator))
+///
+pub fn try_iterative<T: Integer>(e: Expr<T>) -> Result<Expr<T>, LcdError<T>> {
+    simplify_with(e, &mut |_| {})
+}
+
+/// Iteratively computes the Lowest Common Denominator of a fraction, recording
+/// each transformation along the way. See [`iterative`].
+///
+/// Inputs:
+/// - `e: Expr<T>` The expression to simplify.
+///
+/// Outputs: `(Expr<T>, Vec<Step<T>>)`
+/// The simplified expression, and the steps taken to get there.
+/// Undefined expressions (zero denominator) are returned as-is, with no steps.
+///
+/// Panics if the simplified expression doesn't fit in `T`.
+///
+/// Time complexity: O(log(denominator))
+///
+pub fn iterative_traced<T: Integer>(e: Expr<T>) -> (Expr<T>, Vec<Step<T>>) {
+    if e.d.is_zero() {
+        return (e, vec![]);
+    }
+
+    let mut steps = vec![];
+    match simplify_with(e, &mut |step| steps.push(step)) {
+        Ok(res) => (res, steps),
+        Err(err) => panic!("{}", err),
+    }
+}
+
+/// The iterative simplification, reporting each step to `record`.
+fn simplify_with<T: Integer>(
+    e: Expr<T>,
+    record: &mut impl FnMut(Step<T>),
+) -> Result<Expr<T>, LcdError<T>> {
+    // basic undefined edge case
+    if e.d.is_zero() {
+        return Err(LcdError::ZeroDenominator);
+    }
+
+    // already simplified, nothing to do
+    if e.is_canonical() {
+        record(Step::AlreadySimplified(e.clone()));
+        return Ok(e);
+    }
 
-    println!("--- {}", e);
+    // c(n/d) -> (c*d + n)/d
+    // work with a plain fraction so the sign only lives in one place
+    let (mut n, mut d) = e.checked_improper().ok_or(LcdError::Overflow)?;
+    record(Step::Improper {
+        from: e,
+        n: n.clone(),
+        d: d.clone(),
+    });
 
-    if e.n == e.d {
-        println!("n/n or d/d");
-        // 1/1 or similar
-        Expr::new(1, 1, 1)
+    // find (n/m)/(d/m) cases
+    // the largest such m is the greatest common divisor of n and d
+    let gm = gcd::iterative(n.clone(), d.clone());
+    record(Step::CommonFactor {
+        n: n.clone(),
+        d: d.clone(),
+        gcd: gm.clone(),
+    });
+
+    if gm != T::one() {
+        // n and d are both divisible by a common factor
+        // this gives us our lowest common denominator
+        record(Step::Divide {
+            n: n.clone(),
+            d: d.clone(),
+            by: gm.clone(),
+        });
+        (n, d) = divide(&n, &d, &gm)?;
     } else {
-        // general case
-
-        println!("{}", e);
-
-        // handle case where n is a multiple of d
-        // (d*m)/d -> m OR (n*m)/d -> m(n/d)
-        e.c = 1.max(e.n / e.d);
-        e.n = e.n / e.c;
-        println!("{}", e);
-
-        if e.d % e.n == 0 {
-            println!("n/(n*m)");
-            // handle case where d is a constant multiple of n
-            // n/(n*m) -> 1/m
-            e.d = e.d / e.n;
-            e.n = 1;
-            println!("{}", e);
-        } else if e.d > 1 {
-            // Form: n/d
-            // Known constraints:
-            // - n != d
-            // - n % d != 0
-            // - d % n != 0
-            // - 1 < n < d
-
-            let mut m = 2;
-            let mut gm = 2;
-
-            // find (n/m)/(d/m) cases
-            // iterate up to the current denominator
-            while m < e.d {
-                m += 1;
-                // check if we've found a new smallest denominator
-                //(in other words, the largest factor)
-                if e.n % m == 0 && e.d % m == 0 {
-                    // update tracker
-                    gm = m;
-                }
-            }
+        // n and d are coprime and already in a stable form
+    }
+
+    if d.is_negative() {
+        // n/-d -> -n/d
+        record(Step::MoveSign {
+            n: n.clone(),
+            d: d.clone(),
+        });
+        (n, d) = move_sign(&n, &d)?;
+    }
+
+    // (d*m + n)/d -> m(n/d)
+    let res = split(n.clone(), d.clone());
+    record(Step::Split {
+        n,
+        d,
+        into: res.clone(),
+    });
+
+    Ok(res)
+}
+
+//---------------------------------------------------------------------------//
 
-            println!("gm:{}", gm);
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+/// A single transformation made while simplifying an expression.
+/// See [`iterative_traced`].
+pub enum Step<T: Integer = i64> {
+    /// The input was already simplified.
+    AlreadySimplified(Expr<T>),
+    /// The co-efficient was folded into the fraction, giving n/d.
+    Improper { from: Expr<T>, n: T, d: T },
+    /// The greatest common factor of n and d was found.
+    CommonFactor { n: T, d: T, gcd: T },
+    /// n and d were both divided by a common factor.
+    Divide { n: T, d: T, by: T },
+    /// The sign was moved from the denominator to the nominator.
+    MoveSign { n: T, d: T },
+    /// The improper fraction n/d was split into a mixed number.
+    Split { n: T, d: T, into: Expr<T> },
+}
+
+//---------------------------------------------------------------------------//
+
+/// Writes out -x, even when it doesn't fit in the integer type.
+fn negated<T: Integer>(x: &T) -> String {
+    match x.checked_neg() {
+        Some(neg) => neg.to_string(),
+        // only the most negative value can't be negated, just drop its sign
+        None => x.to_string().trim_start_matches('-').to_string(),
+    }
+}
 
-            if e.n % gm == 0 && e.d % gm == 0 && gm < e.d {
-                // n and d are both divisible by a common factor less than d
-                // this gives us our lowest common denominator
-                e.n = e.n / gm;
-                e.d = e.d / gm;
-            } else {
-                // n or d are most likely prime and already in a stable form
+impl<T: Integer> std::fmt::Display for Step<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Step::AlreadySimplified(e) => f.write_fmt(format_args!("{} is already simplified", e)),
+            Step::Improper { from, n, d } => f.write_fmt(format_args!(
+                "{} as an improper fraction is {}/{}",
+                from, n, d
+            )),
+            Step::CommonFactor { n, d, gcd } => f.write_fmt(format_args!(
+                "the greatest common factor of {} and {} is {}",
+                n, d, gcd
+            )),
+            Step::Divide { n, d, by } => match divide(n, d, by) {
+                Ok((a, b)) => {
+                    f.write_fmt(format_args!("({}/{})/({}/{}) -> {}/{}", n, by, d, by, a, b))
+                }
+                Err(_) => f.write_fmt(format_args!("({}/{})/({}/{})", n, by, d, by)),
+            },
+            Step::MoveSign { n, d } => {
+                f.write_fmt(format_args!("{}/{} -> {}/{}", n, d, negated(n), negated(d)))
             }
+            Step::Split { n, d, into } => f.write_fmt(format_args!("{}/{} -> {}", n, d, into)),
         }
-
-        e
     }
 }
 
@@ -212,7 +868,8 @@ pub fn iterative(mut e: Expr) -> Expr {
 
 #[cfg(test)]
 mod tests {
-    use super::Expr;
+    use super::{BigInt, Expr, Integer, LcdError, Step};
+    use crate::primes;
 
     #[test]
     fn iterative() {
@@ -224,17 +881,450 @@ mod tests {
         common(super::recursive);
     }
 
+    #[test]
+    fn iterative_signs() {
+        signs(super::iterative);
+    }
+
+    #[test]
+    fn recursive_signs() {
+        signs(super::recursive);
+    }
+
+    #[test]
+    fn try_iterative() {
+        common(|e| super::try_iterative(e).unwrap());
+        signs(|e| super::try_iterative(e).unwrap());
+        errors(super::try_iterative);
+    }
+
+    #[test]
+    fn try_recursive() {
+        common(|e| super::try_recursive(e).unwrap());
+        signs(|e| super::try_recursive(e).unwrap());
+        errors(super::try_recursive);
+    }
+
+    #[test]
+    fn iterative_traced() {
+        common(|e| super::iterative_traced(e).0);
+        signs(|e| super::iterative_traced(e).0);
+
+        let (res, steps) = super::iterative_traced(Expr::new(0, 21, 45));
+        assert_same(res, Expr::new(0, 7, 15));
+        assert_eq!(
+            steps,
+            [
+                Step::Improper {
+                    from: Expr::new(0, 21, 45),
+                    n: 21,
+                    d: 45
+                },
+                Step::CommonFactor {
+                    n: 21,
+                    d: 45,
+                    gcd: 3
+                },
+                Step::Divide {
+                    n: 21,
+                    d: 45,
+                    by: 3
+                },
+                Step::Split {
+                    n: 7,
+                    d: 15,
+                    into: Expr::new(0, 7, 15)
+                },
+            ]
+        );
+
+        let (res, steps) = super::iterative_traced(Expr::new(1, 1, -3));
+        assert_same(res, Expr::new(0, 2, 3));
+        assert_eq!(steps.len(), 4);
+        assert_eq!(steps[2], Step::MoveSign { n: -2, d: -3 });
+
+        let (res, steps) = super::iterative_traced(Expr::new(2, 1, 5));
+        assert_same(res, Expr::new(2, 1, 5));
+        assert_eq!(steps, [Step::AlreadySimplified(Expr::new(2, 1, 5))]);
+
+        let (res, steps) = super::iterative_traced(Expr::new(0, 1, 0));
+        assert_same(res, Expr::new(0, 1, 0));
+        assert!(steps.is_empty());
+    }
+
+    #[test]
+    fn recursive_traced() {
+        common(|e| super::recursive_traced(e, usize::MAX).0);
+        signs(|e| super::recursive_traced(e, usize::MAX).0);
+
+        let (res, call) = super::recursive_traced(Expr::new(1, 1, -3), usize::MAX);
+        assert_same(res, Expr::new(0, 2, 3));
+        assert_eq!(
+            call.to_text(usize::MAX),
+            "\
+try_recursive(1(1/-3)) = 0(2/3)
+  try_recursive(0(-2/-3)) = 0(2/3)
+    try_recursive(0(2/3)) = 0(2/3)
+"
+        );
+
+        let (res, call) = super::recursive_traced(Expr::new(0, 21, 45), usize::MAX);
+        assert_same(res, Expr::new(0, 7, 15));
+        assert_eq!(call.depth(), 2);
+        assert_eq!(call.children[0].args, "try_recursive(0(7/15))");
+
+        let (res, call) = super::recursive_traced(Expr::new(1, 1, -3), 1);
+        assert_same(res, Expr::new(0, 2, 3));
+        assert_eq!(call.children, []);
+        assert_eq!(call.hidden, 2);
+
+        let (res, call) = super::recursive_traced(Expr::new(0, 1, 0), usize::MAX);
+        assert_same(res, Expr::new(0, 1, 0));
+        assert_eq!(call.size(), 1);
+    }
+
+    #[test]
+    fn try_new() {
+        assert_eq!(Expr::try_new(0, 1, 2), Ok(Expr::new(0, 1, 2)));
+        assert_eq!(Expr::try_new(-2, 1, 5), Ok(Expr::new(-2, 1, 5)));
+        assert_eq!(Expr::try_new(0, -1, 5), Ok(Expr::new(0, -1, 5)));
+        assert_eq!(Expr::try_new(3, 0, 1), Ok(Expr::new(3, 0, 1)));
+        assert_eq!(Expr::try_new(1, 1, 0), Err(LcdError::ZeroDenominator));
+        assert_eq!(Expr::try_new(0, 0, 0), Err(LcdError::ZeroDenominator));
+
+        for (c, n, d) in [(0, 2, 4), (0, 1, -2), (0, 5, 3), (-2, -1, 5), (1, 0, 2)] {
+            assert!(matches!(
+                Expr::try_new(c, n, d),
+                Err(LcdError::NonCanonical(e)) if (e.c, e.n, e.d) == (c, n, d)
+            ));
+        }
+    }
+
+    fn errors(func: fn(Expr) -> Result<Expr, LcdError>) {
+        assert_eq!(func(Expr::new(0, 1, 0)), Err(LcdError::ZeroDenominator));
+        assert_eq!(func(Expr::new(3, -7, 0)), Err(LcdError::ZeroDenominator));
+        assert_eq!(func(Expr::new(i64::MAX, 3, 2)), Err(LcdError::Overflow));
+        assert_eq!(func(Expr::new(0, i64::MIN, -1)), Err(LcdError::Overflow));
+        assert_eq!(func(Expr::new(i64::MIN, 1, 1)), Err(LcdError::Overflow));
+        assert_eq!(func(Expr::new(0, 1, i64::MIN)), Err(LcdError::Overflow));
+
+        // the extremes themselves are fine
+        assert_same(
+            func(Expr::new(i64::MAX, 1, 2)).unwrap(),
+            Expr::new(i64::MAX, 1, 2),
+        );
+        assert_same(
+            func(Expr::new(0, i64::MIN, 1)).unwrap(),
+            Expr::new(i64::MIN, 0, 1),
+        );
+        assert_same(
+            func(Expr::new(0, i64::MAX, 1)).unwrap(),
+            Expr::new(i64::MAX, 0, 1),
+        );
+        assert_same(
+            func(Expr::new(0, 2, i64::MIN)).unwrap(),
+            Expr::new(0, -1, 1 << 62),
+        );
+    }
+
+    #[test]
+    fn equality() {
+        assert_eq!(Expr::new(1, 1, 2), Expr::new(0, 3, 2));
+        assert_eq!(Expr::new(0, 2, 4), Expr::new(0, -1, -2));
+        assert_eq!(Expr::new(-1, 1, 2), Expr::new(0, 3, -2));
+        assert_ne!(Expr::new(1, 1, 2), Expr::new(0, 1, 2));
+        assert_ne!(Expr::new(-1, 1, 2), Expr::new(-1, -1, 2));
+
+        let mut set = std::collections::HashSet::new();
+        set.insert(Expr::new(1, 1, 2));
+        assert!(set.contains(&Expr::new(0, 3, 2)));
+        assert!(set.contains(&Expr::new(0, 6, 4)));
+        assert!(!set.contains(&Expr::new(0, 1, 2)));
+
+        // these don't fit in an i64 once simplified, but still compare
+        let max = Expr::new(i64::MAX, 3, 2);
+        let min = Expr::new(0, i64::MIN, -1);
+        assert_eq!(max, Expr::new(i64::MAX, 6, 4));
+        assert_ne!(max, Expr::new(0, 1, 2));
+        assert_eq!(min, Expr::new(i64::MAX, 1, 1));
+        assert_ne!(min, max);
+        assert!(min < max);
+        assert!(Expr::new(0, 1, 2) < min);
+        assert!(Expr::new(i64::MIN, 1, 1) < Expr::new(i64::MIN, 0, 1));
+
+        set.insert(max);
+        set.insert(min);
+        assert!(set.contains(&Expr::new(i64::MAX, 6, 4)));
+        assert!(set.contains(&Expr::new(i64::MAX, 1, 1)));
+        assert!(!set.contains(&Expr::new(i64::MAX, 1, 2)));
+    }
+
+    #[test]
+    fn ordering() {
+        let mut values = vec![
+            Expr::new(1, 1, 2),
+            Expr::new(0, -1, 3),
+            Expr::new(-2, 1, 5),
+            Expr::new(0, 1, 3),
+            Expr::new(0, 2, 1),
+            Expr::new(0, 0, 1),
+        ];
+        values.sort();
+
+        let expected = [
+            Expr::new(-2, 1, 5),
+            Expr::new(0, -1, 3),
+            Expr::new(0, 0, 1),
+            Expr::new(0, 1, 3),
+            Expr::new(1, 1, 2),
+            Expr::new(2, 0, 1),
+        ];
+        assert_eq!(values, expected);
+
+        // compare against floats for a grid of small fractions
+        for a in -12..=12i64 {
+            for b in 1..=12i64 {
+                for x in -12..=12i64 {
+                    for y in 1..=12i64 {
+                        let lhs = Expr::new(0, a, b);
+                        let rhs = Expr::new(0, x, y);
+                        let expected = (a as f64 / b as f64)
+                            .partial_cmp(&(x as f64 / y as f64))
+                            .unwrap();
+                        assert_eq!(lhs.cmp(&rhs), expected);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn arithmetic() {
+        let half = Expr::new(0, 1, 2);
+        let third = Expr::new(0, 1, 3);
+
+        assert_same(half + third, Expr::new(0, 5, 6));
+        assert_same(half - third, Expr::new(0, 1, 6));
+        assert_same(third - half, Expr::new(0, -1, 6));
+        assert_same(half * third, Expr::new(0, 1, 6));
+        assert_same(half / third, Expr::new(1, 1, 2));
+        assert_same(-half, Expr::new(0, -1, 2));
+        assert_same(-Expr::new(2, 1, 5), Expr::new(-2, 1, 5));
+        assert_same(-Expr::new(-2, 1, 5), Expr::new(2, 1, 5));
+        assert_same(Expr::new(1, 1, 2) + Expr::new(1, 1, 2), Expr::new(3, 0, 1));
+        assert_same(
+            Expr::new(-1, 1, 2) * Expr::new(0, 2, 3),
+            Expr::new(-1, 0, 1),
+        );
+
+        let mut e = Expr::new(0, 1, 4);
+        e += half;
+        assert_same(e, Expr::new(0, 3, 4));
+        e -= Expr::new(1, 0, 1);
+        assert_same(e, Expr::new(0, -1, 4));
+        e *= Expr::new(0, -4, 1);
+        assert_same(e, Expr::new(1, 0, 1));
+        e /= Expr::new(0, 3, 1);
+        assert_same(e, third);
+
+        // compare against cross-multiplication for a grid of small fractions
+        for a in -6..=6i64 {
+            for b in 1..=6i64 {
+                for x in -6..=6i64 {
+                    for y in 1..=6i64 {
+                        let lhs = Expr::new(0, a, b);
+                        let rhs = Expr::new(0, x, y);
+
+                        let sum = lhs + rhs;
+                        assert!(sum.is_canonical());
+                        assert_eq!(sum, Expr::new(0, a * y + x * b, b * y));
+
+                        let diff = lhs - rhs;
+                        assert!(diff.is_canonical());
+                        assert_eq!(diff + rhs, lhs);
+
+                        let prod = lhs * rhs;
+                        assert!(prod.is_canonical());
+                        assert_eq!(prod, Expr::new(0, a * x, b * y));
+
+                        if x != 0 {
+                            let quot = lhs / rhs;
+                            assert!(quot.is_canonical());
+                            assert_eq!(quot * rhs, lhs);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn divide_by_zero() {
+        let _ = Expr::new(0, 1, 2) / Expr::new(0, 0, 1);
+    }
+
+    #[test]
+    fn checked_arithmetic() {
+        let half = Expr::new(0, 1, 2);
+        let max = Expr::new(i64::MAX, 0, 1);
+
+        assert_eq!(half.checked_add(&half), Ok(Expr::new(1, 0, 1)));
+        assert_eq!(
+            half.checked_div(&Expr::new(0, 0, 1)),
+            Err(LcdError::ZeroDenominator)
+        );
+        assert_eq!(
+            max.checked_add(&Expr::new(1, 0, 1)),
+            Err(LcdError::Overflow)
+        );
+        assert_eq!(max.checked_mul(&max), Err(LcdError::Overflow));
+        assert_eq!(
+            Expr::new(i64::MIN, 0, 1).checked_neg(),
+            Err(LcdError::Overflow)
+        );
+        assert_eq!(max.checked_sub(&max), Ok(Expr::new(0, 0, 1)));
+
+        // cross-multiplying would overflow, even though the values are close
+        assert!(Expr::new(i64::MAX - 1, 1, 2) < max);
+        assert!(Expr::new(0, i64::MAX - 2, i64::MAX - 1) < Expr::new(0, i64::MAX - 1, i64::MAX));
+    }
+
+    /// The same checks on every backend, starting from small values.
+    fn backend<T: Integer + From<i32>>() {
+        let e = |c: i32, n: i32, d: i32| Expr::<T>::new(c.into(), n.into(), d.into());
+
+        assert_eq!(super::recursive(e(0, 21, 45)).to_string(), "0(7/15)");
+        assert_eq!(super::iterative(e(0, -11, 5)).to_string(), "-2(1/5)");
+        assert_eq!(super::iterative(e(1, 1, -3)).to_string(), "0(2/3)");
+        assert_eq!(
+            super::try_recursive(e(0, 1, 0)),
+            Err(LcdError::ZeroDenominator)
+        );
+        assert_eq!(
+            super::iterative_traced(e(0, 21, 45)).1[2].to_string(),
+            "(21/3)/(45/3) -> 7/15"
+        );
+
+        // agrees with i64 for a grid of small fractions
+        for a in -6..=6 {
+            for b in 1..=6 {
+                for x in -6..=6 {
+                    for y in 1..=6 {
+                        let (lhs, rhs) = (e(0, a, b), e(0, x, y));
+                        let (l64, r64) = (
+                            Expr::new(0, a as i64, b as i64),
+                            Expr::new(0, x as i64, y as i64),
+                        );
+
+                        assert_eq!(
+                            super::recursive(lhs.clone()).to_string(),
+                            super::recursive(l64).to_string()
+                        );
+                        assert_eq!(
+                            (lhs.clone() + rhs.clone()).to_string(),
+                            (l64 + r64).to_string()
+                        );
+                        assert_eq!(
+                            (lhs.clone() * rhs.clone()).to_string(),
+                            (l64 * r64).to_string()
+                        );
+                        assert_eq!(lhs.cmp(&rhs), l64.cmp(&r64));
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn backends() {
+        backend::<i32>();
+        backend::<i64>();
+        backend::<i128>();
+        backend::<BigInt>();
+    }
+
+    #[test]
+    fn overflow() {
+        // the same sum overflows a small type, but not a wider one
+        let big = Expr::<i32>::new(0, 1, 65_536);
+        assert_eq!(big.checked_mul(&big), Err(LcdError::Overflow));
+        let big = Expr::<i128>::new(0, 1, 65_536);
+        assert_eq!(big.checked_mul(&big), Ok(Expr::new(0, 1, 1 << 32)));
+
+        assert_eq!(
+            super::try_iterative(Expr::<i32>::new(0, i32::MIN, -1)),
+            Err(LcdError::Overflow)
+        );
+        assert_eq!(
+            super::try_recursive(Expr::<i128>::new(0, 2, i128::MIN)),
+            Ok(Expr::new(0, -1, 1 << 126))
+        );
+        assert_eq!(
+            super::try_recursive(Expr::<i64>::new(0, i64::MIN, i64::MIN)),
+            Ok(Expr::new(1, 0, 1))
+        );
+
+        // ordering never multiplies, so it works right up to the limits
+        let m = i128::MAX;
+        assert!(Expr::<i128>::new(0, m - 2, m - 1) < Expr::new(0, m - 1, m));
+        assert!(Expr::<i128>::new(-m, m - 1, m) < Expr::new(-m, m - 2, m - 1));
+
+        // the harmonic numbers quickly outgrow an i64, but not a BigInt
+        let harmonic = |n: i64| {
+            (1..=n).try_fold(Expr::new(0, 0, 1), |acc: Expr, k| {
+                acc.checked_add(&Expr::new(0, 1, k))
+            })
+        };
+        assert_eq!(harmonic(20).map(|h| h.c), Ok(3));
+        assert_eq!(harmonic(60), Err(LcdError::Overflow));
+
+        let one = BigInt::from(1);
+        let zero = Expr::new(BigInt::from(0), BigInt::from(0), one.clone());
+        let total = (1..=100).fold(zero, |acc, k| {
+            acc + Expr::new(BigInt::from(0), one.clone(), BigInt::from(k))
+        });
+        assert!(total.is_canonical());
+        assert_eq!(total.c, BigInt::from(5));
+        assert_eq!(total.d.to_string().len(), 40);
+
+        // and telescoping products come back down again
+        let product = (1..=500).fold(
+            Expr::new(one.clone(), BigInt::from(0), one.clone()),
+            |acc, k| acc * Expr::new(BigInt::from(0), BigInt::from(k), BigInt::from(k + 1)),
+        );
+        assert_eq!(product.to_string(), "0(1/501)");
+
+        let huge = BigInt::from(3) << 300;
+        let e = Expr::new(BigInt::from(0), &huge * 7, &huge * -21);
+        assert_eq!(super::iterative(e.clone()).to_string(), "0(-1/3)");
+        assert_eq!(super::recursive(e).to_string(), "0(-1/3)");
+    }
+
+    /// Checks both the value and the exact representation.
+    fn assert_same(real: Expr, expected: Expr) {
+        println!("{} >< {}", real, expected);
+        assert_eq!(
+            (real.c, real.n, real.d),
+            (expected.c, expected.n, expected.d)
+        );
+    }
+
     fn common(func: fn(Expr) -> Expr) {
         // (m/(d*m)) -> 1/d
         for i in -20..100 {
             if i != 0 {
                 for m in 1..100 {
-                    let input = Expr::new(1, 1 * m, i * m);
+                    let input = Expr::new(0, m, i * m);
 
                     let real = func(input);
-                    let expected = Expr::new(1, 1, i);
-                    println!("{} >< {}", real, expected);
-                    assert_eq!(real, expected);
+                    let expected = if i.abs() == 1 {
+                        // 1/1 -> 1(0/1)
+                        Expr::new(i, 0, 1)
+                    } else {
+                        Expr::new(0, i.signum(), i.abs())
+                    };
+                    assert_same(real, expected);
                 }
             }
         }
@@ -243,32 +1333,21 @@ mod tests {
         for i in -20..100 {
             if i != 0 {
                 for m in 1..100 {
-                    let input = Expr::new(1, i * m, i);
+                    let input = Expr::new(0, i * m, i);
 
                     let real = func(input);
-                    let expected = Expr::new(m, 1, 1);
-                    println!("{} >< {}", real, expected);
-                    assert_eq!(real, expected);
+                    let expected = Expr::new(m, 0, 1);
+                    assert_same(real, expected);
                 }
             }
         }
 
         // (n*m)/(d*m) -> n/d
         // inputs must be primes
-        // https://byjus.com/maths/prime-numbers/#prime-numbers-1-to-200
-        let primes = [
-            2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37, 41, 43, 47, 53, 59, 61, 67, 71, 73, 79, 83,
-            89, 97, 101, 103, 107, 109, 113, 127, 131, 137, 139, 149, 151, 157, 163, 167, 173,
-            179,
-            // 181, 191, 193, 197, 199, 211, 223, 227, 229, 233, 239, 241, 251, 257, 263, 269, 271,
-            // 277, 281, 283, 293, 307, 311, 313, 317, 331, 337, 347, 349, 353, 359, 367, 373, 379,
-            // 383, 389, 397, 401, 409, 419, 421, 431, 433, 439, 443, 449, 457, 461, 463, 467, 479,
-            // 487, 491, 499, 503, 509, 521, 523, 541, 547, 557, 563, 569, 571, 577, 587, 593, 599,
-            // 601, 607, 613, 617, 619, 631, 641, 643, 647, 653, 659, 661, 673, 677, 683, 691, 701,
-            // 709, 719, 727, 733, 739, 743, 751, 757, 761, 769, 773, 787, 797, 809, 811, 821, 823,
-            // 827, 829, 839, 853, 857, 859, 863, 877, 881, 883, 887, 907, 911, 919, 929, 937, 941,
-            // 947, 953, 967, 971, 977, 983, 991, 997,
-        ];
+        let primes = primes::sieve(180)
+            .into_iter()
+            .map(|p| p as i64)
+            .collect::<Vec<_>>();
 
         for i in 0..primes.len() {
             for j in 0..i {
@@ -277,17 +1356,63 @@ mod tests {
 
                 if d != 0 {
                     for m in 1..100 {
-                        let input = Expr::new(1, n * m, d * m);
+                        let input = Expr::new(0, n * m, d * m);
+
+                        let real = func(input);
+                        let expected = Expr::new(0, n, d);
+                        assert_same(real, expected);
+
+                        // (d*m)/(n*m) -> (d/n)((d%n)/n)
+                        let input = Expr::new(0, d * m, n * m);
 
                         let real = func(input);
-                        let expected = Expr::new(1, n, d);
-                        println!("{} >< {}", real, expected);
-                        assert_eq!(real, expected);
+                        let expected = Expr::new(d / n, d % n, n);
+                        assert_same(real, expected);
                     }
                 }
             }
         }
     }
+
+    fn signs(func: fn(Expr) -> Expr) {
+        assert_same(func(Expr::new(0, 11, 5)), Expr::new(2, 1, 5));
+        assert_same(func(Expr::new(0, -11, 5)), Expr::new(-2, 1, 5));
+        assert_same(func(Expr::new(0, 11, -5)), Expr::new(-2, 1, 5));
+        assert_same(func(Expr::new(0, -11, -5)), Expr::new(2, 1, 5));
+        assert_same(func(Expr::new(0, 1, 5)), Expr::new(0, 1, 5));
+        assert_same(func(Expr::new(0, -1, 5)), Expr::new(0, -1, 5));
+        assert_same(func(Expr::new(0, 1, -5)), Expr::new(0, -1, 5));
+        assert_same(func(Expr::new(0, -1, -5)), Expr::new(0, 1, 5));
+        assert_same(func(Expr::new(2, 1, 5)), Expr::new(2, 1, 5));
+        assert_same(func(Expr::new(-2, 1, 5)), Expr::new(-2, 1, 5));
+        assert_same(func(Expr::new(-2, 6, 5)), Expr::new(-3, 1, 5));
+        assert_same(func(Expr::new(2, -6, 5)), Expr::new(0, 4, 5));
+        assert_same(func(Expr::new(1, -3, 2)), Expr::new(0, -1, 2));
+        assert_same(func(Expr::new(0, 0, -7)), Expr::new(0, 0, 1));
+
+        // every combination of signs on every field
+        for c in -4..=4i64 {
+            for n in -30..=30i64 {
+                for d in -30..=30i64 {
+                    if d == 0 {
+                        continue;
+                    }
+
+                    let input = Expr::new(c, n, d);
+                    let real = func(input);
+                    println!("{} -> {}", input, real);
+
+                    assert!(real.is_canonical());
+                    assert!(real.d > 0);
+
+                    // same value, compared as improper fractions
+                    let (a, b) = input.to_improper();
+                    let (x, y) = real.to_improper();
+                    assert_eq!(a * y, x * b);
+                }
+            }
+        }
+    }
 }
 
 ////////////////////////////////////////////////////////////////////////////////
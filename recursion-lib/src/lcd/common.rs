@@ -0,0 +1,228 @@
+////////////////////////////////////////////////////////////////////////////////
+
+use super::{try_iterative, Expr, LcdError};
+use crate::lcm;
+
+////////////////////////////////////////////////////////////////////////////////
+
+/// Finds the Lowest Common Denominator of several expressions.
+/// That is, the least common multiple of their simplified denominators.
+/// (i.e. 1/4, 1(1/6) -> 12 )
+///
+/// Inputs:
+/// - `exprs: &[Expr]` The expressions to look at.
+///
+/// Outputs: `Result<i64, LcdError>`
+/// The lowest common denominator, or why it couldn't be found.
+/// The lowest common denominator of no expressions is 1.
+///
+/// Time complexity: O(len(exprs) * log(max(denominator)))
+pub fn least_common_denominator(exprs: &[Expr]) -> Result<i64, LcdError> {
+    let ds = exprs
+        .iter()
+        .map(|e| try_iterative(*e).map(|e| e.d))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    lcm::iterative(&ds).ok_or(LcdError::Overflow)
+}
+
+//---------------------------------------------------------------------------//
+
+/// Rewrites several expressions so they all share their Lowest Common
+/// Denominator.
+/// Each expression keeps its co-efficient, only the fraction is scaled.
+/// (i.e. 1/4, 1(1/6) -> 3/12, 1(2/12) )
+///
+/// Inputs:
+/// - `exprs: &[Expr]` The expressions to rewrite.
+///
+/// Outputs: `Result<Vec<Expr>, LcdError>`
+/// The rewritten expressions in the same order, or why they couldn't be
+/// rewritten.
+///
+/// Time complexity: O(len(exprs) * log(max(denominator)))
+pub fn over_common_denominator(exprs: &[Expr]) -> Result<Vec<Expr>, LcdError> {
+    let l = least_common_denominator(exprs)?;
+
+    exprs
+        .iter()
+        .map(|e| {
+            // n/d -> (n*m)/(d*m) where d*m = l
+            let e = try_iterative(*e)?;
+            let n = e.n.checked_mul(l / e.d).ok_or(LcdError::Overflow)?;
+            Ok(Expr::new(e.c, n, l))
+        })
+        .collect()
+}
+
+//---------------------------------------------------------------------------//
+
+/// Adds several expressions together by rewriting them over their Lowest
+/// Common Denominator and adding up the nominators.
+/// (i.e. 1/4 + 1(1/6) -> 3/12 + 14/12 -> 17/12 -> 1(5/12) )
+///
+/// Inputs:
+/// - `exprs: &[Expr]` The expressions to add.
+///
+/// Outputs: `Result<Expr, LcdError>`
+/// The simplified sum, or why it couldn't be found.
+/// The sum of no expressions is 0.
+///
+/// Time complexity: O(len(exprs) * log(max(denominator)))
+pub fn sum(exprs: &[Expr]) -> Result<Expr, LcdError> {
+    let l = least_common_denominator(exprs)?;
+    let mut total: i64 = 0;
+
+    for e in over_common_denominator(exprs)? {
+        let (n, _) = e.checked_improper().ok_or(LcdError::Overflow)?;
+        total = total.checked_add(n).ok_or(LcdError::Overflow)?;
+    }
+
+    try_iterative(Expr::new(0, total, l))
+}
+
+//---------------------------------------------------------------------------//
+
+/// Compares two expressions by rewriting them over their Lowest Common
+/// Denominator and comparing the nominators.
+/// (i.e. 2/3 vs 3/4 -> 8/12 vs 9/12 -> Less )
+///
+/// Inputs:
+/// - `a: Expr`
+/// - `b: Expr`
+///
+/// Outputs: `Result<std::cmp::Ordering, LcdError>`
+/// How `a` compares to `b`, or why they couldn't be compared.
+///
+/// Time complexity: O(log(max(denominator)))
+pub fn compare(a: Expr, b: Expr) -> Result<std::cmp::Ordering, LcdError> {
+    let rewritten = over_common_denominator(&[a, b])?;
+
+    let (x, _) = rewritten[0].checked_improper().ok_or(LcdError::Overflow)?;
+    let (y, _) = rewritten[1].checked_improper().ok_or(LcdError::Overflow)?;
+
+    Ok(x.cmp(&y))
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cmp::Ordering;
+
+    fn fields(exprs: &[Expr]) -> Vec<(i64, i64, i64)> {
+        exprs.iter().map(|e| (e.c, e.n, e.d)).collect()
+    }
+
+    #[test]
+    fn rewriting() {
+        let exprs = [Expr::new(0, 1, 4), Expr::new(1, 1, 6)];
+        assert_eq!(least_common_denominator(&exprs), Ok(12));
+        assert_eq!(
+            fields(&over_common_denominator(&exprs).unwrap()),
+            [(0, 3, 12), (1, 2, 12)]
+        );
+
+        // inputs are simplified first, 2/8 is really 1/4
+        let exprs = [Expr::new(0, 2, 8), Expr::new(0, -5, 6), Expr::new(0, 3, 1)];
+        assert_eq!(least_common_denominator(&exprs), Ok(12));
+        assert_eq!(
+            fields(&over_common_denominator(&exprs).unwrap()),
+            [(0, 3, 12), (0, -10, 12), (3, 0, 12)]
+        );
+
+        assert_eq!(least_common_denominator(&[]), Ok(1));
+        assert_eq!(over_common_denominator(&[]), Ok(vec![]));
+
+        let exprs = [Expr::new(0, 1, 2), Expr::new(0, 1, 0)];
+        assert_eq!(
+            least_common_denominator(&exprs),
+            Err(LcdError::ZeroDenominator)
+        );
+        assert_eq!(
+            over_common_denominator(&exprs),
+            Err(LcdError::ZeroDenominator)
+        );
+
+        let exprs = [Expr::new(0, 1, 1 << 62), Expr::new(0, 1, 3)];
+        assert_eq!(least_common_denominator(&exprs), Err(LcdError::Overflow));
+
+        // every rewritten expression keeps its value
+        let exprs = (1..=10)
+            .flat_map(|d| (-d..=d).map(move |n| Expr::new(0, n, d)))
+            .collect::<Vec<_>>();
+        let l = least_common_denominator(&exprs).unwrap();
+        assert_eq!(l, 2520);
+        for (e, r) in exprs.iter().zip(over_common_denominator(&exprs).unwrap()) {
+            assert_eq!(r.d, l);
+            assert_eq!(*e, r);
+        }
+    }
+
+    #[test]
+    fn sums() {
+        let one_quarter = Expr::new(0, 1, 4);
+        let one_and_a_sixth = Expr::new(1, 1, 6);
+        let total = sum(&[one_quarter, one_and_a_sixth]).unwrap();
+        assert_eq!((total.c, total.n, total.d), (1, 5, 12));
+
+        let total = sum(&[]).unwrap();
+        assert_eq!((total.c, total.n, total.d), (0, 0, 1));
+
+        let halves = [Expr::new(0, 1, 2), Expr::new(-1, 1, 2), Expr::new(0, 1, 2)];
+        let total = sum(&halves).unwrap();
+        assert_eq!((total.c, total.n, total.d), (0, -1, 2));
+
+        // agrees with adding them one at a time
+        let exprs = (1..=9)
+            .flat_map(|d| (-3..=3i64).map(move |n| Expr::new(n % 2, n.abs(), d)))
+            .collect::<Vec<_>>();
+        let expected = exprs.iter().fold(Expr::new(0, 0, 1), |acc, e| acc + *e);
+        assert_eq!(sum(&exprs), Ok(expected));
+
+        assert_eq!(
+            sum(&[Expr::new(0, 1, 2), Expr::new(0, 1, 0)]),
+            Err(LcdError::ZeroDenominator)
+        );
+        assert_eq!(
+            sum(&[Expr::new(i64::MAX, 0, 1), Expr::new(1, 0, 1)]),
+            Err(LcdError::Overflow)
+        );
+    }
+
+    #[test]
+    fn comparing() {
+        assert_eq!(
+            compare(Expr::new(0, 2, 3), Expr::new(0, 3, 4)),
+            Ok(Ordering::Less)
+        );
+        assert_eq!(
+            compare(Expr::new(1, 1, 2), Expr::new(0, 3, 2)),
+            Ok(Ordering::Equal)
+        );
+        assert_eq!(
+            compare(Expr::new(-2, 1, 5), Expr::new(0, -1, 5)),
+            Ok(Ordering::Less)
+        );
+        assert_eq!(
+            compare(Expr::new(0, 1, 0), Expr::new(0, 1, 2)),
+            Err(LcdError::ZeroDenominator)
+        );
+
+        // agrees with the Ord implementation
+        for a in -8..=8i64 {
+            for b in 1..=8i64 {
+                for x in -8..=8i64 {
+                    for y in 1..=8i64 {
+                        let lhs = Expr::new(0, a, b);
+                        let rhs = Expr::new(0, x, y);
+                        assert_eq!(compare(lhs, rhs), Ok(lhs.cmp(&rhs)));
+                    }
+                }
+            }
+        }
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
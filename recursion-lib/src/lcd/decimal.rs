@@ -0,0 +1,345 @@
+////////////////////////////////////////////////////////////////////////////////
+
+use std::collections::HashMap;
+
+use tailcall::tailcall;
+
+use super::{Expr, LcdError};
+
+////////////////////////////////////////////////////////////////////////////////
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+/// The exact decimal form of an expression, written with the repeating part
+/// in brackets.
+/// (i.e. 1(1/7) -> 1.(142857), 1/6 -> 0.1(6), 1/8 -> 0.125 )
+pub struct RepeatingDecimal {
+    /// Whether the value is below zero.
+    pub negative: bool,
+    /// The part before the decimal point.
+    pub whole: u128,
+    /// The digits after the decimal point, the pre-period followed by one copy
+    /// of the period.
+    pub digits: Vec<u8>,
+    /// How many digits come before the repeating part.
+    pub pre_period: usize,
+    /// How many digits repeat. Zero if the decimal terminates.
+    pub period: usize,
+}
+
+//---------------------------------------------------------------------------//
+
+impl RepeatingDecimal {
+    /// The `i`th digit after the decimal point, following the period around
+    /// as many times as needed.
+    pub fn digit(&self, i: usize) -> u8 {
+        if i < self.digits.len() {
+            self.digits[i]
+        } else if self.period == 0 {
+            // terminating decimals are followed by zeros forever
+            0
+        } else {
+            self.digits[self.pre_period + (i - self.pre_period) % self.period]
+        }
+    }
+}
+
+//---------------------------------------------------------------------------//
+
+impl std::fmt::Display for RepeatingDecimal {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.negative {
+            f.write_str("-")?;
+        }
+        f.write_fmt(format_args!("{}", self.whole))?;
+
+        if !self.digits.is_empty() {
+            f.write_str(".")?;
+            for d in &self.digits[..self.pre_period] {
+                f.write_fmt(format_args!("{}", d))?;
+            }
+            if self.period > 0 {
+                f.write_str("(")?;
+                for d in &self.digits[self.pre_period..] {
+                    f.write_fmt(format_args!("{}", d))?;
+                }
+                f.write_str(")")?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+/// Splits an expression into its sign, whole part, and the remainder left for
+/// long division.
+fn start(e: Expr) -> Result<(bool, u128, i128, i128), LcdError> {
+    if e.d == 0 {
+        return Err(LcdError::ZeroDenominator);
+    }
+
+    let (n, d) = e.to_improper_wide();
+    let whole = (n.abs() / d) as u128;
+    let r = n.abs() % d;
+
+    Ok((n < 0, whole, r, d))
+}
+
+//---------------------------------------------------------------------------//
+
+/// Recursively writes out an expression as an exact repeating decimal using
+/// long division.
+/// The digits start repeating as soon as a remainder comes up a second time.
+///
+/// Inputs:
+/// - `e: Expr` The expression to write out.
+///
+/// Outputs: `Result<RepeatingDecimal, LcdError>`
+/// The decimal, or why it couldn't be written out.
+///
+/// Time complexity: O(denominator)
+pub fn recursive(e: Expr) -> Result<RepeatingDecimal, LcdError> {
+    #[tailcall]
+    /// Produces digits until the remainder runs out or repeats.
+    /// Returns the position the repeating part starts at.
+    fn inner(r: i128, d: i128, digits: &mut Vec<u8>, seen: &mut HashMap<i128, usize>) -> usize {
+        if r == 0 {
+            // terminating case, divides evenly
+            digits.len()
+        } else if let Some(i) = seen.get(&r) {
+            // terminating case, we've been here before
+            *i
+        } else {
+            // bring down a zero and divide
+            seen.insert(r, digits.len());
+            digits.push((r * 10 / d) as u8);
+            inner(r * 10 % d, d, digits, seen)
+        }
+    }
+
+    let (negative, whole, r, d) = start(e)?;
+    let mut digits = vec![];
+    let pre_period = inner(r, d, &mut digits, &mut HashMap::new());
+
+    Ok(RepeatingDecimal {
+        negative,
+        whole,
+        period: digits.len() - pre_period,
+        digits,
+        pre_period,
+    })
+}
+
+//---------------------------------------------------------------------------//
+
+/// Iteratively writes out an expression as an exact repeating decimal using
+/// long division.
+/// The digits start repeating as soon as a remainder comes up a second time.
+///
+/// Inputs:
+/// - `e: Expr` The expression to write out.
+///
+/// Outputs: `Result<RepeatingDecimal, LcdError>`
+/// The decimal, or why it couldn't be written out.
+///
+/// Time complexity: O(denominator)
+pub fn iterative(e: Expr) -> Result<RepeatingDecimal, LcdError> {
+    let (negative, whole, mut r, d) = start(e)?;
+    let mut digits = vec![];
+
+    // where each remainder was first seen
+    let mut seen = HashMap::new();
+
+    let pre_period = loop {
+        if r == 0 {
+            // divides evenly
+            break digits.len();
+        }
+        if let Some(i) = seen.get(&r) {
+            // we've been here before
+            break *i;
+        }
+
+        // bring down a zero and divide
+        seen.insert(r, digits.len());
+        digits.push((r * 10 / d) as u8);
+        r = r * 10 % d;
+    };
+
+    Ok(RepeatingDecimal {
+        negative,
+        whole,
+        period: digits.len() - pre_period,
+        digits,
+        pre_period,
+    })
+}
+
+//---------------------------------------------------------------------------//
+
+impl Expr {
+    /// Writes out the expression as an exact decimal, with the repeating part
+    /// in brackets. See [`iterative`].
+    /// (i.e. 1(1/7) -> 1.(142857) )
+    pub fn to_repeating_decimal(self) -> Result<RepeatingDecimal, LcdError> {
+        iterative(self)
+    }
+
+    /// Writes out the expression as a decimal with `precision` digits after the
+    /// decimal point, rounding half away from zero.
+    /// Only does as much long division as the digits need, so unlike
+    /// [`iterative`] it doesn't matter how long the period is.
+    /// (i.e. 2/3, 3 -> 0.667 )
+    ///
+    /// Time complexity: O(precision)
+    pub fn to_decimal(self, precision: usize) -> Result<String, LcdError> {
+        let (negative, mut whole, mut r, d) = start(self)?;
+
+        // one more digit than is shown, to round with
+        let mut digits = Vec::with_capacity(precision + 1);
+        for _ in 0..=precision {
+            digits.push((r * 10 / d) as u8);
+            r = r * 10 % d;
+        }
+        let next = digits.pop().unwrap_or(0);
+
+        if next >= 5 {
+            // round up, carrying into the digits before
+            let mut i = precision;
+            loop {
+                if i == 0 {
+                    whole += 1;
+                    break;
+                }
+                i -= 1;
+                if digits[i] == 9 {
+                    digits[i] = 0;
+                } else {
+                    digits[i] += 1;
+                    break;
+                }
+            }
+        }
+
+        // a value that rounds to zero has no sign
+        let mut res = String::new();
+        if negative && (whole != 0 || digits.iter().any(|d| *d != 0)) {
+            res.push('-');
+        }
+        res.push_str(&whole.to_string());
+        if precision > 0 {
+            res.push('.');
+            res.extend(digits.iter().map(|d| (b'0' + d) as char));
+        }
+
+        Ok(res)
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use super::RepeatingDecimal;
+    use crate::lcd::{Expr, LcdError};
+
+    #[test]
+    fn iterative() {
+        common(super::iterative);
+    }
+
+    #[test]
+    fn recursive() {
+        common(super::recursive);
+    }
+
+    fn common(func: fn(Expr) -> Result<RepeatingDecimal, LcdError>) {
+        let show = |e| func(e).map(|dec| dec.to_string());
+
+        assert_eq!(show(Expr::new(1, 1, 7)), Ok("1.(142857)".to_string()));
+        assert_eq!(show(Expr::new(0, 1, 6)), Ok("0.1(6)".to_string()));
+        assert_eq!(show(Expr::new(0, 1, 8)), Ok("0.125".to_string()));
+        assert_eq!(show(Expr::new(0, 1, 3)), Ok("0.(3)".to_string()));
+        assert_eq!(show(Expr::new(0, -1, 3)), Ok("-0.(3)".to_string()));
+        assert_eq!(show(Expr::new(-2, 1, 4)), Ok("-2.25".to_string()));
+        assert_eq!(show(Expr::new(0, 1, 12)), Ok("0.08(3)".to_string()));
+        assert_eq!(show(Expr::new(0, 7, 12)), Ok("0.58(3)".to_string()));
+        assert_eq!(show(Expr::new(0, 1, 97)).unwrap().len(), "0.()".len() + 96);
+        assert_eq!(show(Expr::new(3, 0, 1)), Ok("3".to_string()));
+        assert_eq!(show(Expr::new(0, 0, 1)), Ok("0".to_string()));
+        assert_eq!(show(Expr::new(0, 6, -4)), Ok("-1.5".to_string()));
+        assert_eq!(show(Expr::new(0, 1, 0)), Err(LcdError::ZeroDenominator));
+        assert_eq!(
+            show(Expr::new(i64::MAX, 1, 2)),
+            Ok("9223372036854775807.5".to_string())
+        );
+
+        let dec = func(Expr::new(1, 1, 7)).unwrap();
+        assert_eq!((dec.pre_period, dec.period), (0, 6));
+        let dec = func(Expr::new(0, 1, 6)).unwrap();
+        assert_eq!((dec.pre_period, dec.period), (1, 1));
+        let dec = func(Expr::new(0, 3, 8)).unwrap();
+        assert_eq!((dec.pre_period, dec.period), (3, 0));
+        let dec = func(Expr::new(0, 1, 28)).unwrap();
+        assert_eq!((dec.pre_period, dec.period), (2, 6));
+
+        // reading it back gives the same value
+        // (as long as the digits fit in an i64 for the parser)
+        for d in 1..120i64 {
+            for n in -d..=d {
+                let e = Expr::new(0, n, d);
+                let dec = func(e).unwrap();
+                println!("{} -> {}", e, dec);
+
+                if dec.digits.len() <= 18 {
+                    assert_eq!(dec.to_string().parse::<Expr>(), Ok(e));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn to_decimal() {
+        let show = |e: Expr, p| e.to_decimal(p);
+
+        assert_eq!(show(Expr::new(0, 2, 3), 3), Ok("0.667".to_string()));
+        assert_eq!(show(Expr::new(0, 1, 3), 3), Ok("0.333".to_string()));
+        assert_eq!(show(Expr::new(1, 1, 7), 8), Ok("1.14285714".to_string()));
+        assert_eq!(show(Expr::new(0, 1, 8), 2), Ok("0.13".to_string()));
+        assert_eq!(show(Expr::new(0, 1, 8), 5), Ok("0.12500".to_string()));
+        assert_eq!(show(Expr::new(0, -1, 8), 2), Ok("-0.13".to_string()));
+        assert_eq!(show(Expr::new(0, 999, 1000), 2), Ok("1.00".to_string()));
+        assert_eq!(show(Expr::new(9, 1, 2), 0), Ok("10".to_string()));
+        assert_eq!(show(Expr::new(9, 1, 3), 0), Ok("9".to_string()));
+        assert_eq!(show(Expr::new(0, 1, 0), 2), Err(LcdError::ZeroDenominator));
+
+        // rounding to zero drops the sign
+        assert_eq!(show(Expr::new(0, -1, 1000), 2), Ok("0.00".to_string()));
+        assert_eq!(show(Expr::new(0, -1, 3), 0), Ok("0".to_string()));
+        assert_eq!(show(Expr::new(0, -1, 2), 0), Ok("-1".to_string()));
+
+        // long periods don't need writing out in full
+        assert_eq!(
+            show(Expr::new(0, 1, 999_999_999_989), 2),
+            Ok("0.00".to_string())
+        );
+        assert_eq!(
+            show(Expr::new(0, 1, 999_999_999_989), 14),
+            Ok("0.00000000000100".to_string())
+        );
+
+        // agrees with floats where they're exact enough
+        for d in 1..50i64 {
+            for n in -d..=d {
+                let e = Expr::new(0, n, d);
+                let x = n as f64 / d as f64;
+                let expected = (x * 1e4).abs().round() / 1e4;
+                let real = e.to_decimal(4).unwrap();
+                assert_eq!(real.trim_start_matches('-').parse::<f64>(), Ok(expected));
+            }
+        }
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
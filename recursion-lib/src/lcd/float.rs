@@ -0,0 +1,400 @@
+////////////////////////////////////////////////////////////////////////////////
+
+use super::{try_recursive, BigInt, Expr, Integer, LcdError};
+use crate::continued_fraction;
+use num_bigint::{BigUint, Sign};
+
+////////////////////////////////////////////////////////////////////////////////
+
+/// Bits in the significand of an `f64`, counting the implicit leading one.
+const MANTISSA_BITS: u32 = 53;
+
+//---------------------------------------------------------------------------//
+
+impl From<Expr> for f64 {
+    /// Converts an expression into the nearest `f64`, rounding ties to even.
+    /// Dividing the nominator by the denominator as floats rounds twice, this
+    /// does the long division exactly and rounds once.
+    /// (i.e. 2(1/5) -> 2.2 )
+    ///
+    /// An undefined expression behaves like a float division by zero.
+    fn from(e: Expr) -> f64 {
+        let (n, d) = e.to_improper_wide();
+        let sign = if n < 0 { -1.0 } else { 1.0 };
+
+        if d == 0 {
+            return if n == 0 {
+                f64::NAN
+            } else {
+                sign * f64::INFINITY
+            };
+        }
+        if n == 0 {
+            return 0.0;
+        }
+
+        let (a, b) = (n.unsigned_abs(), d as u128);
+
+        // the value is m * 2^exp, plus a little extra if `sticky` is set
+        // m is built up to two bits more than the significand holds, so the
+        // last of them can be rounded on
+        let (mut m, mut r) = (a / b, a % b);
+        let mut exp = 0i32;
+
+        // too many bits, shift them out and remember if any were set
+        let width = u128::BITS - m.leading_zeros();
+        let mut sticky = false;
+        if width > MANTISSA_BITS + 2 {
+            let shift = width - (MANTISSA_BITS + 2);
+            sticky = m & ((1 << shift) - 1) != 0;
+            m >>= shift;
+            exp += shift as i32;
+        }
+
+        // too few bits, bring down more from the remainder
+        // r < b <= 2^63, so doubling it can't overflow
+        while m < 1 << (MANTISSA_BITS + 1) {
+            r <<= 1;
+            m <<= 1;
+            if r >= b {
+                r -= b;
+                m |= 1;
+            }
+            exp -= 1;
+        }
+        sticky |= r != 0;
+
+        // the significand is exact as an f64, and scaling by a power of two
+        // can't round since the value is well within the normal range
+        sign * round(m, sticky) as f64 * 2f64.powi(exp + 2)
+    }
+}
+
+impl From<Expr<BigInt>> for f64 {
+    /// Converts an exact expression into the nearest `f64`, rounding ties to
+    /// even. Values too large for an `f64` become infinite, and values too
+    /// small become zero, or the nearest subnormal.
+    /// (i.e. 0(1/2^1074) -> 5e-324 )
+    ///
+    /// An undefined expression behaves like a float division by zero.
+    fn from(e: Expr<BigInt>) -> f64 {
+        let (n, d) = e.to_improper();
+        let sign = if n.is_negative() != d.is_negative() {
+            -1.0
+        } else {
+            1.0
+        };
+
+        if d.is_zero() {
+            return if n.is_zero() {
+                f64::NAN
+            } else {
+                sign * f64::INFINITY
+            };
+        }
+        if n.is_zero() {
+            return 0.0;
+        }
+
+        let (a, b) = (n.magnitude(), d.magnitude());
+
+        // a/b >= 2^top, but less than 2^(top+1)
+        let mut top = a.bits() as i64 - b.bits() as i64;
+        let (x, y) = scale(a, b, top);
+        if x < y {
+            top -= 1;
+        }
+        if top >= f64::MAX_EXP as i64 {
+            return sign * f64::INFINITY;
+        }
+
+        // the lowest bit the result can hold, which is fixed for subnormals
+        let lowest = (top + 1 - MANTISSA_BITS as i64).max(-1074);
+
+        // m = a/b / 2^lowest, with two more bits to round on
+        let (x, y) = scale(a, b, lowest - 2);
+        let m = u128::try_from(&x / &y).expect("at most two more bits than an f64");
+        let sticky = (&x % &y) != BigUint::ZERO;
+
+        // the product is exactly representable, so it doesn't round again, but
+        // rounding up can still reach infinity
+        sign * round(m, sticky) as f64 * pow2(lowest)
+    }
+}
+
+/// Rounds `m`, which has two more bits than are kept, half to even. `sticky`
+/// is whether anything was left over below those two bits.
+fn round(m: u128, sticky: bool) -> u128 {
+    let mut significand = m >> 2;
+    let half = m & 0b10 != 0;
+    let rest = m & 0b01 != 0 || sticky;
+    if half && (rest || significand & 1 == 1) {
+        significand += 1;
+    }
+    significand
+}
+
+/// Scales a/b by 2^-exp, by shifting whichever side keeps it whole.
+fn scale(a: &BigUint, b: &BigUint, exp: i64) -> (BigUint, BigUint) {
+    if exp >= 0 {
+        (a.clone(), b << exp as u64)
+    } else {
+        (a << exp.unsigned_abs(), b.clone())
+    }
+}
+
+/// 2^exp, for any exponent an `f64` can hold exactly, subnormals included.
+fn pow2(exp: i64) -> f64 {
+    if exp >= f64::MIN_EXP as i64 - 1 {
+        f64::from_bits(((exp + 1023) as u64) << 52)
+    } else {
+        f64::from_bits(1 << (exp + 1074))
+    }
+}
+
+//---------------------------------------------------------------------------//
+
+impl Expr<BigInt> {
+    /// Converts a float into the exact fraction it stores.
+    /// Every finite `f64` is m * 2^e for some integers m and e, so it is a
+    /// dyadic fraction, though most need far more than an `i64` to write down,
+    /// so this uses a [`BigInt`].
+    /// (i.e. 0.1 -> 3602879701896397/36028797018963968, -2.25 -> -2(1/4) )
+    ///
+    /// Inputs:
+    /// - `x: f64` The float to convert.
+    ///
+    /// Outputs: `Result<Expr<BigInt>, LcdError<BigInt>>`
+    /// The simplified expression with exactly the same value as `x`, or
+    /// `LcdError::NotFinite` for infinities and NaN.
+    ///
+    /// Time complexity: O(1)
+    pub fn from_f64_exact(x: f64) -> Result<Self, LcdError<BigInt>> {
+        if !x.is_finite() {
+            return Err(LcdError::NotFinite);
+        }
+
+        // sign, biased exponent, and fraction bits
+        let bits = x.to_bits();
+        let negative = bits >> 63 == 1;
+        let biased = ((bits >> 52) & 0x7ff) as i32;
+        let fraction = bits & ((1 << 52) - 1);
+
+        // x = m * 2^exp, subnormals have no implicit one
+        let (mut m, mut exp) = if biased == 0 {
+            (fraction, -1074)
+        } else {
+            (fraction | 1 << 52, biased - 1075)
+        };
+
+        if m == 0 {
+            return Ok(Expr::new(BigInt::zero(), BigInt::zero(), BigInt::one()));
+        }
+
+        // move any factors of two out of m, so the fraction is reduced
+        let zeros = m.trailing_zeros();
+        m >>= zeros;
+        exp += zeros as i32;
+
+        let sign = if negative { Sign::Minus } else { Sign::Plus };
+        let m = BigInt::from_biguint(sign, BigUint::from(m));
+
+        if exp >= 0 {
+            // a whole number, m * 2^exp
+            Ok(Expr::new(m << exp, BigInt::zero(), BigInt::one()))
+        } else {
+            // m / 2^-exp, which is already in lowest terms since m is odd
+            try_recursive(Expr::new(BigInt::zero(), m, BigInt::one() << -exp))
+        }
+    }
+}
+
+impl Expr {
+    /// Converts a float into the closest fraction whose denominator is at most
+    /// `max_den`. See [`continued_fraction::try_best_approximation`].
+    /// (i.e. 0.1, 100 -> 1/10, 3.14159..., 1000 -> 3(16/113) )
+    ///
+    /// Inputs:
+    /// - `x: f64` The float to approximate.
+    /// - `max_den: i64` The largest denominator allowed.
+    ///
+    /// Outputs: `Result<Expr, LcdError>`
+    /// The simplified best approximation, or why it couldn't be found.
+    /// `LcdError::ZeroDenominator` if `max_den` is less than 1, and
+    /// `LcdError::Overflow` if the whole part doesn't fit in an `i64`.
+    ///
+    /// Time complexity: O(log(max_den))
+    pub fn from_f64_approx(x: f64, max_den: i64) -> Result<Self, LcdError> {
+        continued_fraction::try_best_approximation(x, max_den)
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use crate::lcd::{BigInt, Expr, LcdError};
+
+    /// A small linear congruential generator, so the samples are repeatable.
+    fn samples(seed: u64) -> impl Iterator<Item = u64> {
+        let mut state = seed;
+        std::iter::from_fn(move || {
+            state = state
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            Some(state)
+        })
+    }
+
+    #[test]
+    fn to_f64() {
+        let f = |c, n, d| f64::from(Expr::new(c, n, d));
+
+        assert_eq!(f(2, 1, 5), 2.2);
+        assert_eq!(f(-2, 1, 5), -2.2);
+        assert_eq!(f(0, -1, 3), -1.0 / 3.0);
+        assert_eq!(f(0, 0, 1), 0.0);
+        assert_eq!(f(7, 0, 1), 7.0);
+        assert_eq!(f(0, 6, -4), -1.5);
+        assert_eq!(f(0, 1, 1 << 62), 2f64.powi(-62));
+        assert_eq!(f(i64::MAX, 1, 2), 2f64.powi(63));
+        assert_eq!(f(i64::MIN, 0, 1), -(2f64.powi(63)));
+        assert_eq!(f(0, 1, 0), f64::INFINITY);
+        assert_eq!(f(0, -1, 0), f64::NEG_INFINITY);
+        assert!(f(0, 0, 0).is_nan());
+
+        // ties round to even
+        let two53 = 1i64 << 53;
+        assert_eq!(f(two53 + 1, 0, 1), two53 as f64);
+        assert_eq!(f(two53 + 3, 0, 1), (two53 + 4) as f64);
+        assert_eq!(f(two53, 1, 2), two53 as f64);
+        assert_eq!(f(two53 + 1, 1, 2), (two53 + 2) as f64);
+
+        // a float division of two exact values is correctly rounded, so they
+        // have to agree
+        for d in 1..200i64 {
+            for n in -2 * d..=2 * d {
+                assert_eq!(f(0, n, d), n as f64 / d as f64);
+            }
+        }
+        for (n, d) in samples(1).zip(samples(2)).take(10_000) {
+            let n = (n >> 11) as i64 - (1 << 52);
+            let d = (d >> 11) as i64 | 1;
+            assert_eq!(f(0, n, d), n as f64 / d as f64, "{}/{}", n, d);
+        }
+    }
+
+    #[test]
+    fn big_to_f64() {
+        let big = |n: BigInt, d: BigInt| f64::from(Expr::new(BigInt::from(0), n, d));
+        let two = BigInt::from(2);
+
+        assert_eq!(big(BigInt::from(-1), BigInt::from(3)), -1.0 / 3.0);
+        assert_eq!(big(BigInt::from(7), BigInt::from(-2)), -3.5);
+        assert_eq!(big(BigInt::from(1), two.pow(1074)), 5e-324);
+        assert_eq!(big(BigInt::from(1), two.pow(1075)), 0.0);
+        assert_eq!(big(BigInt::from(3), two.pow(1076)), 5e-324);
+        assert_eq!(big(two.pow(1024) - 1, BigInt::from(1)), f64::INFINITY);
+        assert_eq!(big(-two.pow(2000), BigInt::from(3)), f64::NEG_INFINITY);
+        assert_eq!(big(BigInt::from(1), BigInt::from(0)), f64::INFINITY);
+        assert!(big(BigInt::from(0), BigInt::from(0)).is_nan());
+        assert_eq!(
+            f64::from(Expr::new(
+                BigInt::from(-2),
+                BigInt::from(1),
+                BigInt::from(5)
+            )),
+            -2.2
+        );
+
+        // the largest finite value, and the tie above it, which rounds to even
+        let max: BigInt = (two.pow(53) - 1) << 971;
+        assert_eq!(big(max.clone(), BigInt::from(1)), f64::MAX);
+        assert_eq!(
+            big(max + (BigInt::from(1) << 970), BigInt::from(1)),
+            f64::INFINITY
+        );
+
+        // the same as the i64 conversion wherever that works
+        for (n, d) in samples(4).zip(samples(5)).take(10_000) {
+            let (n, d) = (n as i64, (d >> 1) as i64 | 1);
+            assert_eq!(
+                big(BigInt::from(n), BigInt::from(d)),
+                f64::from(Expr::new(0, n, d)),
+                "{}/{}",
+                n,
+                d
+            );
+        }
+    }
+
+    #[test]
+    fn from_f64_exact() {
+        let exact = |x| Expr::from_f64_exact(x).map(|e| e.to_string());
+
+        assert_eq!(exact(0.5), Ok("0(1/2)".into()));
+        assert_eq!(exact(-2.25), Ok("-2(1/4)".into()));
+        assert_eq!(exact(3.0), Ok("3(0/1)".into()));
+        assert_eq!(exact(0.0), Ok("0(0/1)".into()));
+        assert_eq!(exact(-0.0), Ok("0(0/1)".into()));
+        assert_eq!(
+            exact(0.1),
+            Ok("0(3602879701896397/36028797018963968)".into())
+        );
+        assert_eq!(exact(2f64.powi(63)), Ok("9223372036854775808(0/1)".into()));
+        assert_eq!(exact(f64::NAN), Err(LcdError::NotFinite));
+        assert_eq!(exact(f64::INFINITY), Err(LcdError::NotFinite));
+
+        let two = BigInt::from(2);
+        let e = Expr::from_f64_exact(f64::MAX).unwrap();
+        assert_eq!(e.c, (two.pow(53) - 1) << 971);
+        let e = Expr::from_f64_exact(-5e-324).unwrap();
+        assert_eq!((e.n, e.d), (BigInt::from(-1), two.pow(1074)));
+
+        // random bit patterns across every exponent, including subnormals
+        for (exp, bits) in
+            (0..0x7ffu64).flat_map(|exp| samples(exp).take(16).map(move |b| (exp, b)))
+        {
+            let sign = bits & (1 << 63);
+            // vary how many of the low bits are set
+            let fraction = (bits & ((1 << 52) - 1)) >> (bits % 53);
+            let x = f64::from_bits(sign | exp << 52 | fraction);
+
+            let e = Expr::from_f64_exact(x).unwrap();
+            assert!(e.is_canonical());
+            // and back again, bit for bit (other than -0.0)
+            assert_eq!(f64::from(e.clone()), x, "{:e} -> {}", x, e);
+            if x != 0.0 {
+                assert_eq!(f64::from(e).to_bits(), x.to_bits());
+            }
+        }
+    }
+
+    #[test]
+    fn from_f64_approx() {
+        let approx = |x, d| Expr::from_f64_approx(x, d).map(|e| (e.c, e.n, e.d));
+
+        assert_eq!(approx(0.1, 100), Ok((0, 1, 10)));
+        assert_eq!(approx(std::f64::consts::PI, 1000), Ok((3, 16, 113)));
+        assert_eq!(approx(-std::f64::consts::PI, 10), Ok((-3, 1, 7)));
+        assert_eq!(approx(-0.3333, 10), Ok((0, -1, 3)));
+        assert_eq!(approx(2.5, 1), Ok((2, 0, 1)));
+        assert_eq!(approx(1e15 + 0.5, 2), Ok((1_000_000_000_000_000, 1, 2)));
+
+        assert_eq!(approx(f64::NAN, 10), Err(LcdError::NotFinite));
+        assert_eq!(approx(0.5, 0), Err(LcdError::ZeroDenominator));
+        assert_eq!(approx(1e30, 10), Err(LcdError::Overflow));
+        assert_eq!(approx(-(2f64.powi(63)), 10), Ok((i64::MIN, 0, 1)));
+        assert_eq!(approx(2f64.powi(63), 10), Err(LcdError::Overflow));
+
+        // always at least as close as the exact value allows
+        for x in samples(3).take(2_000) {
+            let x = (x >> 11) as f64 / (1u64 << 40) as f64 - 4096.0;
+            let e = Expr::from_f64_approx(x, 1000).unwrap();
+            assert!(e.d <= 1000);
+            assert!((f64::from(e) - x).abs() <= 0.5 / e.d as f64);
+        }
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
@@ -0,0 +1,132 @@
+////////////////////////////////////////////////////////////////////////////////
+
+use num_bigint::BigInt;
+
+////////////////////////////////////////////////////////////////////////////////
+
+/// The integer types an [`Expr`](super::Expr) can be built from.
+/// Every operation is checked, returning `None` instead of overflowing, so the
+/// same simplification code works for small fixed width types and for
+/// [`BigInt`], which never overflows.
+pub trait Integer:
+    Clone + Default + std::fmt::Debug + std::fmt::Display + Ord + std::hash::Hash
+{
+    fn zero() -> Self;
+    fn one() -> Self;
+
+    fn checked_add(&self, rhs: &Self) -> Option<Self>;
+    fn checked_sub(&self, rhs: &Self) -> Option<Self>;
+    fn checked_mul(&self, rhs: &Self) -> Option<Self>;
+    /// Truncating division. `None` when dividing by zero.
+    fn checked_div(&self, rhs: &Self) -> Option<Self>;
+    /// The remainder of truncating division. `None` when dividing by zero.
+    fn checked_rem(&self, rhs: &Self) -> Option<Self>;
+    fn checked_neg(&self) -> Option<Self>;
+
+    /// The same value as a [`BigInt`], which can hold any of them.
+    fn to_bigint(&self) -> BigInt;
+
+    fn is_zero(&self) -> bool {
+        *self == Self::zero()
+    }
+
+    fn is_negative(&self) -> bool {
+        *self < Self::zero()
+    }
+}
+
+//---------------------------------------------------------------------------//
+
+macro_rules! primitive {
+    ($($t:ty),*) => {$(
+        impl Integer for $t {
+            fn zero() -> Self {
+                0
+            }
+
+            fn one() -> Self {
+                1
+            }
+
+            fn checked_add(&self, rhs: &Self) -> Option<Self> {
+                <$t>::checked_add(*self, *rhs)
+            }
+
+            fn checked_sub(&self, rhs: &Self) -> Option<Self> {
+                <$t>::checked_sub(*self, *rhs)
+            }
+
+            fn checked_mul(&self, rhs: &Self) -> Option<Self> {
+                <$t>::checked_mul(*self, *rhs)
+            }
+
+            fn checked_div(&self, rhs: &Self) -> Option<Self> {
+                <$t>::checked_div(*self, *rhs)
+            }
+
+            fn checked_rem(&self, rhs: &Self) -> Option<Self> {
+                <$t>::checked_rem(*self, *rhs)
+            }
+
+            fn checked_neg(&self) -> Option<Self> {
+                <$t>::checked_neg(*self)
+            }
+
+            fn to_bigint(&self) -> BigInt {
+                BigInt::from(*self)
+            }
+        }
+    )*};
+}
+
+primitive!(i32, i64, i128);
+
+//---------------------------------------------------------------------------//
+
+impl Integer for BigInt {
+    fn zero() -> Self {
+        BigInt::from(0)
+    }
+
+    fn one() -> Self {
+        BigInt::from(1)
+    }
+
+    fn checked_add(&self, rhs: &Self) -> Option<Self> {
+        Some(self + rhs)
+    }
+
+    fn checked_sub(&self, rhs: &Self) -> Option<Self> {
+        Some(self - rhs)
+    }
+
+    fn checked_mul(&self, rhs: &Self) -> Option<Self> {
+        Some(self * rhs)
+    }
+
+    fn checked_div(&self, rhs: &Self) -> Option<Self> {
+        if rhs.is_zero() {
+            None
+        } else {
+            Some(self / rhs)
+        }
+    }
+
+    fn checked_rem(&self, rhs: &Self) -> Option<Self> {
+        if rhs.is_zero() {
+            None
+        } else {
+            Some(self % rhs)
+        }
+    }
+
+    fn checked_neg(&self) -> Option<Self> {
+        Some(-self)
+    }
+
+    fn to_bigint(&self) -> BigInt {
+        self.clone()
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
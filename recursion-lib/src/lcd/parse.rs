@@ -0,0 +1,401 @@
+////////////////////////////////////////////////////////////////////////////////
+
+use super::{try_recursive, Expr};
+
+////////////////////////////////////////////////////////////////////////////////
+
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+/// What went wrong while parsing an expression.
+pub enum ParseExprErrorKind {
+    /// Found a character that doesn't fit at this point.
+    UnexpectedChar(char),
+    /// The input stopped before the expression was complete.
+    UnexpectedEnd,
+    /// The denominator is zero, so the fraction is undefined.
+    ZeroDenominator,
+    /// The number doesn't fit in an `i64`.
+    Overflow,
+}
+
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+/// An error from parsing an [`Expr`], along with where it happened.
+pub struct ParseExprError {
+    /// What went wrong.
+    pub kind: ParseExprErrorKind,
+    /// The byte offset into the input where it went wrong.
+    pub pos: usize,
+}
+
+//---------------------------------------------------------------------------//
+
+impl std::fmt::Display for ParseExprError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.kind {
+            ParseExprErrorKind::UnexpectedChar(c) => {
+                f.write_fmt(format_args!("unexpected {:?} at position {}", c, self.pos))
+            }
+            ParseExprErrorKind::UnexpectedEnd => f.write_fmt(format_args!(
+                "unexpected end of input at position {}",
+                self.pos
+            )),
+            ParseExprErrorKind::ZeroDenominator => {
+                f.write_fmt(format_args!("zero denominator at position {}", self.pos))
+            }
+            ParseExprErrorKind::Overflow => {
+                f.write_fmt(format_args!("number too large at position {}", self.pos))
+            }
+        }
+    }
+}
+
+impl std::error::Error for ParseExprError {}
+
+////////////////////////////////////////////////////////////////////////////////
+
+/// Walks through the input one character at a time.
+struct Parser<'a> {
+    src: &'a str,
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn peek(&self) -> Option<char> {
+        self.src[self.pos..].chars().next()
+    }
+
+    fn bump(&mut self) {
+        if let Some(c) = self.peek() {
+            self.pos += c.len_utf8();
+        }
+    }
+
+    fn error(&self, kind: ParseExprErrorKind, pos: usize) -> ParseExprError {
+        ParseExprError { kind, pos }
+    }
+
+    /// Complains about whatever is at the current position.
+    fn unexpected(&self) -> ParseExprError {
+        match self.peek() {
+            Some(c) => self.error(ParseExprErrorKind::UnexpectedChar(c), self.pos),
+            None => self.error(ParseExprErrorKind::UnexpectedEnd, self.pos),
+        }
+    }
+
+    fn expect(&mut self, c: char) -> Result<(), ParseExprError> {
+        if self.peek() == Some(c) {
+            self.bump();
+            Ok(())
+        } else {
+            Err(self.unexpected())
+        }
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.peek().is_some_and(char::is_whitespace) {
+            self.bump();
+        }
+    }
+
+    /// Consumes an optional sign, returning whether it was negative.
+    fn sign(&mut self) -> bool {
+        match self.peek() {
+            Some('-') => {
+                self.bump();
+                true
+            }
+            Some('+') => {
+                self.bump();
+                false
+            }
+            _ => false,
+        }
+    }
+
+    /// Consumes one or more digits.
+    /// Returns their value and how many there were.
+    /// The value can be one more than `i64::MAX`, so that `i64::MIN` can be
+    /// parsed before its sign is applied.
+    fn digits(&mut self) -> Result<(i128, u32), ParseExprError> {
+        let start = self.pos;
+        let mut value: i128 = 0;
+        let mut count = 0;
+
+        while let Some(digit) = self.peek().and_then(|c| c.to_digit(10)) {
+            value = value * 10 + digit as i128;
+            if value > -(i64::MIN as i128) {
+                return Err(self.error(ParseExprErrorKind::Overflow, start));
+            }
+            count += 1;
+            self.bump();
+        }
+
+        if count == 0 {
+            Err(self.unexpected())
+        } else {
+            Ok((value, count))
+        }
+    }
+
+    /// Narrows a value down to an `i64`, blaming `start` if it doesn't fit.
+    fn narrow(&self, value: i128, start: usize) -> Result<i64, ParseExprError> {
+        i64::try_from(value).map_err(|_| self.error(ParseExprErrorKind::Overflow, start))
+    }
+
+    /// Consumes a signed integer.
+    fn integer(&mut self) -> Result<i64, ParseExprError> {
+        let start = self.pos;
+        let neg = self.sign();
+        let (value, _) = self.digits()?;
+        self.narrow(if neg { -value } else { value }, start)
+    }
+
+    /// Consumes a denominator, making sure it isn't zero.
+    fn denominator(&mut self) -> Result<i64, ParseExprError> {
+        let start = self.pos;
+        let d = self.integer()?;
+
+        if d == 0 {
+            Err(self.error(ParseExprErrorKind::ZeroDenominator, start))
+        } else {
+            Ok(d)
+        }
+    }
+
+    /// Consumes `n/d`.
+    fn fraction(&mut self) -> Result<(i64, i64), ParseExprError> {
+        let n = self.integer()?;
+        self.expect('/')?;
+        let d = self.denominator()?;
+        Ok((n, d))
+    }
+
+    /// Consumes the digits after a decimal point, including an optional
+    /// bracketed repetend.
+    /// (i.e. `125` -> 125/1000, `1(6)` -> 15/90 )
+    fn decimal(&mut self) -> Result<(i64, i64), ParseExprError> {
+        let start = self.pos;
+        let overflow = self.error(ParseExprErrorKind::Overflow, start);
+
+        // the part that doesn't repeat
+        let (a, a_len) = if self.peek().is_some_and(|c| c.is_ascii_digit()) {
+            let (a, a_len) = self.digits()?;
+            (self.narrow(a, start)?, a_len)
+        } else if self.peek() == Some('(') {
+            (0, 0)
+        } else {
+            return Err(self.unexpected());
+        };
+        let shift = 10i64.checked_pow(a_len).ok_or(overflow)?;
+
+        if self.peek() == Some('(') {
+            // 0.a(r) = (a*(10^|r| - 1) + r) / (10^|a| * (10^|r| - 1))
+            self.bump();
+            let r_start = self.pos;
+            let (r, r_len) = self.digits()?;
+            let r = self.narrow(r, r_start)?;
+            self.expect(')')?;
+
+            let nines = 10i64.checked_pow(r_len).map(|p| p - 1).ok_or(overflow)?;
+            let n = a
+                .checked_mul(nines)
+                .and_then(|n| n.checked_add(r))
+                .ok_or(overflow)?;
+            let d = shift.checked_mul(nines).ok_or(overflow)?;
+
+            Ok((n, d))
+        } else {
+            // 0.a = a / 10^|a|
+            Ok((a, shift))
+        }
+    }
+}
+
+//---------------------------------------------------------------------------//
+
+impl std::str::FromStr for Expr {
+    type Err = ParseExprError;
+
+    /// Parses an expression, simplifying it.
+    /// Accepts any of:
+    /// - Mixed numbers, like `2(1/5)` or `2 1/5`
+    /// - Fractions, like `11/5`
+    /// - Integers, like `-3`
+    /// - Decimals, like `0.125`
+    /// - Repeating decimals, like `0.(3)` or `0.1(6)`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut p = Parser { src: s, pos: 0 };
+
+        p.skip_whitespace();
+        let start = p.pos;
+        let overflow = p.error(ParseExprErrorKind::Overflow, start);
+
+        let neg = p.sign();
+        let (whole, _) = p.digits()?;
+
+        // work out the value as c(n/d), ignoring the sign for now
+        let (c, n, d) = match p.peek() {
+            Some('/') => {
+                // n/d
+                p.bump();
+                (0, whole, p.denominator()? as i128)
+            }
+            Some('(') => {
+                // c(n/d)
+                p.bump();
+                let (n, d) = p.fraction()?;
+                p.expect(')')?;
+                (whole, n as i128, d as i128)
+            }
+            Some('.') => {
+                // c.a(r)
+                p.bump();
+                let (n, d) = p.decimal()?;
+                (whole, n as i128, d as i128)
+            }
+            Some(c) if c.is_whitespace() => {
+                // c n/d, or just trailing whitespace
+                p.skip_whitespace();
+                if p.peek().is_some() {
+                    let (n, d) = p.fraction()?;
+                    (whole, n as i128, d as i128)
+                } else {
+                    (whole, 0, 1)
+                }
+            }
+            _ => (whole, 0, 1),
+        };
+
+        p.skip_whitespace();
+        if p.peek().is_some() {
+            return Err(p.unexpected());
+        }
+
+        // c(n/d) -> (c*d + n)/d, then apply the sign to the whole thing.
+        // Each part fits in 64 bits, so none of this can overflow an i128
+        let n = c * d + n;
+        let n = p.narrow(if neg { -n } else { n }, start)?;
+        let d = p.narrow(d, start)?;
+
+        try_recursive(Expr::new(0, n, d)).map_err(|_| overflow)
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use super::{ParseExprError, ParseExprErrorKind};
+    use crate::lcd::Expr;
+
+    fn parse(s: &str) -> Result<(i64, i64, i64), ParseExprError> {
+        s.parse::<Expr>().map(|e| (e.c, e.n, e.d))
+    }
+
+    fn error(kind: ParseExprErrorKind, pos: usize) -> Result<(i64, i64, i64), ParseExprError> {
+        Err(ParseExprError { kind, pos })
+    }
+
+    #[test]
+    fn forms() {
+        // mixed numbers
+        assert_eq!(parse("2(1/5)"), Ok((2, 1, 5)));
+        assert_eq!(parse("-2(1/5)"), Ok((-2, 1, 5)));
+        assert_eq!(parse("0(-1/5)"), Ok((0, -1, 5)));
+        assert_eq!(parse("1(2/4)"), Ok((1, 1, 2)));
+        assert_eq!(parse("2 1/5"), Ok((2, 1, 5)));
+        assert_eq!(parse("-2 1/5"), Ok((-2, 1, 5)));
+        assert_eq!(parse("  2   1/5  "), Ok((2, 1, 5)));
+
+        // fractions
+        assert_eq!(parse("21/45"), Ok((0, 7, 15)));
+        assert_eq!(parse("11/5"), Ok((2, 1, 5)));
+        assert_eq!(parse("-11/5"), Ok((-2, 1, 5)));
+        assert_eq!(parse("1/-5"), Ok((0, -1, 5)));
+        assert_eq!(parse("10/5"), Ok((2, 0, 1)));
+
+        // integers
+        assert_eq!(parse("0"), Ok((0, 0, 1)));
+        assert_eq!(parse("7"), Ok((7, 0, 1)));
+        assert_eq!(parse("-7"), Ok((-7, 0, 1)));
+        assert_eq!(parse("+7"), Ok((7, 0, 1)));
+        assert_eq!(parse("9223372036854775807"), Ok((i64::MAX, 0, 1)));
+        assert_eq!(parse("-9223372036854775808"), Ok((i64::MIN, 0, 1)));
+        assert_eq!(parse("-9223372036854775808/1"), Ok((i64::MIN, 0, 1)));
+
+        // decimals
+        assert_eq!(parse("0.125"), Ok((0, 1, 8)));
+        assert_eq!(parse("-0.125"), Ok((0, -1, 8)));
+        assert_eq!(parse("1.5"), Ok((1, 1, 2)));
+        assert_eq!(parse("-1.5"), Ok((-1, 1, 2)));
+        assert_eq!(parse("2.0"), Ok((2, 0, 1)));
+
+        // repeating decimals
+        assert_eq!(parse("0.(3)"), Ok((0, 1, 3)));
+        assert_eq!(parse("0.1(6)"), Ok((0, 1, 6)));
+        assert_eq!(parse("-0.(3)"), Ok((0, -1, 3)));
+        assert_eq!(parse("1.(142857)"), Ok((1, 1, 7)));
+        assert_eq!(parse("0.(9)"), Ok((1, 0, 1)));
+        assert_eq!(parse("0.08(3)"), Ok((0, 1, 12)));
+    }
+
+    #[test]
+    fn errors() {
+        use ParseExprErrorKind::*;
+
+        assert_eq!(parse(""), error(UnexpectedEnd, 0));
+        assert_eq!(parse("   "), error(UnexpectedEnd, 3));
+        assert_eq!(parse("abc"), error(UnexpectedChar('a'), 0));
+        assert_eq!(parse("-"), error(UnexpectedEnd, 1));
+        assert_eq!(parse("1/"), error(UnexpectedEnd, 2));
+        assert_eq!(parse("1/0"), error(ZeroDenominator, 2));
+        assert_eq!(parse("1/-0"), error(ZeroDenominator, 2));
+        assert_eq!(parse("2(1/0)"), error(ZeroDenominator, 4));
+        assert_eq!(parse("2(1/5"), error(UnexpectedEnd, 5));
+        assert_eq!(parse("2(1/5]"), error(UnexpectedChar(']'), 5));
+        assert_eq!(parse("2(1)"), error(UnexpectedChar(')'), 3));
+        assert_eq!(parse("2 1"), error(UnexpectedEnd, 3));
+        assert_eq!(parse("1/2/3"), error(UnexpectedChar('/'), 3));
+        assert_eq!(parse("1.2.3"), error(UnexpectedChar('.'), 3));
+        assert_eq!(parse("1."), error(UnexpectedEnd, 2));
+        assert_eq!(parse("0.()"), error(UnexpectedChar(')'), 3));
+        assert_eq!(parse("0.(3"), error(UnexpectedEnd, 4));
+        assert_eq!(parse("0.(3)4"), error(UnexpectedChar('4'), 5));
+        assert_eq!(parse("1 ½"), error(UnexpectedChar('½'), 2));
+        assert_eq!(parse("3 x"), error(UnexpectedChar('x'), 2));
+        assert_eq!(parse("99999999999999999999"), error(Overflow, 0));
+        assert_eq!(parse("9223372036854775808"), error(Overflow, 0));
+        assert_eq!(parse("-9223372036854775809"), error(Overflow, 1));
+        assert_eq!(parse("1/9223372036854775808"), error(Overflow, 2));
+        assert_eq!(parse("1/99999999999999999999"), error(Overflow, 2));
+        assert_eq!(parse("9223372036854775807(1/2)"), error(Overflow, 0));
+        assert_eq!(parse("0.00000000000000000001"), error(Overflow, 2));
+
+        let err = "2(1/0)".parse::<Expr>().unwrap_err();
+        assert_eq!(err.to_string(), "zero denominator at position 4");
+    }
+
+    #[test]
+    fn round_trip() {
+        for c in -5..=5i64 {
+            for n in -12..=12i64 {
+                for d in 1..=12i64 {
+                    let e = Expr::new(c, n, d);
+                    if !e.is_canonical() {
+                        continue;
+                    }
+
+                    let text = e.to_string();
+                    println!("{} -> {:?}", text, parse(&text));
+                    assert_eq!(parse(&text), Ok((c, n, d)));
+                }
+            }
+        }
+
+        // the extremes
+        for e in [Expr::new(i64::MIN, 0, 1), Expr::new(i64::MAX, 0, 1)] {
+            assert_eq!(parse(&e.to_string()), Ok((e.c, e.n, e.d)));
+        }
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
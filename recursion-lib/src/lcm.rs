@@ -0,0 +1,110 @@
+////////////////////////////////////////////////////////////////////////////////
+
+use crate::gcd;
+
+////////////////////////////////////////////////////////////////////////////////
+
+/// Computes the Least Common Multiple of a pair of integers.
+/// lcm(a, b) = |a| / gcd(a, b) * |b|
+///
+/// Returns `None` if the result doesn't fit in an `i64`.
+fn pair(a: i64, b: i64) -> Option<i64> {
+    if a == 0 || b == 0 {
+        // zero is the only multiple of zero
+        Some(0)
+    } else {
+        // dividing first keeps the intermediate value small
+        (a / gcd::iterative(a, b)).checked_mul(b)?.checked_abs()
+    }
+}
+
+//---------------------------------------------------------------------------//
+
+/// Recursively computes the Least Common Multiple of several integers.
+///
+/// Inputs:
+/// - `xs: &[i64]`
+///
+/// Outputs: `Option<i64>`
+/// The smallest non-negative integer that every value in `xs` divides.
+/// The lcm of no values is 1, and the lcm of anything with 0 is 0.
+/// `None` if the result doesn't fit in an `i64`.
+///
+/// Time complexity: O(len(xs) * log(max(xs)))
+pub fn recursive(xs: &[i64]) -> Option<i64> {
+    match xs {
+        // terminating case, 1 divides everything
+        [] => Some(1),
+        // general case, lcm(a, b, c, ...) = lcm(a, lcm(b, c, ...))
+        [x, rest @ ..] => pair(*x, recursive(rest)?),
+    }
+}
+
+//---------------------------------------------------------------------------//
+
+/// Iteratively computes the Least Common Multiple of several integers.
+///
+/// Inputs:
+/// - `xs: &[i64]`
+///
+/// Outputs: `Option<i64>`
+/// The smallest non-negative integer that every value in `xs` divides.
+/// The lcm of no values is 1, and the lcm of anything with 0 is 0.
+/// `None` if the result doesn't fit in an `i64`.
+///
+/// Time complexity: O(len(xs) * log(max(xs)))
+pub fn iterative(xs: &[i64]) -> Option<i64> {
+    let mut res: i64 = 1;
+
+    // fold each value into the running multiple
+    for x in xs {
+        res = pair(res, *x)?;
+    }
+
+    Some(res)
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+
+    #[test]
+    fn iterative() {
+        common(super::iterative);
+    }
+
+    #[test]
+    fn recursive() {
+        common(super::recursive);
+    }
+
+    fn common(func: fn(&[i64]) -> Option<i64>) {
+        assert_eq!(func(&[]), Some(1));
+        assert_eq!(func(&[7]), Some(7));
+        assert_eq!(func(&[-7]), Some(7));
+        assert_eq!(func(&[4, 6]), Some(12));
+        assert_eq!(func(&[-4, 6]), Some(12));
+        assert_eq!(func(&[2, 3, 4, 5, 6]), Some(60));
+        assert_eq!(func(&[20, 22]), Some(220));
+        assert_eq!(func(&[5, 0, 3]), Some(0));
+        assert_eq!(func(&(1..=20).collect::<Vec<_>>()), Some(232_792_560));
+        assert_eq!(func(&[i64::MAX, 2]), None);
+        assert_eq!(func(&[1 << 62, 3]), None);
+
+        // compare against a slow but obviously correct search
+        for a in 1..40i64 {
+            for b in 1..40i64 {
+                for c in 1..8i64 {
+                    let expected = (1..).find(|m| m % a == 0 && m % b == 0 && m % c == 0);
+
+                    let real = func(&[a, b, c]);
+                    println!("lcm({}, {}, {}) = {:?} >< {:?}", a, b, c, real, expected);
+                    assert_eq!(real, expected);
+                }
+            }
+        }
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
@@ -1,7 +1,17 @@
 ////////////////////////////////////////////////////////////////////////////////
 
+pub mod calc;
+pub mod continued_fraction;
+pub mod egyptian;
+pub mod euclid;
 pub mod expo;
+pub mod farey;
+pub mod gcd;
 pub mod int_to_bin;
 pub mod lcd;
+pub mod lcm;
+pub mod primes;
+pub mod stack;
+pub mod trace;
 
 ////////////////////////////////////////////////////////////////////////////////
@@ -0,0 +1,443 @@
+////////////////////////////////////////////////////////////////////////////////
+
+use crate::{expo, gcd};
+
+////////////////////////////////////////////////////////////////////////////////
+
+/// How many numbers the segmented sieve marks off at a time.
+/// Small enough to stay in cache, large enough that the base primes are worth
+/// looping over.
+const SEGMENT: u64 = 1 << 15;
+
+/// Witnesses that are enough to make Miller-Rabin exact for every `u64`.
+const WITNESSES: [u64; 12] = [2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37];
+
+////////////////////////////////////////////////////////////////////////////////
+
+/// (a * b) mod m, without overflowing.
+fn mul_mod(a: u64, b: u64, m: u64) -> u64 {
+    (a as u128 * b as u128 % m as u128) as u64
+}
+
+/// A plain sieve of Eratosthenes over 0..limit, to get the segments started.
+fn simple(limit: u64) -> Vec<u64> {
+    let mut composite = vec![false; limit as usize];
+    let mut res = vec![];
+
+    for i in 2..limit {
+        if !composite[i as usize] {
+            res.push(i);
+            // every smaller multiple was already crossed off by a smaller prime
+            for j in (i * i..limit).step_by(i as usize) {
+                composite[j as usize] = true;
+            }
+        }
+    }
+
+    res
+}
+
+/// Crosses off the multiples of the base primes in `start..end`, returning
+/// what's left. `base` needs every prime up to sqrt(end), and `start` has to
+/// be at least 2.
+fn cross_off(start: u64, end: u64, base: &[u64]) -> Vec<u64> {
+    let mut composite = vec![false; (end - start) as usize];
+
+    for &p in base {
+        // p <= sqrt(u64::MAX), so p * p can't overflow
+        if p * p >= end {
+            break;
+        }
+        // the first multiple of p in the segment that isn't p itself
+        let first = (p * p).max(start.div_ceil(p).saturating_mul(p));
+        // stepping past the end of the range near u64::MAX just stops
+        for j in (first..end).step_by(p as usize) {
+            composite[(j - start) as usize] = true;
+        }
+    }
+
+    (start..end)
+        .zip(composite)
+        .filter(|(_, c)| !c)
+        .map(|(i, _)| i)
+        .collect()
+}
+
+/// Finds every prime below `limit`, a segment at a time, so nothing bigger
+/// than the primes themselves is ever allocated.
+fn base_primes(limit: u64) -> Vec<u64> {
+    let mut res = simple(limit.min(SEGMENT));
+
+    // each segment only needs primes below SEGMENT and those found before it
+    let mut start = SEGMENT;
+    while start < limit {
+        let end = start.saturating_add(SEGMENT).min(limit);
+        let found = cross_off(start, end, &res);
+        res.extend(found);
+        start = end;
+    }
+
+    res
+}
+
+//---------------------------------------------------------------------------//
+
+/// Finds every prime in `lo..hi` using a segmented sieve of Eratosthenes.
+/// Only the primes up to sqrt(hi) are kept around, and both they and the range
+/// are crossed off one fixed size segment at a time, so it works far from
+/// zero.
+/// Ranges too narrow to be worth finding the primes up to sqrt(hi) for are
+/// checked one number at a time with [`is_prime`] instead.
+/// (i.e. 100..130 -> [101, 103, 107, 109, 113, 127] )
+///
+/// Inputs:
+/// - `lo: u64` The start of the range, inclusive.
+/// - `hi: u64` The end of the range, exclusive.
+///
+/// Outputs: `Vec<u64>`
+/// The primes in the range, in order.
+///
+/// Time complexity: O((hi - lo) * log(log(hi)) + sqrt(hi)), or
+/// O((hi - lo) * log(hi)) for narrow ranges
+pub fn segmented(lo: u64, hi: u64) -> Vec<u64> {
+    let lo = lo.max(2);
+    if lo >= hi {
+        return vec![];
+    }
+
+    // any composite below hi has a factor of at most sqrt(hi)
+    let limit = (hi - 1).isqrt() + 1;
+
+    // Miller-Rabin costs about a thousand times as much per number as the
+    // sieve does, so below that it's cheaper than finding the base primes
+    if (hi - lo).saturating_mul(1024) < limit {
+        return (lo..hi).filter(|n| is_prime(*n)).collect();
+    }
+
+    let base = base_primes(limit);
+    let mut res = vec![];
+
+    let mut start = lo;
+    while start < hi {
+        let end = start.saturating_add(SEGMENT).min(hi);
+        res.extend(cross_off(start, end, &base));
+        start = end;
+    }
+
+    res
+}
+
+//---------------------------------------------------------------------------//
+
+/// Finds every prime below `limit`. See [`segmented`].
+/// (i.e. 20 -> [2, 3, 5, 7, 11, 13, 17, 19] )
+pub fn sieve(limit: u64) -> Vec<u64> {
+    segmented(0, limit)
+}
+
+//---------------------------------------------------------------------------//
+
+/// Checks whether `n` is prime with the Miller-Rabin test.
+/// Checking the first twelve primes as witnesses is known to be exact for all
+/// n < 3.3 * 10^24, so for a `u64` there are no false positives.
+///
+/// Inputs:
+/// - `n: u64`
+///
+/// Outputs: `bool`
+/// Whether `n` is prime.
+///
+/// Time complexity: O(log(n))
+pub fn is_prime(n: u64) -> bool {
+    if n < 2 {
+        return false;
+    }
+    for p in WITNESSES {
+        if n.is_multiple_of(p) {
+            return n == p;
+        }
+    }
+
+    // n - 1 = d * 2^s with d odd
+    let s = (n - 1).trailing_zeros();
+    let d = (n - 1) >> s;
+
+    // a prime has no square roots of 1 other than 1 and -1, so squaring a^d
+    // up to a^(n-1) has to either start at 1 or pass through -1
+    WITNESSES.iter().all(|&a| {
+        let mut x = expo::modular(a, d, n);
+        if x == 1 || x == n - 1 {
+            return true;
+        }
+        for _ in 1..s {
+            x = mul_mod(x, x, n);
+            if x == n - 1 {
+                return true;
+            }
+        }
+        false
+    })
+}
+
+//---------------------------------------------------------------------------//
+
+/// Finds a non-trivial factor of a composite `n` with Pollard's rho algorithm.
+/// Iterating x -> x^2 + c (mod n) eventually cycles, and it cycles modulo each
+/// factor of n sooner than modulo n itself, which shows up as a gcd.
+///
+/// Inputs:
+/// - `n: u64` A composite number.
+///
+/// Outputs: `u64`
+/// A factor of `n` strictly between 1 and `n`.
+///
+/// Panics if `n` is prime or less than 4, since there is no such factor.
+///
+/// Time complexity: O(n^(1/4)) expected
+pub fn pollard_rho(n: u64) -> u64 {
+    assert!(n >= 4 && !is_prime(n), "{} has no non-trivial factor", n);
+
+    if n.is_multiple_of(2) {
+        return 2;
+    }
+
+    // some constants only find n itself, so keep trying new ones
+    for c in 1.. {
+        // in u128, since x^2 mod n + c can go past u64::MAX
+        let f = |x: u64| ((mul_mod(x, x, n) as u128 + c as u128) % n as u128) as u64;
+
+        // Floyd's cycle finding, y moves twice as fast as x
+        let (mut x, mut y, mut d) = (2, 2, 1);
+        while d == 1 {
+            x = f(x);
+            y = f(f(y));
+            // widened, since a u64 doesn't fit in an i64
+            d = gcd::iterative(x.abs_diff(y) as i128, n as i128) as u64;
+        }
+
+        if d != n {
+            return d;
+        }
+    }
+
+    unreachable!()
+}
+
+//---------------------------------------------------------------------------//
+
+/// Recursively finds the prime factors of `n`, splitting it with Pollard's rho
+/// until every part is prime.
+/// (i.e. 360 -> [2, 2, 2, 3, 3, 5] )
+///
+/// Inputs:
+/// - `n: u64`
+///
+/// Outputs: `Vec<u64>`
+/// The prime factors of `n` with repeats, in order.
+/// 0 and 1 have no prime factors.
+///
+/// Time complexity: O(n^(1/4) * log(n)) expected
+pub fn factorise_recursive(n: u64) -> Vec<u64> {
+    fn inner(n: u64, res: &mut Vec<u64>) {
+        if n == 1 {
+            // terminating case, nothing left
+        } else if is_prime(n) {
+            // terminating case, can't be split any further
+            res.push(n);
+        } else {
+            // general case, split it and factorise both halves
+            let d = pollard_rho(n);
+            inner(d, res);
+            inner(n / d, res);
+        }
+    }
+
+    let mut res = vec![];
+    if n != 0 {
+        inner(n, &mut res);
+    }
+    res.sort_unstable();
+    res
+}
+
+//---------------------------------------------------------------------------//
+
+/// Iteratively finds the prime factors of `n`, splitting it with Pollard's rho
+/// until every part is prime.
+/// (i.e. 360 -> [2, 2, 2, 3, 3, 5] )
+///
+/// Inputs:
+/// - `n: u64`
+///
+/// Outputs: `Vec<u64>`
+/// The prime factors of `n` with repeats, in order.
+/// 0 and 1 have no prime factors.
+///
+/// Time complexity: O(n^(1/4) * log(n)) expected
+pub fn factorise_iterative(n: u64) -> Vec<u64> {
+    let mut res = vec![];
+
+    // the parts still waiting to be split
+    let mut parts = if n == 0 { vec![] } else { vec![n] };
+
+    while let Some(n) = parts.pop() {
+        if n == 1 {
+            continue;
+        }
+        if is_prime(n) {
+            res.push(n);
+        } else {
+            let d = pollard_rho(n);
+            parts.push(d);
+            parts.push(n / d);
+        }
+    }
+
+    res.sort_unstable();
+    res
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+
+    /// Checks primality by trial division, slowly but obviously correctly.
+    fn trial(n: u64) -> bool {
+        n >= 2
+            && (2..)
+                .take_while(|d| d * d <= n)
+                .all(|d| !n.is_multiple_of(d))
+    }
+
+    #[test]
+    fn sieve() {
+        assert_eq!(
+            super::sieve(100),
+            [
+                2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37, 41, 43, 47, 53, 59, 61, 67, 71, 73, 79,
+                83, 89, 97
+            ]
+        );
+        assert_eq!(super::sieve(0), []);
+        assert_eq!(super::sieve(2), []);
+        assert_eq!(super::sieve(3), [2]);
+        assert_eq!(super::sieve(1_000_000).len(), 78_498);
+
+        assert_eq!(super::segmented(100, 130), [101, 103, 107, 109, 113, 127]);
+        assert_eq!(super::segmented(0, 10), [2, 3, 5, 7]);
+        assert_eq!(super::segmented(24, 29), []);
+        assert_eq!(super::segmented(30, 10), []);
+
+        // far from zero, across several segments
+        let lo = 1_000_000_000;
+        let hi = lo + 100_000;
+        let expected = (lo..hi).filter(|n| super::is_prime(*n)).collect::<Vec<_>>();
+        assert_eq!(super::segmented(lo, hi), expected);
+
+        // the base primes only go up to sqrt(hi)
+        assert_eq!(
+            super::segmented(1_000_000_000_000 - 100, 1_000_000_000_000),
+            [999999999937, 999999999959, 999999999961, 999999999989]
+        );
+        assert_eq!(
+            super::segmented(1_000_000_000_000 - 100_000, 1_000_000_000_000).len(),
+            (1_000_000_000_000 - 100_000..1_000_000_000_000)
+                .filter(|n| super::is_prime(*n))
+                .count()
+        );
+        assert_eq!(super::base_primes(1 << 20).len(), 82_025);
+        assert_eq!(super::base_primes(1 << 20), super::simple(1 << 20));
+
+        // right up against u64::MAX
+        assert_eq!(
+            super::segmented(u64::MAX - 200, u64::MAX),
+            [
+                u64::MAX - 188,
+                u64::MAX - 178,
+                u64::MAX - 94,
+                u64::MAX - 82,
+                u64::MAX - 58
+            ]
+        );
+        assert_eq!(super::segmented(u64::MAX - 58, u64::MAX), [u64::MAX - 58]);
+        assert_eq!(super::segmented(u64::MAX - 57, u64::MAX), []);
+    }
+
+    #[test]
+    fn cross_off() {
+        // stepping by the base primes can't run off the end of a u64
+        let base = super::simple(1000);
+        let lo = u64::MAX - 10_000;
+        let expected = (lo..u64::MAX)
+            .filter(|n| base.iter().all(|p| n % p != 0))
+            .collect::<Vec<_>>();
+        assert_eq!(super::cross_off(lo, u64::MAX, &base), expected);
+    }
+
+    #[test]
+    fn is_prime() {
+        for n in 0..20_000 {
+            assert_eq!(super::is_prime(n), trial(n), "{}", n);
+        }
+
+        assert!(super::is_prime((1 << 61) - 1));
+        assert!(super::is_prime(18446744073709551557));
+        assert!(super::is_prime(4294967291));
+
+        // Carmichael numbers and strong pseudoprimes
+        assert!(!super::is_prime(561));
+        assert!(!super::is_prime(2047));
+        assert!(!super::is_prime(3215031751));
+        assert!(!super::is_prime(3825123056546413051));
+        assert!(!super::is_prime(u64::MAX));
+        assert!(!super::is_prime(4294967291 * 4294967279));
+    }
+
+    #[test]
+    fn pollard_rho() {
+        for n in [4, 9, 15, 25, 91, 561, 1_000_003 * 1_000_033] {
+            let d = super::pollard_rho(n);
+            assert!(1 < d && d < n && n % d == 0, "{} -> {}", n, d);
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn pollard_rho_prime() {
+        super::pollard_rho(101);
+    }
+
+    #[test]
+    fn factorise_iterative() {
+        common(super::factorise_iterative);
+    }
+
+    #[test]
+    fn factorise_recursive() {
+        common(super::factorise_recursive);
+    }
+
+    fn common(func: fn(u64) -> Vec<u64>) {
+        assert_eq!(func(360), [2, 2, 2, 3, 3, 5]);
+        assert_eq!(func(600851475143), [71, 839, 1471, 6857]);
+        assert_eq!(func(1 << 40), [2; 40]);
+        assert_eq!(func(97), [97]);
+        assert_eq!(func(1), []);
+        assert_eq!(func(0), []);
+        assert_eq!(func(u64::MAX), [3, 5, 17, 257, 641, 65537, 6700417]);
+        assert_eq!(func(3825123056546413051), [149491, 747451, 34233211]);
+        assert_eq!(func(4294967291 * 4294967279), [4294967279, 4294967291]);
+        assert_eq!(func(4294967291 * 4294967291), [4294967291, 4294967291]);
+        assert_eq!(func(u64::MAX - 1), [2, 7, 7, 73, 127, 337, 92737, 649657]);
+
+        // every factor is prime and they multiply back up
+        for n in 1..5_000u64 {
+            let factors = func(n);
+            assert!(factors.iter().all(|p| trial(*p)));
+            assert_eq!(factors.iter().product::<u64>(), n);
+        }
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
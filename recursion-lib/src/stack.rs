@@ -0,0 +1,189 @@
+////////////////////////////////////////////////////////////////////////////////
+
+/// Runs `f` on a new thread with a stack of `size` bytes, and waits for it to
+/// finish. Useful for recursion that needs more room than the main thread has.
+///
+/// Note that running out of stack aborts the whole process rather than just
+/// the thread, so probing for the limit needs each attempt to run in a process
+/// of its own. See [`probe`].
+///
+/// Inputs:
+/// - `size: usize` The size of the stack, in bytes.
+/// - `f: impl FnOnce() -> T` What to run.
+///
+/// Outputs: `std::io::Result<T>`
+/// What `f` returned, or why the thread couldn't be started.
+/// Panics in `f` are passed on.
+pub fn on_stack<T, F>(size: usize, f: F) -> std::io::Result<T>
+where
+    T: Send + 'static,
+    F: FnOnce() -> T + Send + 'static,
+{
+    let handle = std::thread::Builder::new().stack_size(size).spawn(f)?;
+    match handle.join() {
+        Ok(res) => Ok(res),
+        Err(panic) => std::panic::resume_unwind(panic),
+    }
+}
+
+//---------------------------------------------------------------------------//
+
+/// Binary searches for the largest input in `lo..=hi` that completes, assuming
+/// that if an input completes, every smaller one does too.
+/// (i.e. 0, 100, |n| n <= 42 -> Some(42) )
+///
+/// Inputs:
+/// - `lo: u64`
+/// - `hi: u64`
+/// - `completes: impl FnMut(u64) -> bool` Whether an input completes.
+///
+/// Outputs: `Option<u64>`
+/// The largest input that completes, or `None` if not even `lo` does.
+///
+/// Time complexity: O(log(hi - lo)) calls of `completes`
+pub fn largest(lo: u64, hi: u64, mut completes: impl FnMut(u64) -> bool) -> Option<u64> {
+    if lo > hi || !completes(lo) {
+        return None;
+    }
+    // often there's no limit at all, which saves the whole search
+    if completes(hi) {
+        return Some(hi);
+    }
+
+    // lo always completes and hi never does
+    let (mut lo, mut hi) = (lo, hi);
+    while hi - lo > 1 {
+        let mid = lo + (hi - lo) / 2;
+        if completes(mid) {
+            lo = mid;
+        } else {
+            hi = mid;
+        }
+    }
+    Some(lo)
+}
+
+//---------------------------------------------------------------------------//
+
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+/// How deep a recursive function got with a given stack.
+pub struct Probe {
+    /// The size of the stack, in bytes.
+    pub stack_size: usize,
+    /// The largest input that completed, if any did.
+    pub largest: Option<u64>,
+    /// How many calls deep the recursion was for `largest`.
+    pub depth: Option<u64>,
+    /// Whether the search reached the top of the range without running out,
+    /// in which case the real limit is higher, if there is one.
+    pub unbounded: bool,
+}
+
+impl Probe {
+    /// Estimates how many bytes each call takes from probes with two
+    /// different stack sizes. Whatever the thread uses before the recursion
+    /// starts is the same for both, so it cancels out.
+    ///
+    /// Outputs: `Option<f64>`
+    /// `None` if either probe didn't find a limit or the depths are the same.
+    pub fn bytes_per_frame(&self, other: &Probe) -> Option<f64> {
+        if self.unbounded || other.unbounded {
+            return None;
+        }
+        let (a, b) = (self.depth? as f64, other.depth? as f64);
+        if a == b {
+            return None;
+        }
+        Some((other.stack_size as f64 - self.stack_size as f64) / (b - a))
+    }
+}
+
+/// Finds the largest input in `lo..=hi` a recursive function can handle with a
+/// stack of `stack_size` bytes.
+///
+/// Inputs:
+/// - `stack_size: usize`
+/// - `lo: u64`
+/// - `hi: u64`
+/// - `depth: impl Fn(u64) -> u64` How many calls deep an input recurses.
+/// - `completes: impl FnMut(usize, u64) -> bool` Whether an input completes
+///   with a given stack size. Since running out of stack aborts the process,
+///   this will normally run each attempt in a new process, using [`on_stack`].
+///
+/// Outputs: `Probe`
+///
+/// Time complexity: O(log(hi - lo)) calls of `completes`
+pub fn probe(
+    stack_size: usize,
+    lo: u64,
+    hi: u64,
+    depth: impl Fn(u64) -> u64,
+    mut completes: impl FnMut(usize, u64) -> bool,
+) -> Probe {
+    let largest = largest(lo, hi, |n| completes(stack_size, n));
+    Probe {
+        stack_size,
+        largest,
+        depth: largest.map(depth),
+        unbounded: largest == Some(hi),
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use crate::expo;
+
+    #[test]
+    fn on_stack() {
+        // far deeper than the test thread's own stack allows
+        let res = super::on_stack(256 * 1024 * 1024, || expo::recursive(1.0, 200_000));
+        assert_eq!(res.unwrap(), 1.0);
+
+        let res = std::panic::catch_unwind(|| super::on_stack(1024 * 1024, || panic!("boom")));
+        assert!(res.is_err());
+    }
+
+    #[test]
+    fn largest() {
+        for limit in [0, 1, 2, 42, 99, 100] {
+            assert_eq!(super::largest(0, 100, |n| n <= limit), Some(limit));
+        }
+        assert_eq!(super::largest(5, 100, |n| n <= 4), None);
+        assert_eq!(super::largest(5, 4, |_| true), None);
+        assert_eq!(super::largest(0, u64::MAX, |n| n <= 1 << 40), Some(1 << 40));
+
+        // no more attempts than the search needs
+        let mut attempts = 0;
+        super::largest(0, 1 << 20, |n| {
+            attempts += 1;
+            n < 1000
+        });
+        assert_eq!(attempts, 22);
+    }
+
+    #[test]
+    fn probe() {
+        // pretend every call takes 100 bytes after 1000 bytes of setup
+        let completes = |stack: usize, n: u64| 1000 + 100 * (n + 1) <= stack as u64;
+        let small = super::probe(10_000, 0, 1_000_000, |n| n + 1, completes);
+        let large = super::probe(20_000, 0, 1_000_000, |n| n + 1, completes);
+
+        assert_eq!(small.largest, Some(89));
+        assert_eq!(small.depth, Some(90));
+        assert!(!small.unbounded);
+        assert_eq!(large.largest, Some(189));
+        assert_eq!(small.bytes_per_frame(&large), Some(100.0));
+
+        let unbounded = super::probe(10_000, 0, 10, |n| n + 1, completes);
+        assert!(unbounded.unbounded);
+        assert_eq!(unbounded.bytes_per_frame(&large), None);
+
+        let none = super::probe(10, 0, 10, |n| n + 1, completes);
+        assert_eq!(none.largest, None);
+        assert_eq!(none.bytes_per_frame(&large), None);
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
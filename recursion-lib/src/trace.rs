@@ -0,0 +1,344 @@
+////////////////////////////////////////////////////////////////////////////////
+
+use std::fmt::Write;
+
+////////////////////////////////////////////////////////////////////////////////
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+/// One call of a recursive function, along with every call it made.
+/// Built by the `recursive_traced` functions.
+pub struct Call {
+    /// The function and its arguments. (i.e. `recursive(2.5, 3)` )
+    pub args: String,
+    /// What it returned, or what it did if it returns nothing.
+    pub result: String,
+    /// The calls it made, in order.
+    pub children: Vec<Call>,
+    /// How many calls were made below this one without being recorded, for
+    /// being past the depth limit.
+    pub hidden: usize,
+}
+
+impl Call {
+    pub fn new(args: String, result: String, children: Vec<Call>) -> Self {
+        Call {
+            args,
+            result,
+            children,
+            hidden: 0,
+        }
+    }
+
+    /// How many calls there are in total, including this one and any that
+    /// weren't recorded.
+    pub fn size(&self) -> usize {
+        let mut res = 0;
+        let mut todo = vec![self];
+        while let Some(call) = todo.pop() {
+            res += 1 + call.hidden;
+            todo.extend(&call.children);
+        }
+        res
+    }
+
+    /// How many recorded calls deep the tree goes, counting this one.
+    pub fn depth(&self) -> usize {
+        let mut res = 0;
+        let mut todo = vec![(self, 1)];
+        while let Some((call, depth)) = todo.pop() {
+            res = res.max(depth);
+            todo.extend(call.children.iter().map(|c| (c, depth + 1)));
+        }
+        res
+    }
+
+    /// Walks the tree in order, stopping `max_depth` calls deep and counting
+    /// the calls left out instead.
+    /// Done with a stack rather than recursion, so very deep trees are fine.
+    fn walk(&self, max_depth: usize, visit: &mut impl FnMut(Visit)) {
+        let mut todo = vec![Visit::Call(self, 0)];
+        while let Some(next) = todo.pop() {
+            visit(next);
+
+            let Visit::Call(call, depth) = next else {
+                continue;
+            };
+            if depth + 1 >= max_depth {
+                let hidden = call.size() - 1;
+                if hidden > 0 {
+                    todo.push(Visit::Hidden(hidden, depth + 1));
+                }
+            } else {
+                // pushed backwards so the first child comes out first, and
+                // anything that wasn't recorded comes out last
+                if call.hidden > 0 {
+                    todo.push(Visit::Hidden(call.hidden, depth + 1));
+                }
+                todo.extend(
+                    call.children
+                        .iter()
+                        .rev()
+                        .map(|c| Visit::Call(c, depth + 1)),
+                );
+            }
+        }
+    }
+
+    /// Writes the tree with each call indented under its caller.
+    /// (i.e.
+    /// ```text
+    /// recursive(2, 1) = 2
+    ///   recursive(2, 0) = 1
+    /// ```
+    /// )
+    ///
+    /// Inputs:
+    /// - `max_depth: usize` How many levels of calls to show, at least 1.
+    ///
+    /// Outputs: `String`
+    ///
+    /// Time complexity: O(n)
+    pub fn to_text(&self, max_depth: usize) -> String {
+        let mut res = String::new();
+        self.walk(max_depth.max(1), &mut |visit| {
+            let _ = match visit {
+                Visit::Call(call, depth) => writeln!(
+                    res,
+                    "{:indent$}{} = {}",
+                    "",
+                    call.args,
+                    call.result,
+                    indent = depth * 2
+                ),
+                Visit::Hidden(n, depth) => {
+                    writeln!(
+                        res,
+                        "{:indent$}... {} more calls",
+                        "",
+                        n,
+                        indent = depth * 2
+                    )
+                }
+            };
+        });
+        res
+    }
+
+    /// Writes the tree as a Graphviz DOT graph, with an edge from each call to
+    /// the calls it made.
+    ///
+    /// Inputs:
+    /// - `max_depth: usize` How many levels of calls to show, at least 1.
+    ///
+    /// Outputs: `String`
+    ///
+    /// Time complexity: O(n)
+    pub fn to_dot(&self, max_depth: usize) -> String {
+        let mut res = String::from("digraph calls {\n    node [shape=box];\n");
+
+        // the id of the most recent call at each depth, which is the parent
+        // of anything one level deeper
+        let mut parents: Vec<usize> = vec![];
+        let mut id = 0;
+
+        self.walk(max_depth.max(1), &mut |visit| {
+            let (label, depth, style) = match visit {
+                Visit::Call(call, depth) => {
+                    (format!("{}\n= {}", call.args, call.result), depth, "")
+                }
+                Visit::Hidden(n, depth) => {
+                    (format!("... {} more calls", n), depth, ", style=dashed")
+                }
+            };
+
+            let _ = writeln!(res, "    n{} [label=\"{}\"{}];", id, escape(&label), style);
+            if depth > 0 {
+                let _ = writeln!(res, "    n{} -> n{};", parents[depth - 1], id);
+            }
+
+            parents.truncate(depth);
+            parents.push(id);
+            id += 1;
+        });
+
+        res.push_str("}\n");
+        res
+    }
+}
+
+impl Drop for Call {
+    /// Frees the calls with a loop, since letting each call drop its children
+    /// would recurse once per call.
+    fn drop(&mut self) {
+        let mut todo = std::mem::take(&mut self.children);
+        while let Some(mut call) = todo.pop() {
+            todo.append(&mut call.children);
+        }
+    }
+}
+
+//---------------------------------------------------------------------------//
+
+/// What a traced call hands back to its caller. Either the call itself, or,
+/// once past the depth limit, just how many calls were made.
+pub(crate) enum Traced {
+    Call(Call),
+    Hidden(usize),
+}
+
+impl Traced {
+    /// Records a call, `max_depth` levels above the depth limit, from what the
+    /// calls it made handed back. At the limit (`max_depth == 0`) the call is
+    /// only counted, so `args` and `result` are never even written.
+    pub(crate) fn record(
+        max_depth: usize,
+        args: impl FnOnce() -> String,
+        result: impl FnOnce() -> String,
+        children: Vec<Traced>,
+    ) -> Traced {
+        let mut recorded = vec![];
+        let mut hidden = 0;
+        for child in children {
+            match child {
+                Traced::Call(call) => recorded.push(call),
+                Traced::Hidden(n) => hidden += n,
+            }
+        }
+
+        if max_depth == 0 {
+            Traced::Hidden(1 + hidden + recorded.iter().map(Call::size).sum::<usize>())
+        } else {
+            let mut call = Call::new(args(), result(), recorded);
+            call.hidden = hidden;
+            Traced::Call(call)
+        }
+    }
+
+    /// The outermost call of a trace, which is always recorded as long as
+    /// it was given a `max_depth` of at least 1.
+    pub(crate) fn into_call(self) -> Call {
+        match self {
+            Traced::Call(call) => call,
+            Traced::Hidden(_) => unreachable!("the outermost call is always recorded"),
+        }
+    }
+}
+
+//---------------------------------------------------------------------------//
+
+#[derive(Clone, Copy)]
+/// What `Call::walk` comes across, and how deep.
+enum Visit<'a> {
+    Call(&'a Call, usize),
+    /// Calls left out for being too deep.
+    Hidden(usize, usize),
+}
+
+/// Escapes a DOT label.
+fn escape(s: &str) -> String {
+    s.replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use super::Call;
+
+    /// A chain of calls, like linear recursion makes.
+    fn chain(n: usize) -> Call {
+        let mut call = Call::new("f(0)".into(), "0".into(), vec![]);
+        for i in 1..n {
+            call = Call::new(format!("f({})", i), i.to_string(), vec![call]);
+        }
+        call
+    }
+
+    fn tree() -> Call {
+        let leaf = |s: &str| Call::new(s.into(), "1".into(), vec![]);
+        Call::new(
+            "fib(3)".into(),
+            "2".into(),
+            vec![
+                Call::new(
+                    "fib(2)".into(),
+                    "1".into(),
+                    vec![leaf("fib(1)"), leaf("fib(0)")],
+                ),
+                leaf("fib(1)"),
+            ],
+        )
+    }
+
+    #[test]
+    fn shape() {
+        assert_eq!(chain(5).size(), 5);
+        assert_eq!(chain(5).depth(), 5);
+        assert_eq!(tree().size(), 5);
+        assert_eq!(tree().depth(), 3);
+
+        // deep enough that recursion would be a problem
+        let deep = chain(200_000);
+        assert_eq!(deep.depth(), 200_000);
+        assert_eq!(deep.to_text(3).lines().count(), 4);
+        // and dropping it doesn't recurse either
+        drop(deep);
+
+        // calls that weren't recorded still count
+        let mut cut = chain(3);
+        cut.children[0].children[0].hidden = 4;
+        assert_eq!(cut.size(), 7);
+        assert_eq!(cut.depth(), 3);
+    }
+
+    #[test]
+    fn to_text() {
+        assert_eq!(
+            tree().to_text(usize::MAX),
+            "fib(3) = 2\n  fib(2) = 1\n    fib(1) = 1\n    fib(0) = 1\n  fib(1) = 1\n"
+        );
+        assert_eq!(
+            tree().to_text(2),
+            "fib(3) = 2\n  fib(2) = 1\n    ... 2 more calls\n  fib(1) = 1\n"
+        );
+        assert_eq!(tree().to_text(0), "fib(3) = 2\n  ... 4 more calls\n");
+
+        let mut cut = chain(2);
+        cut.children[0].hidden = 4;
+        assert_eq!(
+            cut.to_text(usize::MAX),
+            "f(1) = 1\n  f(0) = 0\n    ... 4 more calls\n"
+        );
+        assert_eq!(cut.to_text(1), "f(1) = 1\n  ... 5 more calls\n");
+    }
+
+    #[test]
+    fn to_dot() {
+        assert_eq!(
+            tree().to_dot(usize::MAX),
+            "digraph calls {\n    node [shape=box];\n\
+             \x20   n0 [label=\"fib(3)\\n= 2\"];\n\
+             \x20   n1 [label=\"fib(2)\\n= 1\"];\n\
+             \x20   n0 -> n1;\n\
+             \x20   n2 [label=\"fib(1)\\n= 1\"];\n\
+             \x20   n1 -> n2;\n\
+             \x20   n3 [label=\"fib(0)\\n= 1\"];\n\
+             \x20   n1 -> n3;\n\
+             \x20   n4 [label=\"fib(1)\\n= 1\"];\n\
+             \x20   n0 -> n4;\n\
+             }\n"
+        );
+
+        let dot = chain(10).to_dot(3);
+        assert!(dot.contains("n3 [label=\"... 7 more calls\", style=dashed];\n    n2 -> n3;"));
+
+        let quoted = Call::new("say(\"hi\\\")".into(), "".into(), vec![]);
+        assert!(quoted
+            .to_dot(1)
+            .contains("label=\"say(\\\"hi\\\\\\\")\\n= \""));
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
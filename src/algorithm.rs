@@ -0,0 +1,341 @@
+////////////////////////////////////////////////////////////////////////////////
+
+use anyhow::{bail, Result};
+use recursion_lib::lcd::Expr;
+use recursion_lib::{expo, int_to_bin, lcd};
+use std::time::{Duration, Instant};
+
+////////////////////////////////////////////////////////////////////////////////
+
+/// The largest exponent `expo::recursive` is given.
+/// It recurses once per step and isn't tail recursive, so anything much bigger
+/// risks overflowing the stack.
+pub const MAX_RECURSIVE_EXPONENT: i32 = 10_000;
+
+//---------------------------------------------------------------------------//
+
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+/// One of the two ways every algorithm is written.
+pub enum Implementation {
+    Recursive,
+    Iterative,
+}
+
+impl Implementation {
+    pub fn name(self) -> &'static str {
+        match self {
+            Implementation::Recursive => "recursive",
+            Implementation::Iterative => "iterative",
+        }
+    }
+}
+
+//---------------------------------------------------------------------------//
+
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+/// Which implementations to run.
+pub enum Method {
+    Recursive,
+    Iterative,
+    Both,
+}
+
+impl Method {
+    /// What each method is called on the command line.
+    pub const NAMES: [&'static str; 3] = ["recursive", "iterative", "both"];
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "recursive" => Some(Method::Recursive),
+            "iterative" => Some(Method::Iterative),
+            "both" => Some(Method::Both),
+            _ => None,
+        }
+    }
+
+    pub fn implementations(self) -> &'static [Implementation] {
+        match self {
+            Method::Recursive => &[Implementation::Recursive],
+            Method::Iterative => &[Implementation::Iterative],
+            Method::Both => &[Implementation::Recursive, Implementation::Iterative],
+        }
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+#[derive(Debug, PartialEq, Copy, Clone)]
+/// The input to one of the algorithms.
+pub enum Input {
+    /// x raised to the power of e, see `expo`.
+    Expo { x: f64, e: i32 },
+    /// x in binary, see `int_to_bin`.
+    Bin(i64),
+    /// A fraction to simplify, see `lcd`.
+    Lcd(Expr),
+}
+
+impl Input {
+    /// The name of the algorithm, as used for its subcommand.
+    pub fn algorithm(&self) -> &'static str {
+        match self {
+            Input::Expo { .. } => "expo",
+            Input::Bin(_) => "bin",
+            Input::Lcd(_) => "lcd",
+        }
+    }
+
+    /// The arguments to the subcommand, separated by spaces.
+    /// (i.e. `2.5 -3` )
+    pub fn arguments(&self) -> String {
+        match self {
+            Input::Expo { x, e } => format!("{} {}", x, e),
+            Input::Bin(x) => x.to_string(),
+            Input::Lcd(e) => e.to_string(),
+        }
+    }
+
+    /// Checks the input is one every implementation in `method` can handle,
+    /// rather than panicking or overflowing the stack part way through.
+    pub fn validate(&self, method: Method) -> Result<()> {
+        match *self {
+            Input::Expo { e, .. } => {
+                if e == i32::MIN {
+                    bail!("exponent must be greater than {}", i32::MIN);
+                }
+                if method
+                    .implementations()
+                    .contains(&Implementation::Recursive)
+                    && e.unsigned_abs() > MAX_RECURSIVE_EXPONENT as u32
+                {
+                    bail!(
+                        "exponent {} is too large for the recursive implementation, \
+                         the limit is {}",
+                        e,
+                        MAX_RECURSIVE_EXPONENT
+                    );
+                }
+            }
+            Input::Bin(x) => {
+                if x == i64::MIN {
+                    bail!("integer must be greater than {}", i64::MIN);
+                }
+            }
+            Input::Lcd(e) => {
+                if e.d == 0 {
+                    bail!("denominator must not be zero");
+                }
+                // the simplified form, or a step on the way there, might not fit
+                for imp in method.implementations() {
+                    match imp {
+                        Implementation::Recursive => lcd::try_recursive(e)?,
+                        Implementation::Iterative => lcd::try_iterative(e)?,
+                    };
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Runs one implementation on the input.
+    /// The input should already have been validated.
+    pub fn run(&self, imp: Implementation) -> Output {
+        match (*self, imp) {
+            (Input::Expo { x, e }, Implementation::Recursive) => {
+                Output::Float(expo::recursive(x, e))
+            }
+            (Input::Expo { x, e }, Implementation::Iterative) => {
+                Output::Float(expo::iterative(x, e))
+            }
+            (Input::Bin(x), Implementation::Recursive) => Output::Bits(int_to_bin::recursive(x)),
+            (Input::Bin(x), Implementation::Iterative) => Output::Bits(int_to_bin::iterative(x)),
+            (Input::Lcd(e), Implementation::Recursive) => Output::Expr(lcd::recursive(e)),
+            (Input::Lcd(e), Implementation::Iterative) => Output::Expr(lcd::iterative(e)),
+        }
+    }
+}
+
+impl std::fmt::Display for Input {
+    /// Writes the input back out as a command.
+    /// (i.e. `expo 2.5 -3` )
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_fmt(format_args!("{} {}", self.algorithm(), self.arguments()))
+    }
+}
+
+//---------------------------------------------------------------------------//
+
+#[derive(Debug, Copy, Clone)]
+/// The result of one of the algorithms.
+pub enum Output {
+    Float(f64),
+    Bits([bool; 64]),
+    Expr(Expr),
+}
+
+impl Output {
+    /// Whether two results are exactly the same.
+    /// Floats are compared bit for bit and fractions by representation rather
+    /// than value, so the implementations have to agree on the form too.
+    pub fn same(&self, other: &Output) -> bool {
+        match (self, other) {
+            (Output::Float(a), Output::Float(b)) => a.to_bits() == b.to_bits(),
+            (Output::Bits(a), Output::Bits(b)) => a == b,
+            (Output::Expr(a), Output::Expr(b)) => (a.c, a.n, a.d) == (b.c, b.n, b.d),
+            _ => false,
+        }
+    }
+    /// The result written so it can be passed back in as an argument.
+    /// The same as displaying it, except bits are turned back into an integer.
+    /// (i.e. `-1011` -> `-11` )
+    pub fn argument(&self) -> String {
+        match self {
+            Output::Bits(bits) => {
+                let x = (0..63).filter(|i| bits[*i]).fold(0i64, |x, i| x | 1 << i);
+                if bits[63] { -x } else { x }.to_string()
+            }
+            _ => self.to_string(),
+        }
+    }
+}
+
+impl std::fmt::Display for Output {
+    /// Bits are written most significant first, with a `-` for the sign bit
+    /// and without leading zeros.
+    /// (i.e. -11 -> `-1011` )
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Output::Float(x) => f.write_fmt(format_args!("{}", x)),
+            Output::Bits(bits) => {
+                let digits = bits[..63]
+                    .iter()
+                    .rev()
+                    .skip_while(|b| !**b)
+                    .map(|b| if *b { '1' } else { '0' })
+                    .collect::<String>();
+                let sign = if bits[63] { "-" } else { "" };
+                let digits = if digits.is_empty() { "0" } else { &digits };
+                f.write_fmt(format_args!("{}{}", sign, digits))
+            }
+            Output::Expr(e) => f.write_fmt(format_args!("{}", e)),
+        }
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+#[derive(Debug, Copy, Clone)]
+/// What one implementation gave, and how long it took.
+pub struct Outcome {
+    pub implementation: Implementation,
+    pub output: Output,
+    pub elapsed: Duration,
+}
+
+#[derive(Debug, Clone)]
+/// The results of running some implementations on an input.
+pub struct Report {
+    pub input: Input,
+    pub results: Vec<Outcome>,
+}
+
+impl Report {
+    /// Whether every implementation gave exactly the same result.
+    pub fn agree(&self) -> bool {
+        self.results
+            .windows(2)
+            .all(|w| w[0].output.same(&w[1].output))
+    }
+}
+
+//---------------------------------------------------------------------------//
+
+/// Validates the input, then runs and times each implementation in `method`
+/// on it.
+pub fn run(input: Input, method: Method) -> Result<Report> {
+    input.validate(method)?;
+
+    let results = method
+        .implementations()
+        .iter()
+        .map(|imp| {
+            let start = Instant::now();
+            let output = input.run(*imp);
+            Outcome {
+                implementation: *imp,
+                output,
+                elapsed: start.elapsed(),
+            }
+        })
+        .collect();
+
+    Ok(Report { input, results })
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use super::{Implementation, Input, Method, Output};
+    use recursion_lib::lcd::Expr;
+
+    #[test]
+    fn run() {
+        let report = super::run(Input::Expo { x: 2.5, e: -3 }, Method::Both).unwrap();
+        assert!(report.agree());
+        assert_eq!(report.results.len(), 2);
+        assert_eq!(report.results[0].output.to_string(), "0.064");
+
+        let report = super::run(Input::Bin(-11), Method::Iterative).unwrap();
+        assert_eq!(report.results.len(), 1);
+        assert_eq!(report.results[0].implementation, Implementation::Iterative);
+        assert_eq!(report.results[0].output.to_string(), "-1011");
+
+        let report = super::run(Input::Lcd(Expr::new(0, 21, 45)), Method::Both).unwrap();
+        assert_eq!(report.input.to_string(), "lcd 0(21/45)");
+        assert!(report.agree());
+        assert_eq!(report.results[1].output.to_string(), "0(7/15)");
+
+        assert!(super::run(Input::Bin(i64::MIN), Method::Both).is_err());
+        assert!(super::run(Input::Lcd(Expr::new(0, 1, 0)), Method::Both).is_err());
+
+        // simplifying would overflow, rather than panic
+        for e in [Expr::new(i64::MAX, 3, 2), Expr::new(0, i64::MIN, -1)] {
+            for method in [Method::Recursive, Method::Iterative, Method::Both] {
+                let err = super::run(Input::Lcd(e), method).unwrap_err();
+                assert_eq!(err.to_string(), "value does not fit in the integer type");
+            }
+        }
+        assert!(super::run(
+            Input::Expo {
+                x: 1.0,
+                e: i32::MIN
+            },
+            Method::Iterative
+        )
+        .is_err());
+        assert!(super::run(Input::Expo { x: 1.0, e: 1 << 20 }, Method::Both).is_err());
+        assert!(super::run(Input::Expo { x: 1.0, e: 1 << 20 }, Method::Iterative).is_ok());
+    }
+
+    #[test]
+    fn output() {
+        assert_eq!(Output::Bits([false; 64]).to_string(), "0");
+        for x in [0, 1, -11, i64::MAX, -i64::MAX] {
+            let bits = Output::Bits(recursion_lib::int_to_bin::iterative(x));
+            assert_eq!(bits.argument(), x.to_string());
+        }
+        assert_eq!(Output::Expr(Expr::new(-2, 1, 2)).argument(), "-2(1/2)");
+        assert_eq!(
+            Output::Bits(recursion_lib::int_to_bin::iterative(8)).to_string(),
+            "1000"
+        );
+        assert!(Output::Float(f64::NAN).same(&Output::Float(f64::NAN)));
+        assert!(!Output::Float(0.0).same(&Output::Float(-0.0)));
+        assert!(!Output::Expr(Expr::new(0, 1, 2)).same(&Output::Expr(Expr::new(0, 2, 4))));
+        assert_eq!(Method::from_name("both"), Some(Method::Both));
+        assert_eq!(Method::from_name("neither"), None);
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
@@ -0,0 +1,274 @@
+////////////////////////////////////////////////////////////////////////////////
+
+use crate::algorithm::{self, Method, Report};
+use crate::format::{Printer, Source};
+use anyhow::{anyhow, bail, Context, Result};
+use std::io::{BufRead, Write};
+
+////////////////////////////////////////////////////////////////////////////////
+
+/// Runs a single line like `lcd 20/22`, with the same subcommands and
+/// validation as the command line.
+/// A panic part way through only fails its own line, rather than taking the
+/// whole batch down with it.
+fn job(command: &str, method: Method) -> Result<Report> {
+    let words = command.split_whitespace();
+    let matches = crate::cli()
+        .try_get_matches_from(std::iter::once("recursion-app").chain(words))
+        .map_err(|e| {
+            // just the first line, without the usage and help hints
+            let message = e.to_string();
+            let first = message.lines().next().unwrap_or_default();
+            anyhow!("{}", first.trim_start_matches("error: "))
+        })?;
+    let (name, sub) = matches.subcommand().context("missing command")?;
+    if sub.get_flag("explain") {
+        bail!("--explain can't be used in a batch");
+    }
+
+    let input = crate::input(name, sub)?;
+    let method = crate::method_or(sub, method)?;
+    std::panic::catch_unwind(|| algorithm::run(input, method))
+        .unwrap_or_else(|_| Err(anyhow!("{} panicked", input)))
+}
+
+//---------------------------------------------------------------------------//
+
+/// Runs one job per line of `input`, writing each result or error out in
+/// order. Blank lines and lines starting with `#` are skipped, but still
+/// counted so errors point at the right line.
+///
+/// Inputs:
+/// - `input: impl BufRead`
+/// - `method: Method` Which implementations to run, unless a line picks.
+/// - `threads: usize` How many threads to split the lines between.
+/// - `printer: Printer`
+/// - `out: impl Write`
+///
+/// Outputs: `Result<usize>`
+/// How many lines failed, or why the input couldn't be read.
+pub fn run(
+    input: impl BufRead,
+    method: Method,
+    threads: usize,
+    mut printer: Printer,
+    mut out: impl Write,
+) -> Result<usize> {
+    if threads == 0 {
+        bail!("need at least one thread");
+    }
+
+    let mut lines = vec![];
+    for (i, line) in input.lines().enumerate() {
+        let line = line.with_context(|| format!("couldn't read line {}", i + 1))?;
+        let command = line.trim();
+        if !command.is_empty() && !command.starts_with('#') {
+            lines.push((i + 1, command.to_string()));
+        }
+    }
+
+    // split the lines into one contiguous chunk per thread, so putting the
+    // results back together keeps them in order
+    let chunk = lines.len().div_ceil(threads).max(1);
+    let results = std::thread::scope(|s| {
+        let workers = lines
+            .chunks(chunk)
+            .map(|chunk| {
+                s.spawn(move || {
+                    chunk
+                        .iter()
+                        .map(|(_, command)| job(command, method))
+                        .collect::<Vec<_>>()
+                })
+            })
+            .collect::<Vec<_>>();
+
+        workers
+            .into_iter()
+            .flat_map(|w| w.join().expect("jobs catch their own panics"))
+            .collect::<Vec<_>>()
+    });
+
+    let mut failed = 0;
+    for ((line, command), result) in lines.iter().zip(&results) {
+        let source = Source {
+            line: *line,
+            command,
+        };
+        match result {
+            Ok(report) => {
+                if !report.agree() {
+                    failed += 1;
+                }
+                printer.line(source, Ok(report), &mut out)?;
+            }
+            Err(e) => {
+                failed += 1;
+                printer.line(source, Err(&format!("{:#}", e)), &mut out)?;
+            }
+        }
+    }
+
+    Ok(failed)
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use crate::algorithm::Method;
+    use crate::format::{Format, Printer};
+    use serde_json::Value;
+
+    const SCRIPT: &str = "\
+# a comment
+lcd 20/22
+expo 2 -2
+
+bin seven
+bin -11 --method iterative
+lcd 1/0
+gcd 4 6
+";
+
+    fn batch(format: Format, threads: usize) -> (usize, String) {
+        let mut out = vec![];
+        let failed = super::run(
+            SCRIPT.as_bytes(),
+            Method::Both,
+            threads,
+            Printer::new(format),
+            &mut out,
+        )
+        .unwrap();
+        (failed, String::from_utf8(out).unwrap())
+    }
+
+    #[test]
+    fn text() {
+        let (failed, out) = batch(Format::Text, 1);
+        assert_eq!(failed, 3);
+        assert_eq!(
+            out,
+            "\
+2: lcd 20/22
+  recursive: 0(10/11)
+  iterative: 0(10/11)
+3: expo 2 -2
+  recursive: 0.25
+  iterative: 0.25
+5: bin seven
+  error: invalid value 'seven' for '<x>': invalid digit found in string
+6: bin -11 --method iterative
+  iterative: -1011
+7: lcd 1/0
+  error: invalid value '1/0' for '<expr>': zero denominator at position 2
+8: gcd 4 6
+  error: unrecognized subcommand 'gcd'
+"
+        );
+
+        let mut out = vec![];
+        let printer = Printer::new(Format::Text);
+        let failed = super::run(
+            "bin 3 --explain".as_bytes(),
+            Method::Both,
+            1,
+            printer,
+            &mut out,
+        );
+        assert_eq!(failed.unwrap(), 1);
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "1: bin 3 --explain\n  error: --explain can't be used in a batch\n"
+        );
+    }
+
+    #[test]
+    fn overflow() {
+        // fails its own line, the rest of the batch carries on
+        let script = "lcd 9223372036854775807(3/2)\nbin 3\n";
+        let mut out = vec![];
+        let failed = super::run(
+            script.as_bytes(),
+            Method::Both,
+            2,
+            Printer::new(Format::Text),
+            &mut out,
+        );
+        assert_eq!(failed.unwrap(), 1);
+        let out = String::from_utf8(out).unwrap();
+        assert!(out.contains("1: lcd 9223372036854775807(3/2)\n  error: invalid value"));
+        assert!(out.ends_with("2: bin 3\n  recursive: 11\n  iterative: 11\n"));
+    }
+
+    #[test]
+    fn json() {
+        let (failed, out) = batch(Format::Json, 1);
+        assert_eq!(failed, 3);
+
+        let lines = out
+            .lines()
+            .map(|line| serde_json::from_str::<Value>(line).unwrap())
+            .collect::<Vec<_>>();
+        let numbers = lines.iter().map(|l| l["line"].as_u64().unwrap());
+        assert_eq!(numbers.collect::<Vec<_>>(), [2, 3, 5, 6, 7, 8]);
+
+        assert_eq!(lines[0]["command"], "lcd 20/22");
+        assert_eq!(lines[0]["results"][1]["output"], "0(10/11)");
+        assert!(lines[0].get("error").is_none());
+        assert_eq!(lines[4]["command"], "lcd 1/0");
+        assert_eq!(
+            lines[4]["error"],
+            "invalid value '1/0' for '<expr>': zero denominator at position 2"
+        );
+        assert!(lines[4].get("results").is_none());
+    }
+
+    #[test]
+    fn csv() {
+        let (_, out) = batch(Format::Csv, 1);
+        let rows = out
+            .lines()
+            .map(|line| line.split(',').collect::<Vec<_>>())
+            .collect::<Vec<_>>();
+
+        assert_eq!(rows[0], crate::format::CSV_HEADER);
+        assert_eq!(rows[1][..4], ["lcd", "0(10/11)", "recursive", "0(10/11)"]);
+        assert_eq!(rows[1][6..], ["2", ""]);
+        let error = rows.iter().find(|row| row[6] == "7").unwrap();
+        assert_eq!(error[1], "lcd 1/0");
+        assert_eq!(
+            error[7],
+            "invalid value '1/0' for '<expr>': zero denominator at position 2"
+        );
+    }
+
+    #[test]
+    fn threads() {
+        // the same results in the same order, apart from the timings
+        let without_timing = |out: String| {
+            out.lines()
+                .map(|line| {
+                    let mut value = serde_json::from_str::<Value>(line).unwrap();
+                    if let Some(results) = value["results"].as_array_mut() {
+                        results.iter_mut().for_each(|r| r["nanos"] = 0.into());
+                    }
+                    value
+                })
+                .collect::<Vec<_>>()
+        };
+        let (_, one) = batch(Format::Json, 1);
+        for threads in [2, 3, 8, 100] {
+            let (failed, many) = batch(Format::Json, threads);
+            assert_eq!(failed, 3);
+            assert_eq!(without_timing(many), without_timing(one.clone()));
+        }
+
+        let mut out = vec![];
+        let printer = Printer::new(Format::Text);
+        assert!(super::run(SCRIPT.as_bytes(), Method::Both, 0, printer, &mut out).is_err());
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
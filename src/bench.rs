@@ -0,0 +1,398 @@
+////////////////////////////////////////////////////////////////////////////////
+
+use crate::algorithm::{Implementation, Input, Method};
+use crate::format::{csv_field, Format, SCHEMA_VERSION};
+use anyhow::{bail, Context, Result};
+use serde_json::json;
+use std::hint::black_box;
+use std::io::{self, Write};
+use std::time::{Duration, Instant};
+
+////////////////////////////////////////////////////////////////////////////////
+
+/// The shortest a sample can take, so the timer's own overhead is small next
+/// to what's being timed. Fast calls are repeated until they take this long.
+const MIN_SAMPLE: Duration = Duration::from_micros(200);
+
+/// The most calls a single sample is allowed to repeat.
+const MAX_ITERATIONS: u64 = 1 << 30;
+
+//---------------------------------------------------------------------------//
+
+#[derive(Debug, Copy, Clone)]
+/// How much timing to do for each implementation.
+pub struct Settings {
+    /// Samples taken and thrown away first, to warm up caches and the CPU.
+    pub warmup: usize,
+    /// Samples kept for the statistics.
+    pub samples: usize,
+}
+
+#[derive(Debug, Clone)]
+/// The timing of one implementation on one input.
+pub struct Timing {
+    pub implementation: Implementation,
+    /// The median time per call, in nanoseconds.
+    pub median: f64,
+    /// The median absolute deviation from `median`, in nanoseconds.
+    pub mad: f64,
+    pub samples: usize,
+    /// How many calls each sample timed.
+    pub iterations: u64,
+}
+
+#[derive(Debug, Clone)]
+/// Every implementation's timing on one input.
+pub struct Bench {
+    pub input: Input,
+    pub timings: Vec<Timing>,
+}
+
+impl Bench {
+    /// How many times longer the recursive implementation took than the
+    /// iterative one, if both were timed.
+    pub fn ratio(&self) -> Option<f64> {
+        let median = |imp| {
+            self.timings
+                .iter()
+                .find(|t| t.implementation == imp)
+                .map(|t| t.median)
+        };
+        Some(median(Implementation::Recursive)? / median(Implementation::Iterative)?)
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+/// Reads one input for an algorithm, written the way `bench --inputs` takes
+/// them. (i.e. `2.5^-3` for expo, `-11` for bin and `21/45` for lcd )
+pub fn input(algorithm: &str, s: &str) -> Result<Input> {
+    Ok(match algorithm {
+        "expo" => {
+            let (x, e) = s
+                .split_once('^')
+                .with_context(|| format!("expected x^e, like 2.5^-3, not {:?}", s))?;
+            Input::Expo {
+                x: x.parse().with_context(|| format!("invalid base {:?}", x))?,
+                e: e.parse()
+                    .with_context(|| format!("invalid exponent {:?}", e))?,
+            }
+        }
+        "bin" => Input::Bin(
+            s.parse()
+                .with_context(|| format!("invalid integer {:?}", s))?,
+        ),
+        "lcd" => Input::Lcd(
+            s.parse()
+                .with_context(|| format!("invalid fraction {:?}", s))?,
+        ),
+        _ => bail!("{:?} is not an algorithm", algorithm),
+    })
+}
+
+/// The middle of the values, sorting them in the process.
+pub fn median(xs: &mut [f64]) -> f64 {
+    xs.sort_by(f64::total_cmp);
+    let mid = xs.len() / 2;
+    if xs.is_empty() {
+        f64::NAN
+    } else if xs.len().is_multiple_of(2) {
+        (xs[mid - 1] + xs[mid]) / 2.0
+    } else {
+        xs[mid]
+    }
+}
+
+/// The median absolute deviation, how far values typically are from their
+/// median. Unlike the standard deviation, a few slow outliers barely move it.
+pub fn mad(xs: &[f64], median: f64) -> f64 {
+    let mut deviations = xs.iter().map(|x| (x - median).abs()).collect::<Vec<_>>();
+    self::median(&mut deviations)
+}
+
+//---------------------------------------------------------------------------//
+
+/// Times `iterations` calls, returning the time per call in nanoseconds.
+fn sample(input: Input, imp: Implementation, iterations: u64) -> f64 {
+    let start = Instant::now();
+    for _ in 0..iterations {
+        black_box(black_box(input).run(imp));
+    }
+    start.elapsed().as_nanos() as f64 / iterations as f64
+}
+
+/// How many calls a sample needs to take at least [`MIN_SAMPLE`].
+fn calibrate(input: Input, imp: Implementation) -> u64 {
+    let mut iterations = 1;
+    while iterations < MAX_ITERATIONS {
+        let start = Instant::now();
+        for _ in 0..iterations {
+            black_box(black_box(input).run(imp));
+        }
+        if start.elapsed() >= MIN_SAMPLE {
+            break;
+        }
+        iterations *= 2;
+    }
+    iterations
+}
+
+/// Times each implementation in `method` on an input, warming up first and
+/// then taking `settings.samples` samples.
+pub fn bench(input: Input, method: Method, settings: Settings) -> Result<Bench> {
+    if settings.samples == 0 {
+        bail!("need at least one sample");
+    }
+    input.validate(method)?;
+
+    let timings = method
+        .implementations()
+        .iter()
+        .map(|&imp| {
+            let iterations = calibrate(input, imp);
+            for _ in 0..settings.warmup {
+                sample(input, imp, iterations);
+            }
+
+            let mut samples = (0..settings.samples)
+                .map(|_| sample(input, imp, iterations))
+                .collect::<Vec<_>>();
+            let median = median(&mut samples);
+            Timing {
+                implementation: imp,
+                median,
+                mad: mad(&samples, median),
+                samples: settings.samples,
+                iterations,
+            }
+        })
+        .collect();
+
+    Ok(Bench { input, timings })
+}
+
+//---------------------------------------------------------------------------//
+
+/// Writes the timings out. Text is a table with a column per implementation
+/// and how many times longer recursion took, JSON is one object per input and
+/// CSV one row per implementation per input.
+pub fn write(benches: &[Bench], format: Format, out: &mut impl Write) -> io::Result<()> {
+    match format {
+        Format::Text => {
+            let Some(first) = benches.first() else {
+                return Ok(());
+            };
+
+            let mut header = vec!["input".to_string()];
+            header.extend(
+                first
+                    .timings
+                    .iter()
+                    .map(|t| format!("{} (ns)", t.implementation.name())),
+            );
+            if first.ratio().is_some() {
+                header.push("recursive/iterative".to_string());
+            }
+
+            let mut rows = vec![header];
+            for bench in benches {
+                let mut row = vec![bench.input.to_string()];
+                row.extend(
+                    bench
+                        .timings
+                        .iter()
+                        .map(|t| format!("{:.1} ± {:.1}", t.median, t.mad)),
+                );
+                if let Some(ratio) = bench.ratio() {
+                    row.push(format!("{:.2}x", ratio));
+                }
+                rows.push(row);
+            }
+
+            // pad each column to its widest cell
+            let widths = (0..rows[0].len())
+                .map(|i| rows.iter().map(|r| r[i].chars().count()).max().unwrap_or(0))
+                .collect::<Vec<_>>();
+            for row in &rows {
+                let cells = row
+                    .iter()
+                    .zip(&widths)
+                    .map(|(cell, width)| format!("{:<width$}", cell, width = width))
+                    .collect::<Vec<_>>();
+                writeln!(out, "{}", cells.join("  ").trim_end())?;
+            }
+            Ok(())
+        }
+        Format::Json => {
+            for bench in benches {
+                let timings = bench
+                    .timings
+                    .iter()
+                    .map(|t| {
+                        json!({
+                            "implementation": t.implementation.name(),
+                            "median_nanos": t.median,
+                            "mad_nanos": t.mad,
+                            "samples": t.samples,
+                            "iterations": t.iterations,
+                        })
+                    })
+                    .collect::<Vec<_>>();
+                let value = json!({
+                    "schema": SCHEMA_VERSION,
+                    "algorithm": bench.input.algorithm(),
+                    "input": bench.input.arguments(),
+                    "timings": timings,
+                    "ratio": bench.ratio(),
+                });
+                writeln!(out, "{}", value)?;
+            }
+            Ok(())
+        }
+        Format::Csv => {
+            writeln!(
+                out,
+                "algorithm,input,implementation,median_nanos,mad_nanos,samples,iterations"
+            )?;
+            for bench in benches {
+                for t in &bench.timings {
+                    let row = [
+                        bench.input.algorithm().to_string(),
+                        bench.input.arguments(),
+                        t.implementation.name().to_string(),
+                        t.median.to_string(),
+                        t.mad.to_string(),
+                        t.samples.to_string(),
+                        t.iterations.to_string(),
+                    ];
+                    let row = row.map(|f| csv_field(&f));
+                    writeln!(out, "{}", row.join(","))?;
+                }
+            }
+            Ok(())
+        }
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use super::{Bench, Settings};
+    use crate::algorithm::{Input, Method};
+    use crate::format::Format;
+    use recursion_lib::lcd::Expr;
+    use serde_json::Value;
+
+    const QUICK: Settings = Settings {
+        warmup: 1,
+        samples: 5,
+    };
+
+    fn write(benches: &[Bench], format: Format) -> String {
+        let mut out = vec![];
+        super::write(benches, format, &mut out).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn stats() {
+        assert_eq!(super::median(&mut [3.0, 1.0, 2.0]), 2.0);
+        assert_eq!(super::median(&mut [4.0, 1.0, 3.0, 2.0]), 2.5);
+        assert!(super::median(&mut []).is_nan());
+
+        // one outlier barely moves either
+        let mut xs = [10.0, 11.0, 9.0, 10.0, 1000.0];
+        let median = super::median(&mut xs);
+        assert_eq!(median, 10.0);
+        assert_eq!(super::mad(&xs, median), 1.0);
+    }
+
+    #[test]
+    fn input() {
+        assert_eq!(
+            super::input("expo", "2.5^-3").unwrap(),
+            Input::Expo { x: 2.5, e: -3 }
+        );
+        assert_eq!(super::input("bin", "-11").unwrap(), Input::Bin(-11));
+        assert_eq!(
+            super::input("lcd", "2(3/6)").unwrap().to_string(),
+            "lcd 2(1/2)"
+        );
+        assert!(super::input("lcd", "1/0").is_err());
+        assert!(super::input("lcd", "9223372036854775807(3/2)").is_err());
+        assert!(super::input("expo", "2.5").is_err());
+        assert!(super::input("expo", "2.5^0.5").is_err());
+        assert!(super::input("bin", "eleven").is_err());
+        assert!(super::input("gcd", "4").is_err());
+    }
+
+    #[test]
+    fn bench() {
+        let inputs = [
+            Input::Expo { x: 2.5, e: 20 },
+            Input::Bin(-11),
+            Input::Lcd(Expr::new(0, 21, 45)),
+        ];
+        let benches = inputs
+            .iter()
+            .map(|&input| super::bench(input, Method::Both, QUICK).unwrap())
+            .collect::<Vec<_>>();
+
+        for bench in &benches {
+            assert_eq!(bench.timings.len(), 2);
+            assert!(bench.ratio().unwrap() > 0.0);
+            for t in &bench.timings {
+                assert!(t.median > 0.0 && t.mad >= 0.0);
+                assert_eq!(t.samples, 5);
+                assert!(t.iterations >= 1);
+            }
+        }
+
+        let one = super::bench(Input::Bin(5), Method::Iterative, QUICK).unwrap();
+        assert_eq!(one.timings.len(), 1);
+        assert_eq!(one.ratio(), None);
+
+        let settings = Settings {
+            warmup: 0,
+            samples: 0,
+        };
+        assert!(super::bench(Input::Bin(5), Method::Both, settings).is_err());
+        assert!(super::bench(Input::Expo { x: 2.0, e: 100_000 }, Method::Both, QUICK).is_err());
+
+        // a table with a column per implementation, lined up
+        let text = write(&benches, Format::Text);
+        let lines = text.lines().collect::<Vec<_>>();
+        assert_eq!(lines.len(), 4);
+        assert!(lines[0].starts_with("input "));
+        assert!(lines[0].ends_with("recursive/iterative"));
+        assert!(lines[1].starts_with("expo 2.5 20"));
+        assert!(lines[2].starts_with("bin -11"));
+        assert!(lines[3].ends_with('x'));
+        let column = lines[0].find("iterative (ns)").unwrap();
+        assert!(lines[1..]
+            .iter()
+            .all(|l| l.chars().nth(column - 2) == Some(' ')));
+
+        let text = write(std::slice::from_ref(&one), Format::Text);
+        assert!(text.starts_with("input  iterative (ns)\nbin 5  "));
+
+        let json = write(&benches, Format::Json);
+        let lines = json
+            .lines()
+            .map(|line| serde_json::from_str::<Value>(line).unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(lines[2]["algorithm"], "lcd");
+        assert_eq!(lines[2]["input"], "0(21/45)");
+        assert_eq!(lines[2]["timings"][1]["implementation"], "iterative");
+        assert!(lines[2]["timings"][1]["median_nanos"].is_f64());
+        assert!(lines[2]["ratio"].is_f64());
+
+        let csv = write(&benches, Format::Csv);
+        let rows = csv.lines().collect::<Vec<_>>();
+        assert_eq!(rows.len(), 7);
+        assert!(rows[1].starts_with("expo,2.5 20,recursive,"));
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
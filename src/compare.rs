@@ -0,0 +1,434 @@
+////////////////////////////////////////////////////////////////////////////////
+
+use crate::algorithm::{Input, Method, Output};
+use crate::format::Format;
+use anyhow::{bail, Result};
+use serde_json::json;
+use std::io::{self, Write};
+use std::ops::RangeInclusive;
+
+////////////////////////////////////////////////////////////////////////////////
+
+/// The standard library's answer for an input, where it has one.
+/// `expo` has `f64::powi`, and `bin` is just the bits of the integer.
+pub fn reference(input: &Input) -> Option<Output> {
+    match *input {
+        Input::Expo { x, e } => Some(Output::Float(x.powi(e))),
+        Input::Bin(x) => {
+            let mut bits = [false; 64];
+            for (i, bit) in bits.iter_mut().enumerate().take(63) {
+                *bit = x.unsigned_abs() >> i & 1 == 1;
+            }
+            bits[63] = x < 0;
+            Some(Output::Bits(bits))
+        }
+        Input::Lcd(_) => None,
+    }
+}
+
+/// Every input for `algorithm` over a range.
+/// For `expo` the range is the exponent, tried with each of `bases`.
+/// For `lcd` it is the numerator, over each denominator except zero.
+/// For `bin` it is just the integer.
+pub fn inputs(
+    algorithm: &str,
+    range: RangeInclusive<i64>,
+    bases: &[f64],
+    denominators: RangeInclusive<i64>,
+) -> Result<Vec<Input>> {
+    Ok(match algorithm {
+        "expo" => {
+            let lo = i32::try_from(*range.start())?;
+            let hi = i32::try_from(*range.end())?;
+            bases
+                .iter()
+                .flat_map(|x| (lo..=hi).map(|e| Input::Expo { x: *x, e }))
+                .collect()
+        }
+        "bin" => range.map(Input::Bin).collect(),
+        "lcd" => range
+            .flat_map(|n| {
+                denominators
+                    .clone()
+                    .filter(|d| *d != 0)
+                    .map(move |d| Input::Lcd(recursion_lib::lcd::Expr::new(0, n, d)))
+            })
+            .collect(),
+        _ => bail!("{:?} is not an algorithm", algorithm),
+    })
+}
+
+//---------------------------------------------------------------------------//
+
+/// How many of the 8 little endian bytes differ between two floats, as the
+/// `expo` tests measure it.
+pub fn byte_diff(a: f64, b: f64) -> u32 {
+    let (a, b) = (a.to_le_bytes(), b.to_le_bytes());
+    (0..8).filter(|i| a[*i] != b[*i]).count() as u32
+}
+
+/// How many representable floats apart two floats are.
+/// NaN is as far from everything else as it gets.
+pub fn ulps(a: f64, b: f64) -> u64 {
+    if a.is_nan() || b.is_nan() {
+        return if a.is_nan() && b.is_nan() {
+            0
+        } else {
+            u64::MAX
+        };
+    }
+
+    // flip the negative half around so the bits are in numerical order
+    let ordered = |x: f64| {
+        let bits = x.to_bits() as i64;
+        if bits < 0 {
+            i64::MIN - bits
+        } else {
+            bits
+        }
+    };
+    (ordered(a) as i128 - ordered(b) as i128).unsigned_abs() as u64
+}
+
+/// |actual - expected| / |expected|, or just the difference near zero.
+pub fn relative(actual: f64, expected: f64) -> f64 {
+    if actual.to_bits() == expected.to_bits() || (actual.is_nan() && expected.is_nan()) {
+        return 0.0;
+    }
+    let diff = (actual - expected).abs();
+    if expected == 0.0 {
+        diff
+    } else {
+        diff / expected.abs()
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+#[derive(Debug, Copy, Clone)]
+/// One implementation disagreeing with what it was checked against.
+pub struct Diff {
+    pub input: Input,
+    pub name: &'static str,
+    pub actual: Output,
+    /// `std`, or `recursive` when there is no reference.
+    pub against: &'static str,
+    pub expected: Output,
+}
+
+impl Diff {
+    /// How exactly the two outputs differ.
+    pub fn detail(&self) -> String {
+        match (self.actual, self.expected) {
+            (Output::Float(a), Output::Float(b)) => format!(
+                "{:#018x} vs {:#018x}, {} bytes differ, {} ulps, relative error {:e}",
+                a.to_bits(),
+                b.to_bits(),
+                byte_diff(a, b),
+                ulps(a, b),
+                relative(a, b)
+            ),
+            (Output::Bits(a), Output::Bits(b)) => {
+                let bits = (0..64).filter(|i| a[*i] != b[*i]).collect::<Vec<_>>();
+                format!("bits {:?} differ", bits)
+            }
+            (Output::Expr(a), Output::Expr(b)) => {
+                if a == b {
+                    "same value, different form".to_string()
+                } else {
+                    "different values".to_string()
+                }
+            }
+            _ => "different kinds of output".to_string(),
+        }
+    }
+}
+
+//---------------------------------------------------------------------------//
+
+#[derive(Debug, Clone, Default)]
+/// Every disagreement over a range, and the worst of them.
+pub struct Summary {
+    pub inputs: usize,
+    pub diffs: Vec<Diff>,
+    pub max_bytes: u32,
+    pub max_ulps: u64,
+    pub max_relative: f64,
+}
+
+/// Runs each implementation in `method` over every input, checking them
+/// against the standard library where it has an answer, or against each
+/// other where it doesn't.
+///
+/// Inputs:
+/// - `inputs: &[Input]`
+/// - `method: Method`
+///
+/// Outputs: `Result<Summary>`
+/// What disagreed, or why the inputs can't be run.
+///
+/// Time complexity: O(n) runs
+pub fn compare(inputs: &[Input], method: Method) -> Result<Summary> {
+    let mut summary = Summary {
+        inputs: inputs.len(),
+        ..Summary::default()
+    };
+
+    for input in inputs {
+        input.validate(method)?;
+
+        let outputs = method
+            .implementations()
+            .iter()
+            .map(|imp| (imp.name(), input.run(*imp)))
+            .collect::<Vec<_>>();
+
+        // with nothing to check against, the first implementation is the
+        // expected answer
+        let ((against, expected), rest) = match reference(input) {
+            Some(output) => (("std", output), &outputs[..]),
+            None if outputs.len() > 1 => (outputs[0], &outputs[1..]),
+            None => bail!(
+                "{} has no reference, so needs --method both to compare",
+                input.algorithm()
+            ),
+        };
+
+        for (name, actual) in rest {
+            if actual.same(&expected) {
+                continue;
+            }
+            if let (Output::Float(a), Output::Float(b)) = (actual, expected) {
+                summary.max_bytes = summary.max_bytes.max(byte_diff(*a, b));
+                summary.max_ulps = summary.max_ulps.max(ulps(*a, b));
+                summary.max_relative = summary.max_relative.max(relative(*a, b));
+            }
+            summary.diffs.push(Diff {
+                input: *input,
+                name,
+                actual: *actual,
+                against,
+                expected,
+            });
+        }
+    }
+
+    Ok(summary)
+}
+
+//---------------------------------------------------------------------------//
+
+/// Writes every disagreement, then the totals.
+pub fn write(summary: &Summary, format: Format, out: &mut impl Write) -> io::Result<()> {
+    match format {
+        Format::Text => {
+            for d in &summary.diffs {
+                writeln!(
+                    out,
+                    "{}: {} = {}, {} = {} ({})",
+                    d.input,
+                    d.name,
+                    d.actual,
+                    d.against,
+                    d.expected,
+                    d.detail()
+                )?;
+            }
+            writeln!(
+                out,
+                "{} inputs, {} disagreements",
+                summary.inputs,
+                summary.diffs.len()
+            )?;
+            if summary.max_ulps > 0 {
+                writeln!(
+                    out,
+                    "max error: {} bytes, {} ulps, relative {:e}",
+                    summary.max_bytes, summary.max_ulps, summary.max_relative
+                )?;
+            }
+            Ok(())
+        }
+        Format::Json => {
+            let diffs = summary
+                .diffs
+                .iter()
+                .map(|d| {
+                    json!({
+                        "input": d.input.to_string(),
+                        "implementation": d.name,
+                        "output": d.actual.to_string(),
+                        "against": d.against,
+                        "expected": d.expected.to_string(),
+                        "detail": d.detail(),
+                    })
+                })
+                .collect::<Vec<_>>();
+            let value = json!({
+                "schema": crate::format::SCHEMA_VERSION,
+                "inputs": summary.inputs,
+                "diffs": diffs,
+                "max_bytes": summary.max_bytes,
+                "max_ulps": summary.max_ulps,
+                "max_relative": summary.max_relative,
+            });
+            writeln!(out, "{}", value)
+        }
+        Format::Csv => {
+            writeln!(out, "input,implementation,output,against,expected,detail")?;
+            for d in &summary.diffs {
+                let row = [
+                    d.input.to_string(),
+                    d.name.to_string(),
+                    d.actual.to_string(),
+                    d.against.to_string(),
+                    d.expected.to_string(),
+                    d.detail(),
+                ];
+                let row = row.map(|f| crate::format::csv_field(&f));
+                writeln!(out, "{}", row.join(","))?;
+            }
+            Ok(())
+        }
+    }
+}
+
+//---------------------------------------------------------------------------//
+
+/// Reads `a..b` or `a..=b`.
+pub fn range(s: &str) -> Result<RangeInclusive<i64>, String> {
+    let (lo, hi, inclusive) = match s.split_once("..=") {
+        Some((lo, hi)) => (lo, hi, true),
+        None => match s.split_once("..") {
+            Some((lo, hi)) => (lo, hi, false),
+            None => return Err(format!("expected a range like -5..5, found {:?}", s)),
+        },
+    };
+    let int = |x: &str| {
+        x.trim()
+            .parse::<i64>()
+            .map_err(|e| format!("{:?}: {}", x, e))
+    };
+    let (lo, hi) = (int(lo)?, int(hi)?);
+
+    if inclusive {
+        Ok(lo..=hi)
+    } else if lo < hi {
+        Ok(lo..=hi - 1)
+    } else {
+        Err(format!("{:?} is empty", s))
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use crate::algorithm::{Implementation, Input, Method, Output};
+    use crate::format::Format;
+    use recursion_lib::lcd::Expr;
+
+    #[test]
+    fn metrics() {
+        assert_eq!(super::byte_diff(1.0, 1.0), 0);
+        assert_eq!(
+            super::byte_diff(1.0, f64::from_bits(1.0f64.to_bits() + 1)),
+            1
+        );
+        assert_eq!(super::ulps(1.0, f64::from_bits(1.0f64.to_bits() + 3)), 3);
+        assert_eq!(super::ulps(0.0, -0.0), 0);
+        assert_eq!(super::ulps(f64::from_bits(1), -f64::from_bits(1)), 2);
+        assert_eq!(super::ulps(f64::NAN, 1.0), u64::MAX);
+        assert_eq!(super::relative(1.5, 1.0), 0.5);
+        assert_eq!(super::relative(0.25, 0.0), 0.25);
+        assert_eq!(super::relative(f64::NAN, f64::NAN), 0.0);
+
+        assert_eq!(super::range("-5..5"), Ok(-5..=4));
+        assert_eq!(super::range("-5..=5"), Ok(-5..=5));
+        assert!(super::range("5..5").is_err());
+        assert!(super::range("5").is_err());
+        assert!(super::range("a..b").is_err());
+    }
+
+    #[test]
+    fn compare() {
+        // the recursive and iterative versions multiply in the same order,
+        // but powi doesn't, so a few inputs are off in the last place
+        let inputs = super::inputs("expo", -30..=30, &[0.1, 2.5, -3.0], 1..=1).unwrap();
+        assert_eq!(inputs.len(), 183);
+        let summary = super::compare(&inputs, Method::Both).unwrap();
+        assert!(!summary.diffs.is_empty());
+        assert!(summary.diffs.iter().all(|d| d.against == "std"));
+        assert!(0 < summary.max_ulps && summary.max_ulps < 64);
+        assert!(summary.max_bytes < 3);
+        assert!(summary.max_relative < 1e-13);
+
+        let inputs = super::inputs("bin", -1000..=1000, &[], 1..=1).unwrap();
+        let summary = super::compare(&inputs, Method::Both).unwrap();
+        assert_eq!(summary.inputs, 2001);
+        assert!(summary.diffs.is_empty());
+
+        let inputs = super::inputs("lcd", -30..=30, &[], -12..=12).unwrap();
+        assert_eq!(inputs.len(), 61 * 24);
+        let summary = super::compare(&inputs, Method::Both).unwrap();
+        assert!(summary.diffs.is_empty());
+        assert!(super::compare(&inputs, Method::Iterative).is_err());
+
+        assert!(super::inputs("gcd", 0..=1, &[], 1..=1).is_err());
+        let too_deep = super::inputs("expo", 0..=100_000, &[2.0], 1..=1).unwrap();
+        assert!(super::compare(&too_deep, Method::Both).is_err());
+    }
+
+    #[test]
+    fn write() {
+        let expected = Output::Float(0.1);
+        let actual = Output::Float(f64::from_bits(0.1f64.to_bits() + 2));
+        let diff = super::Diff {
+            input: Input::Expo { x: 0.1, e: 1 },
+            name: Implementation::Iterative.name(),
+            actual,
+            against: "std",
+            expected,
+        };
+        let max_relative = match actual {
+            Output::Float(a) => super::relative(a, 0.1),
+            _ => unreachable!(),
+        };
+        let summary = super::Summary {
+            inputs: 1,
+            diffs: vec![diff],
+            max_bytes: 1,
+            max_ulps: 2,
+            max_relative,
+        };
+
+        let mut out = vec![];
+        super::write(&summary, Format::Text, &mut out).unwrap();
+        let out = String::from_utf8(out).unwrap();
+        assert!(out.starts_with(
+            "expo 0.1 1: iterative = 0.10000000000000003, std = 0.1 \
+             (0x3fb999999999999c vs 0x3fb999999999999a, 1 bytes differ, 2 ulps"
+        ));
+        assert!(out.contains("1 inputs, 1 disagreements\nmax error: 1 bytes, 2 ulps"));
+
+        let mut out = vec![];
+        super::write(&summary, Format::Json, &mut out).unwrap();
+        let value = serde_json::from_slice::<serde_json::Value>(&out).unwrap();
+        assert_eq!(value["inputs"], 1);
+        assert_eq!(value["max_ulps"], 2);
+        assert_eq!(value["diffs"][0]["implementation"], "iterative");
+        assert_eq!(value["diffs"][0]["expected"], "0.1");
+
+        // lcd forms that are equal in value are still reported
+        let diff = super::Diff {
+            input: Input::Lcd(Expr::new(0, 2, 4)),
+            name: "iterative",
+            actual: Output::Expr(Expr::new(0, 2, 4)),
+            against: "recursive",
+            expected: Output::Expr(Expr::new(0, 1, 2)),
+        };
+        assert_eq!(diff.detail(), "same value, different form");
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
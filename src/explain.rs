@@ -0,0 +1,300 @@
+////////////////////////////////////////////////////////////////////////////////
+
+use crate::algorithm::{Input, Method};
+use anyhow::Result;
+use recursion_lib::{expo, int_to_bin, lcd};
+use std::io::Write;
+
+////////////////////////////////////////////////////////////////////////////////
+
+/// How many multiplications are shown at either end before the rest are
+/// summarised.
+const SHOWN_MULTIPLICATIONS: usize = 3;
+
+//---------------------------------------------------------------------------//
+
+/// Narrates what the iterative implementation of an algorithm does with an
+/// input, one sentence per line, built from the steps the library records.
+pub fn explain(input: Input) -> Result<Vec<String>> {
+    input.validate(Method::Iterative)?;
+
+    Ok(match input {
+        Input::Expo { x, e } => expo(x, e),
+        Input::Bin(x) => bin(x, &int_to_bin::iterative_traced(x).1),
+        Input::Lcd(e) => lcd(&lcd::iterative_traced(e).1),
+    })
+}
+
+/// Writes the explanation of an input as numbered lines.
+pub fn write(input: Input, out: &mut impl Write) -> Result<()> {
+    for (i, line) in explain(input)?.iter().enumerate() {
+        writeln!(out, "{}. {}", i + 1, line)?;
+    }
+    Ok(())
+}
+
+/// Lists the multiplications, leaving out the middle of a long run. Steps are
+/// dropped as they're made, so huge exponents don't pile them up.
+fn expo(x: f64, e: i32) -> Vec<String> {
+    if e == 0 {
+        return vec![format!("Anything to the power of 0 is 1, so {}^0 = 1.", x)];
+    }
+
+    let mut res = vec![format!(
+        "Start from 1 and multiply by {}, {} times.",
+        x,
+        e.unsigned_abs()
+    )];
+
+    let hidden = (e.unsigned_abs() as usize).saturating_sub(2 * SHOWN_MULTIPLICATIONS);
+    let mut i = 0;
+    expo::power_with(x, e, &mut |step| match step {
+        expo::Step::Multiply { .. } => {
+            if hidden > 0 && i == SHOWN_MULTIPLICATIONS {
+                res.push(format!("... {} more multiplications ...", hidden));
+            }
+            if hidden == 0 || i < SHOWN_MULTIPLICATIONS || i >= SHOWN_MULTIPLICATIONS + hidden {
+                res.push(format!("{}.", step));
+            }
+            i += 1;
+        }
+        expo::Step::Invert(_) => res.push(format!(
+            "The exponent is negative, so the answer is one over that: {}.",
+            step
+        )),
+    });
+    res
+}
+
+/// Walks down the bits, summarising the leading bits that are too large to be
+/// on.
+fn bin(x: i64, steps: &[int_to_bin::Step]) -> Vec<String> {
+    let mut res = vec![];
+    let mut steps = steps;
+
+    if let [int_to_bin::Step::Negative(_), rest @ ..] = steps {
+        res.push(format!(
+            "{} is negative, so the sign bit 63 is switched on and the rest is worked out for {}.",
+            x,
+            x.unsigned_abs()
+        ));
+        steps = rest;
+    }
+
+    res.push(
+        "Working down from bit 62, each bit is switched on if its value fits in what's \
+         left, and its value is subtracted."
+            .to_string(),
+    );
+
+    // the bits above the highest one that's on
+    let leading = steps
+        .iter()
+        .take_while(|s| matches!(s, int_to_bin::Step::Off { .. }))
+        .count();
+    if leading > 0 {
+        let last = match steps[leading - 1] {
+            int_to_bin::Step::Off { bit, value, .. } => (bit, value),
+            _ => unreachable!(),
+        };
+        res.push(format!(
+            "Bits 62 to {} are off, since their values, down to {}, are all more than {}.",
+            last.0,
+            last.1,
+            x.unsigned_abs()
+        ));
+    }
+
+    for step in &steps[leading..] {
+        let bit = match step {
+            int_to_bin::Step::On { bit, value, .. } | int_to_bin::Step::Off { bit, value, .. } => {
+                format!("Bit {} is worth {}: ", bit, value)
+            }
+            int_to_bin::Step::Negative(_) => String::new(),
+        };
+        res.push(format!("{}{}.", bit, step));
+    }
+    res
+}
+
+/// One sentence per step of the simplification.
+fn lcd(steps: &[lcd::Step]) -> Vec<String> {
+    steps
+        .iter()
+        .map(|step| match *step {
+            lcd::Step::AlreadySimplified(e) => {
+                format!(
+                    "{} is already in its simplest form, so there's nothing to do.",
+                    e
+                )
+            }
+            lcd::Step::Improper { from, n, d } if from.c == 0 => {
+                format!(
+                    "{} has no whole part, so it's just the fraction {}/{}.",
+                    from, n, d
+                )
+            }
+            lcd::Step::Improper { from, n, d } => {
+                // a negative whole part pulls the fraction away from zero too
+                let op = if from.c < 0 { '-' } else { '+' };
+                format!(
+                    "Fold the whole part into the fraction: {} is ({} * {} {} {})/{} = {}/{}.",
+                    from, from.c, from.d, op, from.n, from.d, n, d
+                )
+            }
+            lcd::Step::CommonFactor { n, d, gcd: 1 } => format!(
+                "Searching for a common factor, {} and {} only share 1, so there's nothing \
+                 to divide out.",
+                n, d
+            ),
+            lcd::Step::CommonFactor { n, d, gcd } => format!(
+                "Searching for a common factor, the greatest one {} and {} share is {}.",
+                n, d, gcd
+            ),
+            lcd::Step::Divide { n, d, by } => format!(
+                "Divide both by {}: {}/{} = {}/{}.",
+                by,
+                n,
+                d,
+                n / by,
+                d / by
+            ),
+            lcd::Step::MoveSign { .. } => format!(
+                "The denominator is negative, so move the sign to the nominator: {}.",
+                step
+            ),
+            lcd::Step::Split { n, d, into } if into.c == 0 => format!(
+                "{} is smaller than {}, so there's no whole part to take out, leaving {}.",
+                n.unsigned_abs(),
+                d,
+                into
+            ),
+            lcd::Step::Split { n, d, into } => format!(
+                "{} goes into {} {} whole times with {} left over, leaving {}.",
+                d,
+                n.unsigned_abs(),
+                into.c.unsigned_abs(),
+                into.n.unsigned_abs(),
+                into
+            ),
+        })
+        .collect()
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use crate::algorithm::Input;
+    use recursion_lib::lcd::Expr;
+
+    fn explain(input: Input) -> Vec<String> {
+        super::explain(input).unwrap()
+    }
+
+    #[test]
+    fn lcd() {
+        assert_eq!(
+            explain(Input::Lcd(Expr::new(0, 21, 45))),
+            [
+                "0(21/45) has no whole part, so it's just the fraction 21/45.",
+                "Searching for a common factor, the greatest one 21 and 45 share is 3.",
+                "Divide both by 3: 21/45 = 7/15.",
+                "7 is smaller than 15, so there's no whole part to take out, leaving 0(7/15).",
+            ]
+        );
+        assert_eq!(
+            explain(Input::Lcd(Expr::new(1, 1, -3))),
+            [
+                "Fold the whole part into the fraction: 1(1/-3) is (1 * -3 + 1)/-3 = -2/-3.",
+                "Searching for a common factor, -2 and -3 only share 1, so there's nothing to divide out.",
+                "The denominator is negative, so move the sign to the nominator: -2/-3 -> 2/3.",
+                "2 is smaller than 3, so there's no whole part to take out, leaving 0(2/3).",
+            ]
+        );
+        assert_eq!(
+            explain(Input::Lcd(Expr::new(0, -16, 6)))[3],
+            "3 goes into 8 2 whole times with 2 left over, leaving -2(2/3)."
+        );
+        assert_eq!(
+            explain(Input::Lcd(Expr::new(-2, 2, 4)))[0],
+            "Fold the whole part into the fraction: -2(2/4) is (-2 * 4 - 2)/4 = -10/4."
+        );
+        assert_eq!(
+            explain(Input::Lcd(Expr::new(2, 1, 5))),
+            ["2(1/5) is already in its simplest form, so there's nothing to do."]
+        );
+        assert!(super::explain(Input::Lcd(Expr::new(0, 1, 0))).is_err());
+    }
+
+    #[test]
+    fn bin() {
+        assert_eq!(
+            explain(Input::Bin(11)),
+            [
+                "Working down from bit 62, each bit is switched on if its value fits in what's left, and its value is subtracted.",
+                "Bits 62 to 4 are off, since their values, down to 16, are all more than 11.",
+                "Bit 3 is worth 8: 11 - 8 = 3, bit 3 is on.",
+                "Bit 2 is worth 4: 4 > 3, bit 2 is off.",
+                "Bit 1 is worth 2: 3 - 2 = 1, bit 1 is on.",
+                "Bit 0 is worth 1: 1 - 1 = 0, bit 0 is on.",
+            ]
+        );
+        let negative = explain(Input::Bin(-11));
+        assert_eq!(
+            negative[0],
+            "-11 is negative, so the sign bit 63 is switched on and the rest is worked out for 11."
+        );
+        assert_eq!(negative[1..], explain(Input::Bin(11))[..]);
+        assert_eq!(
+            explain(Input::Bin(0))[1],
+            "Bits 62 to 0 are off, since their values, down to 1, are all more than 0."
+        );
+    }
+
+    #[test]
+    fn expo() {
+        assert_eq!(
+            explain(Input::Expo { x: 2.0, e: -2 }),
+            [
+                "Start from 1 and multiply by 2, 2 times.",
+                "1 * 2 = 2.",
+                "2 * 2 = 4.",
+                "The exponent is negative, so the answer is one over that: 1 / 4 = 0.25.",
+            ]
+        );
+        assert_eq!(
+            explain(Input::Expo { x: 2.5, e: 0 }),
+            ["Anything to the power of 0 is 1, so 2.5^0 = 1."]
+        );
+
+        let long = explain(Input::Expo { x: 2.0, e: 10 });
+        assert_eq!(long.len(), 8);
+        assert_eq!(long[3], "4 * 2 = 8.");
+        assert_eq!(long[4], "... 4 more multiplications ...");
+        assert_eq!(long[5], "128 * 2 = 256.");
+        assert_eq!(long[7], "512 * 2 = 1024.");
+
+        let huge = explain(Input::Expo {
+            x: 1.0,
+            e: -1_000_000,
+        });
+        assert_eq!(huge.len(), 9);
+        assert_eq!(huge[4], "... 999994 more multiplications ...");
+        assert_eq!(huge[7], "1 * 1 = 1.");
+        assert_eq!(
+            huge[8],
+            "The exponent is negative, so the answer is one over that: 1 / 1 = 1."
+        );
+
+        let mut out = vec![];
+        super::write(Input::Expo { x: 3.0, e: 1 }, &mut out).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "1. Start from 1 and multiply by 3, 1 times.\n\
+             2. 1 * 3 = 3.\n"
+        );
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
@@ -0,0 +1,366 @@
+////////////////////////////////////////////////////////////////////////////////
+
+use crate::algorithm::{Input, Output, Report};
+use serde_json::{json, Value};
+use std::io::{self, Write};
+
+////////////////////////////////////////////////////////////////////////////////
+
+/// Bumped whenever a field is removed or changes meaning. Adding fields
+/// doesn't change it.
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// The columns of the CSV output, in order.
+pub const CSV_HEADER: [&str; 8] = [
+    "algorithm",
+    "input",
+    "implementation",
+    "output",
+    "nanos",
+    "agree",
+    "line",
+    "error",
+];
+
+//---------------------------------------------------------------------------//
+
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+/// How results are written out.
+pub enum Format {
+    /// One `implementation: output` line per implementation.
+    Text,
+    /// One JSON object per line, per input:
+    ///
+    /// ```text
+    /// {
+    ///   "schema": 1,
+    ///   "algorithm": "expo" | "bin" | "lcd",
+    ///   "input": {"x": 2.5, "e": -3} | {"x": -11} | {"c": 0, "n": 21, "d": 45},
+    ///   "results": [
+    ///     {
+    ///       "implementation": "recursive" | "iterative",
+    ///       "output": "0.064",
+    ///       "value": 0.064 | -11 | {"c": 0, "n": 7, "d": 15},
+    ///       "nanos": 1200
+    ///     }
+    ///   ],
+    ///   "agree": true
+    /// }
+    /// ```
+    ///
+    /// `output` is the text form, `value` the typed form. A float that isn't
+    /// finite has a `value` of `null`. Keys are sorted.
+    ///
+    /// In a batch every object also has `line` and `command`, and a line that
+    /// failed is just `{"schema", "line", "command", "error"}`.
+    Json,
+    /// A header of [`CSV_HEADER`], then one row per implementation per input.
+    /// `line` and `error` are only filled in for a batch, where a line that
+    /// failed is a single row with just `input`, `line` and `error`.
+    Csv,
+}
+
+impl Format {
+    /// What each format is called on the command line.
+    pub const NAMES: [&'static str; 3] = ["text", "json", "csv"];
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "text" => Some(Format::Text),
+            "json" => Some(Format::Json),
+            "csv" => Some(Format::Csv),
+            _ => None,
+        }
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+fn input_json(input: &Input) -> Value {
+    match input {
+        Input::Expo { x, e } => json!({ "x": x, "e": e }),
+        Input::Bin(x) => json!({ "x": x }),
+        Input::Lcd(e) => json!({ "c": e.c, "n": e.n, "d": e.d }),
+    }
+}
+
+fn output_json(output: &Output) -> Value {
+    match output {
+        // serde_json writes NaN and infinity as null
+        Output::Float(x) => json!(x),
+        Output::Bits(_) => json!(output.argument().parse::<i64>().ok()),
+        Output::Expr(e) => json!({ "c": e.c, "n": e.n, "d": e.d }),
+    }
+}
+
+/// Quotes a CSV field if it needs it.
+pub fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+//---------------------------------------------------------------------------//
+
+#[derive(Debug, Copy, Clone)]
+/// Where a report came from, when there are many of them.
+pub struct Source<'a> {
+    /// Counting from 1.
+    pub line: usize,
+    /// The line as it was written.
+    pub command: &'a str,
+}
+
+/// Writes reports in one format, remembering whether the CSV header has been
+/// written yet.
+pub struct Printer {
+    format: Format,
+    header: bool,
+}
+
+impl Printer {
+    pub fn new(format: Format) -> Self {
+        Printer {
+            format,
+            header: false,
+        }
+    }
+
+    fn csv_row(&mut self, row: &[String], out: &mut impl Write) -> io::Result<()> {
+        if !self.header {
+            self.header = true;
+            writeln!(out, "{}", CSV_HEADER.join(","))?;
+        }
+        let row = row.iter().map(|f| csv_field(f)).collect::<Vec<_>>();
+        writeln!(out, "{}", row.join(","))
+    }
+
+    /// Writes the results of one input.
+    pub fn report(&mut self, report: &Report, out: &mut impl Write) -> io::Result<()> {
+        self.write(None, Ok(report), out)
+    }
+
+    /// Writes the results of one line of a batch, or why it failed.
+    pub fn line(
+        &mut self,
+        source: Source,
+        result: Result<&Report, &str>,
+        out: &mut impl Write,
+    ) -> io::Result<()> {
+        self.write(Some(source), result, out)
+    }
+
+    fn write(
+        &mut self,
+        source: Option<Source>,
+        result: Result<&Report, &str>,
+        out: &mut impl Write,
+    ) -> io::Result<()> {
+        match self.format {
+            Format::Text => {
+                // results from a batch are indented under the line they're for
+                let indent = match source {
+                    Some(source) => {
+                        writeln!(out, "{}: {}", source.line, source.command)?;
+                        "  "
+                    }
+                    None => "",
+                };
+                match result {
+                    Ok(report) => {
+                        for r in &report.results {
+                            let name = r.implementation.name();
+                            writeln!(out, "{}{}: {}", indent, name, r.output)?;
+                        }
+                        if !report.agree() {
+                            writeln!(out, "{}warning: the implementations disagree", indent)?;
+                        }
+                    }
+                    Err(e) => writeln!(out, "{}error: {}", indent, e)?,
+                }
+                Ok(())
+            }
+            Format::Json => {
+                let mut value = match result {
+                    Ok(report) => {
+                        let results = report
+                            .results
+                            .iter()
+                            .map(|r| {
+                                json!({
+                                    "implementation": r.implementation.name(),
+                                    "output": r.output.to_string(),
+                                    "value": output_json(&r.output),
+                                    "nanos": r.elapsed.as_nanos() as u64,
+                                })
+                            })
+                            .collect::<Vec<_>>();
+                        json!({
+                            "schema": SCHEMA_VERSION,
+                            "algorithm": report.input.algorithm(),
+                            "input": input_json(&report.input),
+                            "results": results,
+                            "agree": report.agree(),
+                        })
+                    }
+                    Err(e) => json!({ "schema": SCHEMA_VERSION, "error": e }),
+                };
+                if let Some(source) = source {
+                    value["line"] = json!(source.line);
+                    value["command"] = json!(source.command);
+                }
+                writeln!(out, "{}", value)
+            }
+            Format::Csv => {
+                let line = source.map(|s| s.line.to_string()).unwrap_or_default();
+                match result {
+                    Ok(report) => {
+                        let agree = report.agree();
+                        for r in &report.results {
+                            let row = [
+                                report.input.algorithm().to_string(),
+                                report.input.arguments(),
+                                r.implementation.name().to_string(),
+                                r.output.to_string(),
+                                r.elapsed.as_nanos().to_string(),
+                                agree.to_string(),
+                                line.clone(),
+                                String::new(),
+                            ];
+                            self.csv_row(&row, out)?;
+                        }
+                    }
+                    Err(e) => {
+                        // the line didn't parse, so keep what was written
+                        let command = source.map(|s| s.command).unwrap_or_default();
+                        let row = [
+                            String::new(),
+                            command.to_string(),
+                            String::new(),
+                            String::new(),
+                            String::new(),
+                            String::new(),
+                            line,
+                            e.to_string(),
+                        ];
+                        self.csv_row(&row, out)?;
+                    }
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use super::{Format, Printer};
+    use crate::algorithm::{self, Input, Method};
+    use recursion_lib::lcd::Expr;
+    use serde_json::Value;
+
+    /// Runs each input and writes the reports out in `format`.
+    fn emit(format: Format, inputs: &[Input]) -> String {
+        let mut printer = Printer::new(format);
+        let mut out = vec![];
+        for input in inputs {
+            let report = algorithm::run(*input, Method::Both).unwrap();
+            printer.report(&report, &mut out).unwrap();
+        }
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn json() {
+        let out = emit(
+            Format::Json,
+            &[
+                Input::Expo { x: 2.5, e: -3 },
+                Input::Bin(-11),
+                Input::Lcd(Expr::new(0, 21, 45)),
+                Input::Expo { x: 0.0, e: -1 },
+            ],
+        );
+        let lines = out
+            .lines()
+            .map(|line| serde_json::from_str::<Value>(line).unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(lines.len(), 4);
+
+        for line in &lines {
+            assert_eq!(line["schema"], 1);
+            assert_eq!(line["agree"], true);
+            let results = line["results"].as_array().unwrap();
+            assert_eq!(results.len(), 2);
+            assert_eq!(results[0]["implementation"], "recursive");
+            assert_eq!(results[1]["implementation"], "iterative");
+            assert!(results.iter().all(|r| r["nanos"].is_u64()));
+        }
+
+        assert_eq!(lines[0]["algorithm"], "expo");
+        assert_eq!(lines[0]["input"]["x"], 2.5);
+        assert_eq!(lines[0]["input"]["e"], -3);
+        assert_eq!(lines[0]["results"][0]["output"], "0.064");
+        assert_eq!(lines[0]["results"][0]["value"], 0.064);
+
+        assert_eq!(lines[1]["algorithm"], "bin");
+        assert_eq!(lines[1]["input"]["x"], -11);
+        assert_eq!(lines[1]["results"][1]["output"], "-1011");
+        assert_eq!(lines[1]["results"][1]["value"], -11);
+
+        assert_eq!(lines[2]["algorithm"], "lcd");
+        assert_eq!(lines[2]["input"]["n"], 21);
+        assert_eq!(lines[2]["input"]["d"], 45);
+        assert_eq!(lines[2]["results"][0]["output"], "0(7/15)");
+        assert_eq!(lines[2]["results"][0]["value"]["n"], 7);
+        assert_eq!(lines[2]["results"][0]["value"]["d"], 15);
+
+        assert_eq!(lines[3]["results"][0]["output"], "inf");
+        assert!(lines[3]["results"][0]["value"].is_null());
+    }
+
+    #[test]
+    fn csv() {
+        let out = emit(
+            Format::Csv,
+            &[
+                Input::Expo { x: 2.5, e: -3 },
+                Input::Lcd(Expr::new(2, 3, 6)),
+            ],
+        );
+        let rows = out
+            .lines()
+            .map(|line| line.split(',').collect::<Vec<_>>())
+            .collect::<Vec<_>>();
+
+        assert_eq!(rows.len(), 5);
+        assert_eq!(rows[0], super::CSV_HEADER);
+        assert!(rows.iter().all(|row| row.len() == super::CSV_HEADER.len()));
+
+        assert_eq!(rows[1][..4], ["expo", "2.5 -3", "recursive", "0.064"]);
+        assert_eq!(rows[2][..4], ["expo", "2.5 -3", "iterative", "0.064"]);
+        assert_eq!(rows[3][..4], ["lcd", "2(3/6)", "recursive", "2(1/2)"]);
+        assert!(rows[1..].iter().all(|row| row[4].parse::<u64>().is_ok()));
+        assert!(rows[1..].iter().all(|row| row[5] == "true"));
+        assert!(rows[1..]
+            .iter()
+            .all(|row| row[6].is_empty() && row[7].is_empty()));
+
+        assert_eq!(super::csv_field("a,b"), "\"a,b\"");
+        assert_eq!(super::csv_field("say \"hi\""), "\"say \"\"hi\"\"\"");
+    }
+
+    #[test]
+    fn text() {
+        let out = emit(Format::Text, &[Input::Bin(5)]);
+        assert_eq!(out, "recursive: 101\niterative: 101\n");
+        assert_eq!(Format::from_name("json"), Some(Format::Json));
+        assert_eq!(Format::from_name("xml"), None);
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
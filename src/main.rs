@@ -1,11 +1,578 @@
 ////////////////////////////////////////////////////////////////////////////////
 
-use recursion_lib::expo::iterative;
+mod algorithm;
+mod batch;
+mod bench;
+mod compare;
+mod explain;
+mod format;
+mod repl;
+mod stack;
+mod trace;
+
+use algorithm::{Input, Method};
+use anyhow::{bail, Context, Result};
+use clap::parser::ValueSource;
+use clap::{value_parser, Arg, ArgAction, ArgMatches, Command};
+use format::{Format, Printer};
+use recursion_lib::lcd::Expr;
+use std::ops::RangeInclusive;
+
+////////////////////////////////////////////////////////////////////////////////
+
+/// A subcommand for each algorithm, shared by the top level and `trace`.
+fn algorithms() -> [Command; 3] {
+    [
+        Command::new("expo")
+            .about("Raises x to the power of e")
+            .allow_negative_numbers(true)
+            .arg(
+                Arg::new("x")
+                    .required(true)
+                    .value_parser(value_parser!(f64))
+                    .help("The base, e.g. 2.5"),
+            )
+            .arg(
+                Arg::new("e")
+                    .required(true)
+                    .value_parser(value_parser!(i32))
+                    .help("The integer exponent, e.g. -3"),
+            ),
+        Command::new("bin")
+            .about("Writes an integer in binary")
+            .allow_negative_numbers(true)
+            .arg(
+                Arg::new("x")
+                    .required(true)
+                    .value_parser(value_parser!(i64))
+                    .help("The integer, e.g. -11"),
+            ),
+        Command::new("lcd")
+            .about("Simplifies a fraction to its lowest common denominator")
+            .arg(
+                Arg::new("expr")
+                    .required(true)
+                    .allow_hyphen_values(true)
+                    .value_parser(|s: &str| s.parse::<Expr>().map_err(|e| e.to_string()))
+                    .help("The fraction, e.g. 21/45, 2(3/6) or 0.1(6)"),
+            ),
+    ]
+}
+
+//---------------------------------------------------------------------------//
+
+/// The command line, with a subcommand for each algorithm.
+fn cli() -> Command {
+    Command::new("recursion-app")
+        .about("Runs the recursive and iterative implementations side by side")
+        .subcommand_required(true)
+        .arg_required_else_help(true)
+        .arg(
+            Arg::new("method")
+                .long("method")
+                .short('m')
+                .global(true)
+                .value_parser(Method::NAMES)
+                .default_value("both")
+                .help("Which implementations to run"),
+        )
+        .arg(
+            Arg::new("explain")
+                .long("explain")
+                .global(true)
+                .action(ArgAction::SetTrue)
+                .help("Narrate each step the iterative implementation takes"),
+        )
+        .arg(
+            Arg::new("format")
+                .long("format")
+                .short('f')
+                .global(true)
+                .value_parser(Format::NAMES)
+                .default_value("text")
+                .help("How to write the results, see the README for the JSON schema"),
+        )
+        .subcommands(algorithms())
+        .subcommand(
+            Command::new("trace")
+                .about("Shows every call the recursive implementation makes")
+                .subcommand_required(true)
+                .subcommands(algorithms())
+                .arg(
+                    Arg::new("dot")
+                        .long("dot")
+                        .action(ArgAction::SetTrue)
+                        .help("Write a Graphviz DOT graph instead of an indented tree"),
+                )
+                .arg(
+                    Arg::new("max-depth")
+                        .long("max-depth")
+                        .short('d')
+                        .value_parser(value_parser!(u64).range(1..))
+                        .help("How many levels of calls to show, counting the rest instead"),
+                ),
+        )
+        .subcommand(
+            Command::new("batch")
+                .about("Runs one command per line, like `lcd 20/22`, from a file or stdin")
+                .arg(
+                    Arg::new("file")
+                        .default_value("-")
+                        .help("The file to read, or - for stdin"),
+                )
+                .arg(
+                    Arg::new("threads")
+                        .long("threads")
+                        .short('t')
+                        .value_parser(value_parser!(u64).range(1..=1024))
+                        .default_value("1")
+                        .help("How many threads to split the lines between"),
+                ),
+        )
+        .subcommand(
+            Command::new("compare")
+                .about("Checks the implementations against each other and std over a range")
+                .arg(
+                    Arg::new("algorithm")
+                        .required(true)
+                        .value_parser(["expo", "bin", "lcd"]),
+                )
+                .arg(
+                    Arg::new("range")
+                        .long("range")
+                        .short('r')
+                        .required(true)
+                        .allow_hyphen_values(true)
+                        .value_parser(compare::range)
+                        .help("e.g. -10..10 or 0..=100, the exponent for expo and the numerator for lcd"),
+                )
+                .arg(
+                    Arg::new("base")
+                        .long("base")
+                        .allow_hyphen_values(true)
+                        .action(ArgAction::Append)
+                        .value_parser(value_parser!(f64))
+                        .default_value("2.5")
+                        .help("A base for expo, can be given more than once"),
+                )
+                .arg(
+                    Arg::new("denominators")
+                        .long("denominators")
+                        .allow_hyphen_values(true)
+                        .value_parser(compare::range)
+                        .default_value("-12..=12")
+                        .help("The denominators for lcd, skipping zero"),
+                ),
+        )
+        .subcommand(
+            Command::new("bench")
+                .about("Times the implementations against each other, without cargo bench")
+                .arg(
+                    Arg::new("algorithm")
+                        .required(true)
+                        .value_parser(["expo", "bin", "lcd"]),
+                )
+                .arg(
+                    Arg::new("inputs")
+                        .long("inputs")
+                        .short('i')
+                        .required(true)
+                        .action(ArgAction::Append)
+                        .value_delimiter(',')
+                        .allow_hyphen_values(true)
+                        .help(
+                            "Comma separated, e.g. 2.5^-3,2^10 for expo, -11,5 for bin \
+                             or 21/45,2(3/6) for lcd",
+                        ),
+                )
+                .arg(
+                    Arg::new("samples")
+                        .long("samples")
+                        .short('n')
+                        .value_parser(value_parser!(u64).range(1..=1_000_000))
+                        .default_value("51")
+                        .help("How many timed samples to take per implementation"),
+                )
+                .arg(
+                    Arg::new("warmup")
+                        .long("warmup")
+                        .value_parser(value_parser!(u64).range(..=1_000_000))
+                        .default_value("5")
+                        .help("How many samples to take and throw away first"),
+                ),
+        )
+        .subcommand(
+            Command::new("stack")
+                .about("Finds how deep each recursive implementation can go before the stack runs out")
+                .arg(
+                    Arg::new("algorithms")
+                        .num_args(0..)
+                        .value_parser(["expo", "bin", "lcd"])
+                        .help("Which algorithms to probe, all of them by default"),
+                )
+                .arg(
+                    Arg::new("stack-size")
+                        .long("stack-size")
+                        .short('s')
+                        .value_parser(stack::size)
+                        .default_value("2M")
+                        .help("The stack to probe with, e.g. 8M or 512K"),
+                ),
+        )
+        .subcommand(
+            Command::new(stack::CHILD)
+                .hide(true)
+                .arg(Arg::new("algorithm").required(true))
+                .arg(Arg::new("n").required(true).value_parser(value_parser!(u64)))
+                .arg(
+                    Arg::new("stack-size")
+                        .long("stack-size")
+                        .required(true)
+                        .value_parser(value_parser!(usize)),
+                ),
+        )
+        .subcommand(
+            Command::new("repl")
+                .about("Runs commands typed in one line at a time, saving each result"),
+        )
+}
+
+//---------------------------------------------------------------------------//
+
+/// Reads the input for an algorithm's subcommand.
+fn input(name: &str, matches: &ArgMatches) -> Result<Input> {
+    Ok(match name {
+        "expo" => Input::Expo {
+            x: *matches.get_one::<f64>("x").context("missing x")?,
+            e: *matches.get_one::<i32>("e").context("missing e")?,
+        },
+        "bin" => Input::Bin(*matches.get_one::<i64>("x").context("missing x")?),
+        "lcd" => Input::Lcd(*matches.get_one::<Expr>("expr").context("missing expr")?),
+        _ => bail!("{:?} is not an algorithm", name),
+    })
+}
+
+/// Reads the `--method` flag, which every subcommand has.
+fn method(matches: &ArgMatches) -> Result<Method> {
+    let name = matches
+        .get_one::<String>("method")
+        .context("missing method")?;
+    Method::from_name(name).with_context(|| format!("unknown method {:?}", name))
+}
+
+/// Reads the `--method` flag, unless it was left as the default, in which case
+/// `default` is used instead.
+fn method_or(matches: &ArgMatches, default: Method) -> Result<Method> {
+    if given(matches, "method") {
+        method(matches)
+    } else {
+        Ok(default)
+    }
+}
+
+/// Whether a flag was actually passed, rather than left as its default.
+fn given(matches: &ArgMatches, id: &str) -> bool {
+    !matches!(
+        matches.value_source(id),
+        Some(ValueSource::DefaultValue) | None
+    )
+}
+
+/// Reads the `--format` flag, which every subcommand has.
+fn format(matches: &ArgMatches) -> Result<Format> {
+    let name = matches
+        .get_one::<String>("format")
+        .context("missing format")?;
+    Format::from_name(name).with_context(|| format!("unknown format {:?}", name))
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+fn main() -> Result<()> {
+    let matches = cli().get_matches();
+    let (name, sub) = matches.subcommand().context("missing command")?;
+
+    if sub.get_flag("explain")
+        && ["repl", "batch", "bench", "compare", "trace", "stack"].contains(&name)
+    {
+        bail!(
+            "--explain can't be used with {}, only a single algorithm",
+            name
+        );
+    }
+
+    match name {
+        "repl" => repl::run(
+            method(sub)?,
+            format(sub)?,
+            std::io::stdin().lock(),
+            std::io::stdout(),
+        ),
+        "batch" => run_batch(sub),
+        "trace" => run_trace(sub),
+        "bench" => run_bench(sub),
+        "stack" => run_stack(sub),
+        stack::CHILD => run_stack_child(sub),
+        "compare" => run_compare(sub),
+        _ => run_algorithm(name, sub),
+    }
+}
+
+/// Runs every line of a file, or stdin, as its own command.
+fn run_batch(sub: &ArgMatches) -> Result<()> {
+    let file = sub.get_one::<String>("file").context("missing file")?;
+    let threads = *sub.get_one::<u64>("threads").context("missing threads")? as usize;
+    let printer = Printer::new(format(sub)?);
+    let out = std::io::stdout().lock();
+
+    let failed = if file == "-" {
+        batch::run(std::io::stdin().lock(), method(sub)?, threads, printer, out)?
+    } else {
+        let f = std::fs::File::open(file).with_context(|| format!("couldn't open {}", file))?;
+        let input = std::io::BufReader::new(f);
+        batch::run(input, method(sub)?, threads, printer, out)?
+    };
+
+    if failed > 0 {
+        bail!("{} lines failed or disagreed", failed);
+    }
+    Ok(())
+}
+
+/// Writes the calls the recursive implementation makes for one input.
+fn run_trace(sub: &ArgMatches) -> Result<()> {
+    if format(sub)? != Format::Text {
+        bail!("trace writes a text tree, or a DOT graph with --dot");
+    }
+    let (name, algorithm) = sub.subcommand().context("missing algorithm")?;
+    if given(algorithm, "method") {
+        bail!("--method can't be used with trace, it always runs the recursive implementation");
+    }
+    let max_depth = match sub.get_one::<u64>("max-depth") {
+        Some(depth) => *depth as usize,
+        None => usize::MAX,
+    };
+    let dot = sub.get_flag("dot");
+    trace::write(
+        input(name, algorithm)?,
+        dot,
+        max_depth,
+        &mut std::io::stdout().lock(),
+    )
+}
+
+/// Times the implementations on each of the inputs.
+fn run_bench(sub: &ArgMatches) -> Result<()> {
+    let algorithm = sub
+        .get_one::<String>("algorithm")
+        .context("missing algorithm")?;
+    let settings = bench::Settings {
+        warmup: *sub.get_one::<u64>("warmup").context("missing warmup")? as usize,
+        samples: *sub.get_one::<u64>("samples").context("missing samples")? as usize,
+    };
+
+    let inputs = sub
+        .get_many::<String>("inputs")
+        .context("missing inputs")?
+        .map(|s| bench::input(algorithm, s))
+        .collect::<Result<Vec<_>>>()?;
+    let benches = inputs
+        .into_iter()
+        .map(|input| bench::bench(input, method(sub)?, settings))
+        .collect::<Result<Vec<_>>>()?;
+    bench::write(&benches, format(sub)?, &mut std::io::stdout())?;
+    Ok(())
+}
+
+/// Finds how deep each recursive implementation can go before overflowing
+/// the stack.
+fn run_stack(sub: &ArgMatches) -> Result<()> {
+    if given(sub, "method") {
+        bail!("--method can't be used with stack, it always runs the recursive implementation");
+    }
+    let algorithms = match sub.get_many::<String>("algorithms") {
+        Some(names) => names.map(String::as_str).collect::<Vec<_>>(),
+        None => vec!["expo", "bin", "lcd"],
+    };
+    let stack_size = *sub
+        .get_one::<usize>("stack-size")
+        .context("missing stack size")?;
+
+    let reports = stack::run(&algorithms, stack_size)?;
+    stack::write(&reports, format(sub)?, &mut std::io::stdout())?;
+    Ok(())
+}
+
+/// One probe of `stack`, run in its own process so it's fine for it to
+/// overflow.
+fn run_stack_child(sub: &ArgMatches) -> Result<()> {
+    let algorithm = sub
+        .get_one::<String>("algorithm")
+        .context("missing algorithm")?;
+    let n = *sub.get_one::<u64>("n").context("missing n")?;
+    let stack_size = *sub
+        .get_one::<usize>("stack-size")
+        .context("missing stack size")?;
+    stack::child(algorithm, n, stack_size)
+}
+
+/// Checks the implementations agree over a range of inputs.
+fn run_compare(sub: &ArgMatches) -> Result<()> {
+    let algorithm = sub
+        .get_one::<String>("algorithm")
+        .context("missing algorithm")?;
+    let range = sub
+        .get_one::<RangeInclusive<i64>>("range")
+        .context("missing range")?;
+    let bases = sub
+        .get_many::<f64>("base")
+        .context("missing base")?
+        .copied()
+        .collect::<Vec<_>>();
+    let denominators = sub
+        .get_one::<RangeInclusive<i64>>("denominators")
+        .context("missing denominators")?;
+
+    let inputs = compare::inputs(algorithm, range.clone(), &bases, denominators.clone())?;
+    let summary = compare::compare(&inputs, method(sub)?)?;
+    compare::write(&summary, format(sub)?, &mut std::io::stdout())?;
+
+    if !summary.diffs.is_empty() {
+        bail!("{} disagreements", summary.diffs.len());
+    }
+    Ok(())
+}
+
+/// Runs a single algorithm on one input.
+fn run_algorithm(name: &str, sub: &ArgMatches) -> Result<()> {
+    let explain = sub.get_flag("explain");
+    if explain && format(sub)? != Format::Text {
+        bail!("--explain only works with --format text");
+    }
+
+    let report = algorithm::run(input(name, sub)?, method(sub)?)?;
+    Printer::new(format(sub)?).report(&report, &mut std::io::stdout())?;
+    if explain {
+        explain::write(report.input, &mut std::io::stdout())?;
+    }
+
+    if !report.agree() {
+        bail!("the implementations disagree on {}", report.input);
+    }
+    Ok(())
+}
 
 ////////////////////////////////////////////////////////////////////////////////
 
-fn main() {
-    println!("Hello, world!");
+#[cfg(test)]
+mod tests {
+
+    #[test]
+    fn cli() {
+        super::cli().debug_assert();
+
+        let parse = |args: &[&str]| {
+            let matches = super::cli().try_get_matches_from(args)?;
+            let (name, sub) = matches.subcommand().unwrap();
+            Ok::<_, anyhow::Error>((super::input(name, sub)?, super::method(sub)?))
+        };
+
+        let (input, method) = parse(&["recursion-app", "expo", "2.5", "-3"]).unwrap();
+        assert_eq!(input, super::Input::Expo { x: 2.5, e: -3 });
+        assert_eq!(method, super::Method::Both);
+
+        let (input, method) =
+            parse(&["recursion-app", "bin", "-11", "--method", "iterative"]).unwrap();
+        assert_eq!(input, super::Input::Bin(-11));
+        assert_eq!(method, super::Method::Iterative);
+
+        let (input, _) = parse(&["recursion-app", "-m", "recursive", "lcd", "-21/45"]).unwrap();
+        assert_eq!(input.to_string(), "lcd 0(-7/15)");
+        let (input, _) = parse(&["recursion-app", "lcd", "0.1(6)"]).unwrap();
+        assert_eq!(input.to_string(), "lcd 0(1/6)");
+
+        assert!(parse(&["recursion-app", "expo", "2.5"]).is_err());
+        assert!(parse(&["recursion-app", "expo", "2.5", "0.5"]).is_err());
+        assert!(parse(&["recursion-app", "bin", "eleven"]).is_err());
+        assert!(parse(&["recursion-app", "lcd", "1(2)"]).is_err());
+
+        assert!(parse(&["recursion-app", "lcd", "1/0"]).is_err());
+        assert!(parse(&["recursion-app", "lcd", "9223372036854775807(3/2)"]).is_err());
+        assert!(parse(&["recursion-app", "lcd", "-9223372036854775808/-1"]).is_err());
+        assert!(parse(&["recursion-app", "lcd", "1/2", "-m", "sideways"]).is_err());
+        assert!(parse(&["recursion-app", "gcd", "1", "2"]).is_err());
+
+        let matches = super::cli()
+            .try_get_matches_from(["recursion-app", "trace", "--dot", "-d", "3", "bin", "-11"])
+            .unwrap();
+        let (_, trace) = matches.subcommand().unwrap();
+        assert!(trace.get_flag("dot"));
+        assert_eq!(trace.get_one::<u64>("max-depth"), Some(&3));
+        let (name, sub) = trace.subcommand().unwrap();
+        assert_eq!(super::input(name, sub).unwrap(), super::Input::Bin(-11));
+        assert!(super::cli()
+            .try_get_matches_from(["recursion-app", "trace", "-d", "0", "bin", "1"])
+            .is_err());
+
+        // --method is seen wherever it's put, so trace can reject it
+        let algorithm = |args: &[&str]| {
+            let matches = super::cli().try_get_matches_from(args).unwrap();
+            let (_, trace) = matches.subcommand().unwrap();
+            super::given(trace.subcommand().unwrap().1, "method")
+        };
+        assert!(!algorithm(&["recursion-app", "trace", "bin", "1"]));
+        assert!(algorithm(&[
+            "recursion-app",
+            "-m",
+            "both",
+            "trace",
+            "bin",
+            "1"
+        ]));
+        assert!(algorithm(&[
+            "recursion-app",
+            "trace",
+            "-m",
+            "both",
+            "bin",
+            "1"
+        ]));
+        assert!(algorithm(&[
+            "recursion-app",
+            "trace",
+            "bin",
+            "1",
+            "-m",
+            "both"
+        ]));
+
+        // and stack rejects it the same way
+        let stack = |args: &[&str]| {
+            let matches = super::cli().try_get_matches_from(args).unwrap();
+            super::given(matches.subcommand().unwrap().1, "method")
+        };
+        assert!(!stack(&["recursion-app", "stack"]));
+        assert!(stack(&["recursion-app", "-m", "iterative", "stack"]));
+        assert!(stack(&["recursion-app", "stack", "expo", "-m", "both"]));
+
+        let matches = super::cli()
+            .try_get_matches_from([
+                "recursion-app",
+                "bench",
+                "lcd",
+                "--inputs",
+                "-21/45,2(3/6)",
+                "-m",
+                "iterative",
+                "-i",
+                "1/2",
+            ])
+            .unwrap();
+        let (_, bench) = matches.subcommand().unwrap();
+        let inputs = bench.get_many::<String>("inputs").unwrap();
+        assert_eq!(inputs.collect::<Vec<_>>(), ["-21/45", "2(3/6)", "1/2"]);
+        assert_eq!(super::method(bench).unwrap(), super::Method::Iterative);
+    }
 }
 
 ////////////////////////////////////////////////////////////////////////////////
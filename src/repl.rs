@@ -0,0 +1,305 @@
+////////////////////////////////////////////////////////////////////////////////
+
+use crate::algorithm::{self, Method, Report};
+use crate::explain;
+use crate::format::{Format, Printer};
+use anyhow::{anyhow, bail, Context, Result};
+use std::io::{BufRead, Write};
+
+////////////////////////////////////////////////////////////////////////////////
+
+const HELP: &str = "\
+Type a command to run it, for example:
+  expo 2.5 -3
+  bin -11 --method iterative
+  lcd 21/45 --explain
+Each result is saved, and `$1`, `$2`, ... can be used in place of an argument.
+Other commands:
+  history  list the saved results
+  help     show this message, or `help <command>` for its arguments
+  quit     leave the repl";
+
+/// Commands that only make sense from the command line.
+const NOT_IN_REPL: [&str; 7] = [
+    "repl",
+    "batch",
+    "trace",
+    "stack",
+    crate::stack::CHILD,
+    "bench",
+    "compare",
+];
+
+/// Fails for a command that can't be run from the repl.
+fn available(name: &str) -> Result<()> {
+    if NOT_IN_REPL.contains(&name) {
+        bail!(
+            "{} isn't available in the repl, run it from the command line instead",
+            name
+        );
+    }
+    Ok(())
+}
+
+//---------------------------------------------------------------------------//
+
+/// Everything the repl remembers between lines.
+struct Session {
+    /// Used when a line doesn't pick its own `--method`.
+    method: Method,
+    /// Every successful result, `$1` first.
+    history: Vec<Report>,
+    format: Format,
+    printer: Printer,
+}
+
+impl Session {
+    /// Replaces each `$k` with the result it refers to, written so it can be
+    /// read back in as an argument.
+    fn substitute(&self, word: &str) -> Result<String> {
+        let Some(k) = word.strip_prefix('$') else {
+            return Ok(word.to_string());
+        };
+
+        let report = k
+            .parse::<usize>()
+            .ok()
+            .and_then(|k| self.history.get(k.checked_sub(1)?))
+            .with_context(|| {
+                format!(
+                    "there is no result {}, the history has {} entries",
+                    word,
+                    self.history.len()
+                )
+            })?;
+        let first = report.results.first().context("no results")?;
+
+        Ok(first.output.argument())
+    }
+
+    /// Runs one line, writing whatever it shows to `out`.
+    /// Returns false once the user asks to leave.
+    fn line(&mut self, line: &str, out: &mut impl Write) -> Result<bool> {
+        let words = line
+            .split_whitespace()
+            .map(|word| self.substitute(word))
+            .collect::<Result<Vec<_>>>()?;
+
+        match words.first().map(String::as_str) {
+            None => return Ok(true),
+            Some("quit" | "exit") => return Ok(false),
+            Some("help") if words.len() == 1 => {
+                writeln!(out, "{}", HELP)?;
+                return Ok(true);
+            }
+            Some(name) if NOT_IN_REPL.contains(&name) => available(name)?,
+            Some("history") => {
+                for (i, report) in self.history.iter().enumerate() {
+                    writeln!(
+                        out,
+                        "${} = {} -> {}",
+                        i + 1,
+                        report.input,
+                        report.results[0].output
+                    )?;
+                }
+                return Ok(true);
+            }
+            _ => {}
+        }
+
+        // the same subcommands as the command line, so the same validation
+        let matches = match crate::cli().try_get_matches_from(
+            std::iter::once("recursion-app").chain(words.iter().map(String::as_str)),
+        ) {
+            Ok(matches) => matches,
+            Err(e) => {
+                // help requests come back as errors too, but aren't mistakes
+                write!(out, "{}", e.render())?;
+                return Ok(true);
+            }
+        };
+        let (name, sub) = matches.subcommand().context("missing command")?;
+        available(name)?;
+
+        // a line can pick its own format, otherwise the session's is used
+        let format = if crate::given(sub, "format") {
+            crate::format(sub)?
+        } else {
+            self.format
+        };
+        let explain = sub.get_flag("explain");
+        if explain && format != Format::Text {
+            bail!("--explain only works with --format text");
+        }
+
+        // a panic is just another mistake, it shouldn't end the session
+        let input = crate::input(name, sub)?;
+        let method = crate::method_or(sub, self.method)?;
+        let report = std::panic::catch_unwind(|| algorithm::run(input, method))
+            .map_err(|_| anyhow!("{} panicked", input))??;
+        if format == self.format {
+            self.printer.report(&report, out)?;
+        } else {
+            Printer::new(format).report(&report, out)?;
+        }
+        if explain {
+            explain::write(report.input, out)?;
+        }
+
+        self.history.push(report);
+        writeln!(out, "saved as ${}", self.history.len())?;
+        Ok(true)
+    }
+}
+
+//---------------------------------------------------------------------------//
+
+/// Reads commands from `input` one line at a time until it runs out or the
+/// user quits, showing each result and saving it to the history.
+/// Mistakes are reported without leaving the repl.
+///
+/// Inputs:
+/// - `method: Method` Which implementations to run by default.
+/// - `format: Format` How to write each result.
+/// - `input: impl BufRead`
+/// - `out: impl Write`
+///
+/// Outputs: `Result<()>`
+/// Only fails if reading or writing does.
+pub fn run(method: Method, format: Format, input: impl BufRead, mut out: impl Write) -> Result<()> {
+    let mut session = Session {
+        method,
+        history: vec![],
+        format,
+        printer: Printer::new(format),
+    };
+
+    writeln!(
+        out,
+        "Type `help` for a list of commands, or `quit` to leave."
+    )?;
+    write!(out, "> ")?;
+    out.flush()?;
+
+    for line in input.lines() {
+        match session.line(&line?, &mut out) {
+            Ok(true) => {}
+            Ok(false) => return Ok(()),
+            Err(e) => writeln!(out, "error: {:#}", e)?,
+        }
+        write!(out, "> ")?;
+        out.flush()?;
+    }
+
+    writeln!(out)?;
+    Ok(())
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use super::{Format, Method};
+
+    /// Runs a script through the repl, returning everything it wrote.
+    fn script(method: Method, lines: &[&str]) -> String {
+        let mut out = vec![];
+        let input = lines.join("\n");
+        super::run(method, Format::Text, input.as_bytes(), &mut out).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn run() {
+        let out = script(
+            Method::Both,
+            &[
+                "expo 2.5 -3",
+                "",
+                "lcd 21/45",
+                "expo $1 -1",
+                "bin -11",
+                "bin $4",
+            ],
+        );
+        assert!(out.contains("recursive: 0.064\niterative: 0.064\nsaved as $1\n"));
+        assert!(out.contains("recursive: 0(7/15)\niterative: 0(7/15)\nsaved as $2\n"));
+        assert!(out.contains("recursive: 15.625\niterative: 15.625\nsaved as $3\n"));
+        assert!(out.contains("recursive: -1011\niterative: -1011\nsaved as $4\n"));
+        assert!(out.contains("saved as $5\n"));
+
+        let out = script(Method::Both, &["bin 5 --explain"]);
+        assert!(out.contains("iterative: 101\n1. Working down from bit 62"));
+        assert!(out.contains("4. Bit 1 is worth 2: 2 > 1, bit 1 is off.\n"));
+
+        let out = script(
+            Method::Iterative,
+            &["lcd 2(4/6)", "lcd $1 -m both", "history"],
+        );
+        assert!(out.contains("iterative: 2(2/3)\nsaved as $1\n"));
+        assert!(out.contains("recursive: 2(2/3)\niterative: 2(2/3)\nsaved as $2\n"));
+        assert!(out.contains("$1 = lcd 2(2/3) -> 2(2/3)\n$2 = lcd 2(2/3) -> 2(2/3)\n"));
+
+        // a line's own --format is only used for that line
+        let out = script(
+            Method::Iterative,
+            &[
+                "bin 5 -f json",
+                "bin 6",
+                "bin 7 --format csv",
+                "bin 8 -f json --explain",
+            ],
+        );
+        assert!(out.contains("{\"agree\":true,\"algorithm\":\"bin\",\"input\":{\"x\":5}"));
+        assert!(out.contains("iterative: 110\nsaved as $2\n"));
+        assert!(
+            out.contains("algorithm,input,implementation,output,nanos,agree,line,error\nbin,7,")
+        );
+        assert!(out.contains("error: --explain only works with --format text\n"));
+    }
+
+    #[test]
+    fn errors() {
+        let out = script(
+            Method::Both,
+            &[
+                "expo 2",
+                "bin $1",
+                "lcd 1/0",
+                "lcd 9223372036854775807(3/2)",
+                "gcd 4 6",
+                "repl",
+                "trace",
+                "trace bin 5",
+                "-m both stack",
+                "compare expo",
+                "bench",
+                "expo 2 100000",
+                "bin 5",
+                "quit",
+                "bin 6",
+            ],
+        );
+
+        // every mistake is reported and the repl carries on
+        assert!(out.contains("error: the following required arguments were not provided"));
+        assert!(out.contains("error: there is no result $1, the history has 0 entries\n"));
+        assert!(out.contains("error: invalid value '1/0' for '<expr>': zero denominator"));
+        assert!(out.contains("'9223372036854775807(3/2)' for '<expr>': number too large"));
+        assert!(out.contains("error: unrecognized subcommand 'gcd'"));
+        assert!(out.contains("error: repl isn't available in the repl, run it"));
+        assert!(out.contains("error: trace isn't available in the repl, run it"));
+        assert!(out.contains("error: stack isn't available in the repl, run it"));
+        assert!(out.contains("error: compare isn't available in the repl, run it"));
+        assert!(out.contains("error: bench isn't available in the repl, run it"));
+        assert!(!out.contains("is not an algorithm"));
+        assert!(out.contains("error: exponent 100000 is too large"));
+        assert!(out.contains("iterative: 101\nsaved as $1\n"));
+
+        // nothing after quitting
+        assert!(!out.contains("110"));
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
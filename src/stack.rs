@@ -0,0 +1,373 @@
+////////////////////////////////////////////////////////////////////////////////
+
+use crate::algorithm::{Implementation, Input};
+use crate::format::{csv_field, Format, SCHEMA_VERSION};
+use anyhow::{bail, Result};
+use recursion_lib::lcd::{self, Expr};
+use recursion_lib::stack::{self, Probe};
+use serde_json::json;
+use std::hint::black_box;
+use std::io::{self, Write};
+use std::process::{Command, Stdio};
+
+////////////////////////////////////////////////////////////////////////////////
+
+/// The hidden subcommand each attempt runs in, since running out of stack
+/// aborts the whole process.
+pub const CHILD: &str = "stack-child";
+
+//---------------------------------------------------------------------------//
+
+/// An algorithm's inputs, numbered so that a larger number never recurses
+/// less deeply.
+pub struct Family {
+    pub algorithm: &'static str,
+    pub hi: u64,
+    pub input: fn(u64) -> Input,
+    /// How many calls deep the recursive implementation goes for an input.
+    pub depth: fn(u64) -> u64,
+}
+
+pub fn family(algorithm: &str) -> Result<Family> {
+    Ok(match algorithm {
+        "expo" => Family {
+            algorithm: "expo",
+            hi: i32::MAX as u64,
+            input: |n| Input::Expo {
+                x: 1.0,
+                e: n as i32,
+            },
+            // e down to 0
+            depth: |n| n + 1,
+        },
+        "bin" => Family {
+            algorithm: "bin",
+            hi: i64::MAX as u64,
+            input: |n| Input::Bin(n as i64),
+            // `inner` is a tail call, so it reuses its frame for every bit
+            depth: |_| 2,
+        },
+        "lcd" => Family {
+            algorithm: "lcd",
+            hi: i64::MAX as u64 - 1,
+            input: |n| Input::Lcd(lcd_input(n)),
+            // `recursive` and then each `try_recursive`
+            depth: |n| lcd::recursive_traced(lcd_input(n), usize::MAX).1.depth() as u64 + 1,
+        },
+        _ => bail!("{:?} is not an algorithm", algorithm),
+    })
+}
+
+/// 1(n/-(n+1)), which is made improper, then has its sign moved and is then
+/// already simplified, however large n is.
+fn lcd_input(n: u64) -> Expr {
+    Expr::new(1, n as i64, -(n as i64) - 1)
+}
+
+//---------------------------------------------------------------------------//
+
+/// Runs the recursive implementation on one input with a stack of
+/// `stack_size` bytes. This is what [`CHILD`] does, and it aborts the process
+/// if the stack runs out.
+pub fn child(algorithm: &str, n: u64, stack_size: usize) -> Result<()> {
+    let input = (family(algorithm)?.input)(n);
+    stack::on_stack(stack_size, move || {
+        black_box(black_box(input).run(Implementation::Recursive));
+    })?;
+    Ok(())
+}
+
+/// Whether an input completes, by running it in a new copy of this program.
+fn completes(algorithm: &str, stack_size: usize, n: u64) -> io::Result<bool> {
+    let status = Command::new(std::env::current_exe()?)
+        .args([
+            CHILD,
+            algorithm,
+            &n.to_string(),
+            "--stack-size",
+            &stack_size.to_string(),
+        ])
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()?;
+    Ok(status.success())
+}
+
+//---------------------------------------------------------------------------//
+
+/// The probes for one algorithm, with a stack of the given size and one of
+/// twice that, so the size of each call can be estimated.
+pub struct Report {
+    pub algorithm: &'static str,
+    pub input: Option<Input>,
+    pub probe: Probe,
+    pub bytes_per_frame: Option<f64>,
+}
+
+/// Probes the recursive implementation of each algorithm.
+pub fn run(algorithms: &[&str], stack_size: usize) -> Result<Vec<Report>> {
+    algorithms
+        .iter()
+        .map(|algorithm| {
+            let family = family(algorithm)?;
+
+            // the search wants a plain bool, so hang on to the first error
+            let mut error = None;
+            let mut attempt = |stack_size, n| match completes(family.algorithm, stack_size, n) {
+                Ok(done) => done,
+                Err(e) => {
+                    error.get_or_insert(e);
+                    false
+                }
+            };
+            let probe = stack::probe(stack_size, 0, family.hi, family.depth, &mut attempt);
+            let double = stack::probe(stack_size * 2, 0, family.hi, family.depth, &mut attempt);
+            if let Some(e) = error {
+                bail!("couldn't run {}: {}", family.algorithm, e);
+            }
+
+            Ok(Report {
+                algorithm: family.algorithm,
+                input: probe.largest.map(family.input),
+                probe,
+                bytes_per_frame: probe.bytes_per_frame(&double),
+            })
+        })
+        .collect()
+}
+
+//---------------------------------------------------------------------------//
+
+/// Reads a size in bytes, with an optional `K`, `M` or `G` suffix.
+/// (i.e. `8M` -> 8388608 )
+pub fn size(s: &str) -> Result<usize, String> {
+    let (digits, scale) = match s.char_indices().last() {
+        Some((i, 'K' | 'k')) => (&s[..i], 1 << 10),
+        Some((i, 'M' | 'm')) => (&s[..i], 1 << 20),
+        Some((i, 'G' | 'g')) => (&s[..i], 1 << 30),
+        _ => (s, 1),
+    };
+    let size = digits
+        .parse::<usize>()
+        .ok()
+        .and_then(|n| n.checked_mul(scale))
+        .ok_or_else(|| format!("expected a size like 8M, 512K or 65536, not {:?}", s))?;
+    if size < 64 << 10 {
+        return Err("the stack needs to be at least 64K".to_string());
+    }
+    if size > 64 << 30 {
+        return Err("the stack can be at most 64G".to_string());
+    }
+    Ok(size)
+}
+
+/// Writes a size in bytes the way [`size`] reads it, where it's exact.
+fn show_size(bytes: usize) -> String {
+    for (suffix, scale) in [("G", 1 << 30), ("M", 1 << 20), ("K", 1 << 10)] {
+        if bytes.is_multiple_of(scale) {
+            return format!("{}{}", bytes / scale, suffix);
+        }
+    }
+    bytes.to_string()
+}
+
+/// Writes the reports out, as a table for text, an object per algorithm for
+/// JSON and a row per algorithm for CSV.
+pub fn write(reports: &[Report], format: Format, out: &mut impl Write) -> io::Result<()> {
+    let largest = |r: &Report| match (r.input, r.probe.unbounded) {
+        (Some(input), true) => format!("{} (no limit found)", input),
+        (Some(input), false) => input.to_string(),
+        (None, _) => "none".to_string(),
+    };
+
+    match format {
+        Format::Text => {
+            let mut rows = vec![[
+                "algorithm".to_string(),
+                "stack".to_string(),
+                "largest input".to_string(),
+                "depth".to_string(),
+                "bytes per frame".to_string(),
+            ]];
+            for r in reports {
+                rows.push([
+                    r.algorithm.to_string(),
+                    show_size(r.probe.stack_size),
+                    largest(r),
+                    r.probe.depth.map(|d| d.to_string()).unwrap_or_default(),
+                    match r.bytes_per_frame {
+                        Some(bytes) => format!("~{:.0}", bytes),
+                        None => "-".to_string(),
+                    },
+                ]);
+            }
+
+            // pad each column to its widest cell
+            let widths = (0..5)
+                .map(|i| rows.iter().map(|r| r[i].len()).max().unwrap_or(0))
+                .collect::<Vec<_>>();
+            for row in &rows {
+                let cells = row
+                    .iter()
+                    .zip(&widths)
+                    .map(|(cell, width)| format!("{:<width$}", cell, width = width))
+                    .collect::<Vec<_>>();
+                writeln!(out, "{}", cells.join("  ").trim_end())?;
+            }
+            Ok(())
+        }
+        Format::Json => {
+            for r in reports {
+                let value = json!({
+                    "schema": SCHEMA_VERSION,
+                    "algorithm": r.algorithm,
+                    "stack_size": r.probe.stack_size,
+                    "largest": r.input.map(|i| i.arguments()),
+                    "unbounded": r.probe.unbounded,
+                    "depth": r.probe.depth,
+                    "bytes_per_frame": r.bytes_per_frame,
+                });
+                writeln!(out, "{}", value)?;
+            }
+            Ok(())
+        }
+        Format::Csv => {
+            writeln!(
+                out,
+                "algorithm,stack_size,largest,unbounded,depth,bytes_per_frame"
+            )?;
+            for r in reports {
+                let row = [
+                    r.algorithm.to_string(),
+                    r.probe.stack_size.to_string(),
+                    r.input.map(|i| i.arguments()).unwrap_or_default(),
+                    r.probe.unbounded.to_string(),
+                    r.probe.depth.map(|d| d.to_string()).unwrap_or_default(),
+                    r.bytes_per_frame.map(|b| b.to_string()).unwrap_or_default(),
+                ];
+                let row = row.map(|f| csv_field(&f));
+                writeln!(out, "{}", row.join(","))?;
+            }
+            Ok(())
+        }
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use super::Report;
+    use crate::algorithm::Input;
+    use crate::format::Format;
+    use recursion_lib::stack::Probe;
+
+    #[test]
+    fn family() {
+        let expo = super::family("expo").unwrap();
+        assert_eq!((expo.input)(5), Input::Expo { x: 1.0, e: 5 });
+        assert_eq!((expo.depth)(5), 6);
+        assert_eq!(
+            (expo.input)(expo.hi),
+            Input::Expo {
+                x: 1.0,
+                e: i32::MAX
+            }
+        );
+
+        let bin = super::family("bin").unwrap();
+        assert_eq!((bin.input)(bin.hi), Input::Bin(i64::MAX));
+
+        // every lcd input takes the same path, and the largest still fits
+        let lcd = super::family("lcd").unwrap();
+        assert_eq!((lcd.input)(2).to_string(), "lcd 1(2/-3)");
+        assert_eq!((lcd.depth)(2), 4);
+        assert_eq!((lcd.depth)(lcd.hi), 4);
+        assert!((lcd.input)(lcd.hi)
+            .validate(crate::algorithm::Method::Both)
+            .is_ok());
+
+        assert!(super::family("gcd").is_err());
+    }
+
+    #[test]
+    fn child() {
+        // in this process the stack is big enough, so it just completes
+        super::child("expo", 1000, 64 * 1024 * 1024).unwrap();
+        super::child("lcd", 1 << 40, 1024 * 1024).unwrap();
+        assert!(super::child("gcd", 1, 1024 * 1024).is_err());
+    }
+
+    #[test]
+    fn size() {
+        assert_eq!(super::size("8M"), Ok(8 << 20));
+        assert_eq!(super::size("512k"), Ok(512 << 10));
+        assert_eq!(super::size("1G"), Ok(1 << 30));
+        assert_eq!(super::size("65536"), Ok(65536));
+        assert!(super::size("1K").is_err());
+        assert!(super::size("65G").is_err());
+        assert!(super::size("M").is_err());
+        assert!(super::size("8X").is_err());
+        assert_eq!(super::show_size(8 << 20), "8M");
+        assert_eq!(super::show_size(100_000), "100000");
+    }
+
+    #[test]
+    fn write() {
+        let reports = [
+            Report {
+                algorithm: "expo",
+                input: Some(Input::Expo { x: 1.0, e: 43_689 }),
+                probe: Probe {
+                    stack_size: 2 << 20,
+                    largest: Some(43_689),
+                    depth: Some(43_690),
+                    unbounded: false,
+                },
+                bytes_per_frame: Some(48.2),
+            },
+            Report {
+                algorithm: "bin",
+                input: Some(Input::Bin(i64::MAX)),
+                probe: Probe {
+                    stack_size: 2 << 20,
+                    largest: Some(i64::MAX as u64),
+                    depth: Some(2),
+                    unbounded: true,
+                },
+                bytes_per_frame: None,
+            },
+        ];
+
+        let mut out = vec![];
+        super::write(&reports, Format::Text, &mut out).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "\
+algorithm  stack  largest input                             depth  bytes per frame
+expo       2M     expo 1 43689                              43690  ~48
+bin        2M     bin 9223372036854775807 (no limit found)  2      -
+"
+        );
+
+        let mut out = vec![];
+        super::write(&reports, Format::Json, &mut out).unwrap();
+        let first = String::from_utf8(out).unwrap();
+        let first = serde_json::from_str::<serde_json::Value>(first.lines().next().unwrap());
+        let first = first.unwrap();
+        assert_eq!(first["largest"], "1 43689");
+        assert_eq!(first["depth"], 43_690);
+        assert_eq!(first["unbounded"], false);
+
+        let mut out = vec![];
+        super::write(&reports, Format::Csv, &mut out).unwrap();
+        let out = String::from_utf8(out).unwrap();
+        assert_eq!(
+            out.lines().nth(2),
+            Some("bin,2097152,9223372036854775807,true,2,")
+        );
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
@@ -0,0 +1,106 @@
+////////////////////////////////////////////////////////////////////////////////
+
+use crate::algorithm::{Input, Method};
+use anyhow::{anyhow, Result};
+use recursion_lib::trace::Call;
+use recursion_lib::{expo, int_to_bin, lcd};
+use std::io::Write;
+
+////////////////////////////////////////////////////////////////////////////////
+
+/// The stack given to the thread doing the tracing. Each traced call holds on
+/// to what the calls below it handed back, so it needs far more room than the
+/// plain recursion does, especially in debug builds.
+const STACK_SIZE: usize = 256 * 1024 * 1024;
+
+/// Runs the recursive implementation of an algorithm, recording the first
+/// `max_depth` levels of calls it makes along with what they returned, and
+/// counting the rest.
+pub fn trace(input: Input, max_depth: usize) -> Result<Call> {
+    input.validate(Method::Recursive)?;
+
+    Ok(match input {
+        Input::Expo { x, e } => expo::recursive_traced(x, e, max_depth).1,
+        Input::Bin(x) => int_to_bin::recursive_traced(x, max_depth).1,
+        Input::Lcd(e) => lcd::recursive_traced(e, max_depth).1,
+    })
+}
+
+/// Traces an input and writes the calls as an indented tree, or as a Graphviz
+/// DOT graph, showing at most `max_depth` levels of calls.
+/// Done on its own thread with a larger stack, since tracing recurses once per
+/// call, even for the calls too deep to be recorded.
+pub fn write(input: Input, dot: bool, max_depth: usize, out: &mut impl Write) -> Result<()> {
+    let text = std::thread::Builder::new()
+        .stack_size(STACK_SIZE)
+        .spawn(move || {
+            let call = trace(input, max_depth)?;
+            Ok::<_, anyhow::Error>(if dot {
+                call.to_dot(max_depth)
+            } else {
+                call.to_text(max_depth)
+            })
+        })?
+        .join()
+        .map_err(|_| anyhow!("tracing {} panicked", input))??;
+
+    out.write_all(text.as_bytes())?;
+    Ok(())
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use crate::algorithm::Input;
+    use recursion_lib::lcd::Expr;
+
+    fn render(input: Input, dot: bool, max_depth: usize) -> String {
+        let mut out = vec![];
+        super::write(input, dot, max_depth, &mut out).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn trace() {
+        assert_eq!(
+            render(Input::Expo { x: 2.5, e: -2 }, false, usize::MAX),
+            "\
+recursive(2.5, -2) = 0.16
+  recursive(2.5, 2) = 6.25
+    recursive(2.5, 1) = 2.5
+      recursive(2.5, 0) = 1
+"
+        );
+        assert_eq!(
+            render(Input::Lcd(Expr::new(0, 21, 45)), false, usize::MAX),
+            "try_recursive(0(21/45)) = 0(7/15)\n  try_recursive(0(7/15)) = 0(7/15)\n"
+        );
+
+        // capped, the rest are counted instead
+        let out = render(Input::Bin(-11), false, 3);
+        assert_eq!(out.lines().count(), 4);
+        assert!(out.ends_with("\n      ... 61 more calls\n"));
+
+        let out = render(Input::Expo { x: 2.0, e: 3 }, true, usize::MAX);
+        assert!(out.starts_with("digraph calls {\n"));
+        assert!(out.contains("    n0 [label=\"recursive(2, 3)\\n= 8\"];\n"));
+        assert!(out.contains("    n2 -> n3;\n"));
+        assert!(out.ends_with("}\n"));
+
+        // the same limits as running the recursive implementation
+        assert!(super::trace(Input::Expo { x: 2.0, e: 100_000 }, 1).is_err());
+        assert!(super::trace(Input::Lcd(Expr::new(0, 1, 0)), 1).is_err());
+
+        // as deep as the recursive implementation is allowed to go
+        let out = render(Input::Expo { x: 1.01, e: 10_000 }, false, 2);
+        assert_eq!(out.lines().last(), Some("    ... 9999 more calls"));
+
+        // and only the calls that are shown are kept
+        let call = super::trace(Input::Expo { x: 1.01, e: 1000 }, 2).unwrap();
+        assert_eq!(call.depth(), 2);
+        assert_eq!(call.children[0].hidden, 999);
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////